@@ -0,0 +1,62 @@
+//! Embeds version information for `/about` that Cargo doesn't provide itself.
+
+use std::{env, fs, process::Command};
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+
+    for (name, key) in [("poise", "POISE_VERSION"), ("serenity", "SERENITY_VERSION")] {
+        let version = locked_version(&lock, name).unwrap_or_else(|| "unknown".to_owned());
+        println!("cargo:rustc-env={}={}", key, version);
+    }
+
+    // Builds without a git checkout (e.g. with Nix) can provide the commit
+    // themselves, and otherwise it's just left out.
+    if let Some(commit) = env::var("GIT_COMMIT").ok().or_else(git_commit) {
+        println!("cargo:rustc-env=POMOCOP_GIT_COMMIT={}", commit);
+    }
+}
+
+/// Find the version of the package `name` in the contents of `Cargo.lock`,
+/// along with the commit it was built from if it came from git.
+fn locked_version(lock: &str, name: &str) -> Option<String> {
+    let name_line = format!("name = \"{}\"", name);
+    let package = lock
+        .split("[[package]]")
+        .find(|package| package.lines().any(|line| line == name_line))?;
+
+    let field = |key: &str| {
+        package.lines().find_map(|line| {
+            line.strip_prefix(key)
+                .and_then(|rest| rest.strip_prefix(" = "))
+                .map(|value| value.trim_matches('"'))
+        })
+    };
+
+    let version = field("version")?;
+
+    match field("source")
+        .filter(|source| source.starts_with("git+"))
+        .and_then(|source| source.rsplit_once('#'))
+    {
+        Some((_, rev)) => Some(format!("{} ({})", version, &rev[..rev.len().min(7)])),
+        None => Some(version.to_owned()),
+    }
+}
+
+/// Get the short hash of the commit being built, if this is a git checkout.
+fn git_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|commit| commit.trim().to_owned())
+}
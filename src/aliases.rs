@@ -0,0 +1,396 @@
+//! Plain phrases that can be sent instead of some commands, like "start
+//! working" instead of `/start`, for servers that would rather talk to the bot
+//! than remember command names.
+
+use std::{collections::HashMap, str::FromStr};
+
+use poise::serenity_prelude::{self as serenity, ChannelId, Message, UserId};
+use thiserror::Error;
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::{
+    commands::pomo::{run_session, session_channel_for, start_blocked, StartBlocked},
+    pomo::{
+        reply::{say_alias_outcome, say_session_starting},
+        session::{PhaseType, SessionConfig, SessionError},
+        SessionContext,
+    },
+    settings::{FirstSkip, SkipResponse},
+    Data, Error,
+};
+
+/// The commands that phrases can stand in for, which are the ones that make
+/// sense without any options.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AliasCommand {
+    Start,
+    Join,
+    Leave,
+    Skip,
+    Stop,
+}
+
+impl FromStr for AliasCommand {
+    type Err = AliasError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().trim_start_matches('/').to_lowercase().as_str() {
+            "start" => Ok(AliasCommand::Start),
+            "join" => Ok(AliasCommand::Join),
+            "leave" => Ok(AliasCommand::Leave),
+            "skip" => Ok(AliasCommand::Skip),
+            "stop" => Ok(AliasCommand::Stop),
+            _ => Err(AliasError::UnknownCommand(s.trim().to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AliasError {
+    #[error("{0:?} should look like `phrase=command`")]
+    Malformed(String),
+    #[error(
+        "{0:?} isn't a command phrases can be used for (try start, join, leave, skip or stop)"
+    )]
+    UnknownCommand(String),
+    #[error("the phrase {0:?} is used more than once")]
+    Duplicate(String),
+    #[error("the phrase {0:?} would clash with the {1} command")]
+    Conflict(String, String),
+}
+
+/// Phrases that can be sent to the bot instead of commands.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextAliases {
+    phrases: HashMap<String, AliasCommand>,
+    /// The prefix that phrases can follow, if prefix commands can be used with
+    /// one. Phrases can always follow a mention of the bot.
+    prefix: Option<String>,
+}
+
+impl TextAliases {
+    /// Parse aliases written as `phrase=command` pairs separated by
+    /// semicolons, e.g. `start working=start;done for today=stop`.
+    pub fn parse(value: &str) -> Result<Self, AliasError> {
+        let mut phrases = HashMap::new();
+
+        for alias in value.split(';').filter(|alias| !alias.trim().is_empty()) {
+            let (phrase, command) = alias
+                .split_once('=')
+                .ok_or_else(|| AliasError::Malformed(alias.trim().to_owned()))?;
+
+            let phrase = normalize_phrase(phrase);
+
+            if phrase.is_empty() {
+                return Err(AliasError::Malformed(alias.trim().to_owned()));
+            }
+
+            if phrases.insert(phrase.clone(), command.parse()?).is_some() {
+                return Err(AliasError::Duplicate(phrase));
+            }
+        }
+
+        Ok(Self {
+            phrases,
+            prefix: None,
+        })
+    }
+
+    /// Let phrases follow `prefix` as well as mentions of the bot.
+    pub fn with_prefix(self, prefix: Option<String>) -> Self {
+        Self { prefix, ..self }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.phrases.is_empty()
+    }
+
+    /// Check that no phrase starts with the name of one of `commands`, which
+    /// should be the prefix commands, since the phrase would run that command
+    /// too.
+    pub fn check_conflicts<'a, I>(&self, commands: I) -> Result<(), AliasError>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        for command in commands {
+            let command = command.to_lowercase();
+
+            if let Some(phrase) = self
+                .phrases
+                .keys()
+                .find(|phrase| phrase.split(' ').next() == Some(command.as_str()))
+            {
+                return Err(AliasError::Conflict(phrase.clone(), command));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find the command that `content` stands in for, if it's one of the
+    /// phrases following the prefix or a mention of `bot`.
+    ///
+    /// Phrases have to be addressed to the bot one way or the other, so that
+    /// they aren't picked out of normal conversation.
+    pub fn command(&self, content: &str, bot: UserId) -> Option<AliasCommand> {
+        let content = content.trim_start();
+        let mentions = [format!("<@{}>", bot), format!("<@!{}>", bot)];
+
+        let rest = mentions
+            .iter()
+            .map(String::as_str)
+            .chain(self.prefix.as_deref().filter(|prefix| !prefix.is_empty()))
+            .find_map(|prefix| content.strip_prefix(prefix))?;
+
+        self.phrases.get(&normalize_phrase(rest)).copied()
+    }
+}
+
+/// Put `phrase` in lowercase with single spaces and no trailing punctuation,
+/// so that "Start  working!" matches "start working".
+fn normalize_phrase(phrase: &str) -> String {
+    phrase
+        .trim()
+        .trim_end_matches(|c| matches!(c, '.' | '!' | '?'))
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// What happened when someone used a phrase.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AliasOutcome {
+    Joined,
+    AlreadyMember,
+    /// The session only lets members join during breaks, and this isn't one.
+    WaitForBreak,
+    /// The session already has this many members, which is as many as it
+    /// allows.
+    Full(usize),
+    Left,
+    NotMember,
+    Skipping {
+        skipped: PhaseType,
+        next: PhaseType,
+    },
+    /// The first phase can't be skipped without `/skip confirm:True`.
+    ConfirmSkip,
+    Stopping,
+    NoSession,
+    /// The session with this ID was between phases, so nothing could be done.
+    Failed(Uuid),
+    CannotStart(StartBlocked),
+}
+
+/// Respond to `message` if it's one of the phrases that can be used instead of
+/// a command, by doing whatever that command would.
+#[instrument(skip(discord, message, data), fields(message_id = %message.id))]
+pub async fn handle_message(
+    discord: &serenity::Context,
+    message: &Message,
+    data: &Data,
+) -> Result<(), Error> {
+    if data.text_aliases.is_empty() || message.author.bot {
+        return Ok(());
+    }
+
+    let command = match data
+        .text_aliases
+        .command(&message.content, discord.cache.current_user_id())
+    {
+        Some(command) => command,
+        None => return Ok(()),
+    };
+
+    let ctx = SessionContext {
+        discord,
+        data,
+        channel_id: message.channel_id,
+        guild_id: message.guild_id,
+    };
+    let ctx = SessionContext {
+        channel_id: session_channel_for(ctx).await,
+        ..ctx
+    };
+    let author = message.author.id;
+
+    info!(?command, %author, channel_id = %ctx.channel_id, "running text alias");
+
+    let outcome = match command {
+        AliasCommand::Start => match start_blocked(ctx, author).await {
+            Some(blocked) => AliasOutcome::CannotStart(blocked),
+            None => {
+                let mut session = SessionConfig::default().build(author);
+
+                let message = say_session_starting(ctx, &session).await;
+                session.set_start_message(message.map(|message| (message.channel_id, message.id)));
+
+                return run_session(ctx, session).await;
+            }
+        },
+        command => {
+            let first_skip = data.guild_settings(ctx.guild_id).await.first_skip;
+            run(data, ctx.channel_id, author, command, first_skip).await
+        }
+    };
+
+    say_alias_outcome(ctx, author, &outcome).await;
+
+    Ok(())
+}
+
+/// Do what `command` would do to the session in `channel_id` on behalf of
+/// `author`.
+///
+/// Starting a session needs more than the session map, so that's left to
+/// [`handle_message`], and only gets as far as finding there's already one
+/// running here.
+async fn run(
+    data: &Data,
+    channel_id: ChannelId,
+    author: UserId,
+    command: AliasCommand,
+    first_skip: FirstSkip,
+) -> AliasOutcome {
+    let mut sessions = data.sessions.lock().await;
+
+    let session = match sessions.get_mut(&channel_id) {
+        Some(session) => session,
+        None => return AliasOutcome::NoSession,
+    };
+
+    match command {
+        AliasCommand::Start => AliasOutcome::CannotStart(StartBlocked::Running(channel_id)),
+        AliasCommand::Join if session.is_member(author) => AliasOutcome::AlreadyMember,
+        AliasCommand::Join if !session.can_join_now(author) => AliasOutcome::WaitForBreak,
+        AliasCommand::Join if !session.has_room_for(author) => {
+            AliasOutcome::Full(session.members().len())
+        }
+        AliasCommand::Join => {
+            session.add_member(author);
+            AliasOutcome::Joined
+        }
+        AliasCommand::Leave if session.remove_member(author) => AliasOutcome::Left,
+        AliasCommand::Leave => AliasOutcome::NotMember,
+        AliasCommand::Skip => {
+            if first_skip.response(session.is_first_phase(), false) == SkipResponse::AskToConfirm {
+                return AliasOutcome::ConfirmSkip;
+            }
+
+            match session.skip_many(1) {
+                Ok((skipped, next)) => AliasOutcome::Skipping {
+                    skipped: skipped[0],
+                    next,
+                },
+                Err(SessionError::NotActive) => AliasOutcome::Failed(session.id()),
+            }
+        }
+        AliasCommand::Stop => match session.stop() {
+            Ok(()) => AliasOutcome::Stopping,
+            Err(SessionError::NotActive) => AliasOutcome::Failed(session.id()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::ApplicationId;
+
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let aliases = TextAliases::parse("Start  working=start; done for today = /stop;").unwrap();
+
+        assert_eq!(
+            aliases.command("<@1> start working!", UserId(1)),
+            Some(AliasCommand::Start)
+        );
+        assert_eq!(
+            aliases.command("<@!1>   Done for TODAY.", UserId(1)),
+            Some(AliasCommand::Stop)
+        );
+        assert_eq!(
+            aliases.command("start working", UserId(1)),
+            None,
+            "phrases should need to be addressed to the bot"
+        );
+        assert_eq!(aliases.command("<@2> start working", UserId(1)), None);
+        assert_eq!(
+            aliases
+                .clone()
+                .with_prefix(Some("|".to_owned()))
+                .command("| start working", UserId(1)),
+            Some(AliasCommand::Start)
+        );
+
+        assert_eq!(
+            TextAliases::parse("start working"),
+            Err(AliasError::Malformed("start working".to_owned()))
+        );
+        assert_eq!(
+            TextAliases::parse(" =start"),
+            Err(AliasError::Malformed("=start".to_owned()))
+        );
+        assert_eq!(
+            TextAliases::parse("go=explode"),
+            Err(AliasError::UnknownCommand("explode".to_owned()))
+        );
+        assert_eq!(
+            TextAliases::parse("go=start;Go=stop"),
+            Err(AliasError::Duplicate("go".to_owned()))
+        );
+        assert!(TextAliases::parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn conflicts() {
+        let aliases = TextAliases::parse("register me=join").unwrap();
+
+        assert_eq!(
+            aliases.check_conflicts(vec!["register"]),
+            Err(AliasError::Conflict(
+                "register me".to_owned(),
+                "register".to_owned()
+            ))
+        );
+        assert_eq!(aliases.check_conflicts(vec!["reg", "reload"]), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn run_commands() {
+        let data = Data::new(ApplicationId(1), UserId(1), chrono::Duration::hours(8));
+        let channel = ChannelId(1);
+        let host = UserId(2);
+        let user = UserId(3);
+
+        assert_eq!(
+            run(&data, channel, user, AliasCommand::Join, FirstSkip::Normal).await,
+            AliasOutcome::NoSession
+        );
+
+        data.sessions
+            .lock()
+            .await
+            .insert(channel, SessionConfig::default().build(host));
+
+        assert_eq!(
+            run(&data, channel, user, AliasCommand::Join, FirstSkip::Normal).await,
+            AliasOutcome::Joined
+        );
+        assert_eq!(
+            run(&data, channel, user, AliasCommand::Join, FirstSkip::Normal).await,
+            AliasOutcome::AlreadyMember
+        );
+        assert_eq!(
+            run(&data, channel, user, AliasCommand::Leave, FirstSkip::Normal).await,
+            AliasOutcome::Left
+        );
+        assert_eq!(
+            run(&data, channel, user, AliasCommand::Leave, FirstSkip::Normal).await,
+            AliasOutcome::NotMember
+        );
+    }
+}
@@ -0,0 +1,818 @@
+use chrono::Duration;
+use chrono_tz::{Tz, UTC};
+use poise::serenity_prelude as serenity;
+use serenity::Mentionable;
+use tracing::{info, instrument};
+
+#[cfg(feature = "daily-stats")]
+use crate::pomo::reply::reply_daily_stats_set;
+use crate::{
+    commands::{
+        meta::{is_admin, is_owner},
+        pomo::guild_channels,
+    },
+    pomo::{
+        reply::{
+            config_summary, reply_audit_channel_set, reply_cooldown_invalid, reply_cooldown_set,
+            reply_first_skip_set, reply_force_stop_ambiguous, reply_force_stop_failed,
+            reply_force_stop_not_found, reply_force_stopped, reply_guild_stats_reset,
+            reply_guild_timezone_set, reply_invalid_timezone, reply_mentions_set,
+            reply_one_session_set, reply_stats_mode_set, reply_stats_reset_nothing,
+            reply_template_deleted, reply_template_not_found, reply_template_saved,
+            reply_thread_sessions_set, reply_tidy_set, reply_tips_set,
+            reply_transcript_channel_set, say_audit_entry, say_session_force_stopped,
+        },
+        session::SessionConfig,
+        SessionContext,
+    },
+    settings::FirstSkip,
+    stats::StatsMode,
+    Context, Error,
+};
+#[cfg(feature = "scheduled-events")]
+use crate::{
+    events::EventLink,
+    pomo::reply::{
+        reply_event_channel_elsewhere, reply_event_linked, reply_event_not_found,
+        reply_event_not_linked, reply_event_unlinked, reply_invalid_event_id,
+    },
+};
+
+/// Set how long to wait after a session ends before another can be started in
+/// the same channel
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn setcooldown(
+    ctx: Context<'_>,
+    #[description = "Cooldown in seconds, up to a day (default: 30)"] seconds: usize,
+) -> Result<(), Error> {
+    if seconds > MAX_COOLDOWN_SECONDS {
+        reply_cooldown_invalid(ctx, MAX_COOLDOWN_SECONDS).await;
+        return Ok(());
+    }
+
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+    let cooldown = Duration::seconds(seconds as i64);
+
+    info!(%guild_id, %cooldown, "setting start cooldown");
+
+    ctx.data()
+        .guild_settings
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .start_cooldown = cooldown;
+
+    reply_cooldown_set(ctx, cooldown).await;
+
+    audit(
+        ctx,
+        "/setcooldown",
+        format!("Start cooldown: {} seconds", seconds),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// The longest start cooldown that can be set, in seconds.
+const MAX_COOLDOWN_SECONDS: usize = 24 * 60 * 60;
+
+/// Turn daily summaries of focus time on or off for this server
+#[cfg(feature = "daily-stats")]
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn dailystats(
+    ctx: Context<'_>,
+    #[description = "Whether to post daily summaries"] enabled: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+
+    info!(%guild_id, enabled, "setting daily stats");
+
+    ctx.data()
+        .guild_settings
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .daily_stats = enabled;
+
+    reply_daily_stats_set(ctx, enabled).await;
+
+    audit(
+        ctx,
+        "/dailystats",
+        format!("Daily stats: {}", on_off(enabled)),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Delete confirmations of commands like /skip after a delay, to keep channels
+/// tidy
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn settidy(
+    ctx: Context<'_>,
+    #[description = "Seconds to wait before deleting confirmations (default: never delete them)"]
+    seconds: Option<usize>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+    let delay = seconds
+        .filter(|seconds| *seconds > 0)
+        .map(|seconds| Duration::seconds(seconds as i64));
+
+    info!(%guild_id, ?delay, "setting tidy delay");
+
+    ctx.data()
+        .guild_settings
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .tidy_delay = delay;
+
+    reply_tidy_set(ctx, delay).await;
+
+    let target = match delay {
+        Some(delay) => format!("Tidy delay: {} seconds", delay.num_seconds()),
+        None => "Tidy delay: never".to_owned(),
+    };
+    audit(ctx, "/settidy", target).await;
+
+    Ok(())
+}
+
+/// Choose how often work sessions start with a productivity tip
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn settips(
+    ctx: Context<'_>,
+    #[description = "Percentage of work sessions that start with a tip (default: 20)"] percent: u32,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+    let percent = percent.min(100);
+
+    info!(%guild_id, percent, "setting work tip percentage");
+
+    ctx.data()
+        .guild_settings
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .work_tip_percent = percent;
+
+    reply_tips_set(ctx, percent).await;
+
+    audit(ctx, "/settips", format!("Work tips: {}%", percent)).await;
+
+    Ok(())
+}
+
+/// Choose whether skipped work sessions count towards focus time for the time
+/// they ran
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn setstatsmode(
+    ctx: Context<'_>,
+    #[description = "Whether skipped work sessions count partially (default: false)"] partial: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+    let mode = if partial {
+        StatsMode::Partial
+    } else {
+        StatsMode::Strict
+    };
+
+    info!(%guild_id, ?mode, "setting stats mode");
+
+    ctx.data()
+        .guild_settings
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .stats_mode = mode;
+
+    reply_stats_mode_set(ctx, mode).await;
+
+    audit(ctx, "/setstatsmode", format!("Stats mode: {:?}", mode)).await;
+
+    Ok(())
+}
+
+/// Choose whether threads share their parent channel's session, or have their
+/// own
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn setthreads(
+    ctx: Context<'_>,
+    #[description = "Whether threads share their parent channel's session (default: false)"]
+    share: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+
+    info!(%guild_id, share, "setting whether threads share sessions");
+
+    ctx.data()
+        .guild_settings
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .share_thread_sessions = share;
+
+    reply_thread_sessions_set(ctx, share).await;
+
+    audit(
+        ctx,
+        "/setthreads",
+        format!("Thread sessions: {}", on_off(share)),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Choose whether only one session can run in this server at a time
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn setonesession(
+    ctx: Context<'_>,
+    #[description = "Whether only one session can run in the server at a time (default: false)"]
+    enabled: bool,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+
+    info!(%guild_id, enabled, "setting whether there's one session per guild");
+
+    ctx.data()
+        .guild_settings
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .one_session_per_guild = enabled;
+
+    reply_one_session_set(ctx, enabled).await;
+
+    audit(
+        ctx,
+        "/setonesession",
+        format!("One session per server: {}", on_off(enabled)),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Choose how many members of a session get pinged one by one, and who gets
+/// pinged after that
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn setmentions(
+    ctx: Context<'_>,
+    #[description = "Most members to ping one by one (default: no limit)"] limit: Option<usize>,
+    #[description = "Role to ping instead past the limit (default: nobody)"] role: Option<
+        serenity::Role,
+    >,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+    let role_id = role.map(|role| role.id);
+
+    info!(%guild_id, ?limit, ?role_id, "setting mention limit");
+
+    {
+        let mut guild_settings = ctx.data().guild_settings.lock().await;
+        let settings = guild_settings.entry(guild_id).or_default();
+        settings.mention_limit = limit;
+        settings.mention_role = role_id;
+    }
+
+    reply_mentions_set(ctx, limit, role_id).await;
+
+    let target = match (limit, role_id) {
+        (None, _) => "Mention limit: none".to_owned(),
+        (Some(limit), Some(role_id)) => {
+            format!("Mention limit: {}, then {}", limit, role_id.mention())
+        }
+        (Some(limit), None) => format!("Mention limit: {}, then nobody", limit),
+    };
+    audit(ctx, "/setmentions", target).await;
+
+    Ok(())
+}
+
+/// Choose what /skip does when it's used on the first phase of a session
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn setfirstskip(
+    ctx: Context<'_>,
+    #[description = "What to do when someone skips the first phase"] first_skip: FirstSkip,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+
+    info!(%guild_id, ?first_skip, "setting what skipping the first phase does");
+
+    ctx.data()
+        .guild_settings
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .first_skip = first_skip;
+
+    reply_first_skip_set(ctx, first_skip).await;
+
+    audit(
+        ctx,
+        "/setfirstskip",
+        format!("First skip: {:?}", first_skip),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Set the time zone that times are shown in for anyone who doesn't give their
+/// own
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn setguildtz(
+    ctx: Context<'_>,
+    #[description = "Time zone (example: Europe/Berlin, default: UTC)"] timezone: Option<String>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+
+    let tz: Tz = match timezone {
+        Some(timezone) => match timezone.trim().parse() {
+            Ok(tz) => tz,
+            Err(_) => {
+                reply_invalid_timezone(ctx, &timezone).await;
+                return Ok(());
+            }
+        },
+        None => UTC,
+    };
+
+    info!(%guild_id, %tz, "setting guild time zone");
+
+    ctx.data()
+        .guild_settings
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .timezone = tz;
+
+    reply_guild_timezone_set(ctx, tz).await;
+
+    audit(ctx, "/setguildtz", format!("Time zone: {}", tz)).await;
+
+    Ok(())
+}
+
+/// Delete all the focus time recorded in this server, for everyone
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin", rename = "resetserver")]
+pub async fn stats_reset_server(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+    let deleted = ctx.data().stats.lock().await.delete_guild(guild_id);
+
+    match deleted {
+        Some(minutes) => {
+            info!(%guild_id, minutes, "reset guild stats");
+            reply_guild_stats_reset(ctx, minutes).await;
+
+            audit(
+                ctx,
+                "/stats resetserver",
+                format!("Deleted {} minutes of focus time", minutes),
+            )
+            .await;
+        }
+        None => reply_stats_reset_nothing(ctx).await,
+    }
+
+    Ok(())
+}
+
+/// Manage this server's session templates, which anyone can start sessions from
+#[instrument(skip(_ctx))]
+#[poise::command(
+    slash_command,
+    guild_only,
+    check = "is_admin",
+    subcommands("template_create", "template_delete")
+)]
+pub async fn template(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Create a template for anyone in this server to start sessions from
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin", rename = "create")]
+pub async fn template_create(
+    ctx: Context<'_>,
+    #[description = "Name of the template, to use with /start"] name: String,
+    #[description = "Length of a work session in minutes (default: 25)"] work: Option<usize>,
+    #[description = "Length of a short break in minutes (default: 5)"] short: Option<usize>,
+    #[description = "Length of a long break in minutes (default: 15)"] long: Option<usize>,
+    #[description = "How many work sessions between each long break (default: 4)"] interval: Option<
+        usize,
+    >,
+    #[description = "Length of every other work session in minutes (default: same as work)"]
+    alternate_work: Option<usize>,
+    #[description = "Never take long breaks, only short ones (default: false)"] no_long: Option<
+        bool,
+    >,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+
+    let config = SessionConfig::default()
+        .work_or_default(work)
+        .short_or_default(short)
+        .long_or_default(long)
+        .interval_or_default(interval)
+        .alternate_work(alternate_work)
+        .no_long(no_long.unwrap_or(false))
+        .validate()?;
+
+    let replaced = ctx
+        .data()
+        .templates
+        .lock()
+        .await
+        .save(guild_id, &name, config.clone())?
+        .is_some();
+
+    info!(%guild_id, %name, replaced, "saved template");
+
+    reply_template_saved(ctx, &name, &config, replaced).await;
+
+    audit(
+        ctx,
+        "/template create",
+        format!(
+            "Template {}: {}",
+            name.trim().to_lowercase(),
+            config_summary(&config)
+        ),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Delete one of this server's templates
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin", rename = "delete")]
+pub async fn template_delete(
+    ctx: Context<'_>,
+    #[description = "Name of the template"] name: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+
+    let deleted = ctx.data().templates.lock().await.delete(guild_id, &name)?;
+
+    if deleted.is_some() {
+        info!(%guild_id, %name, "deleted template");
+        reply_template_deleted(ctx, &name).await;
+
+        audit(
+            ctx,
+            "/template delete",
+            format!("Template {}", name.trim().to_lowercase()),
+        )
+        .await;
+    } else {
+        reply_template_not_found(ctx, &name).await;
+    }
+
+    Ok(())
+}
+
+/// Choose a channel to log uses of admin commands in
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn setaudit(
+    ctx: Context<'_>,
+    #[description = "Channel to log admin commands in (default: don't log them)"] channel: Option<
+        serenity::Channel,
+    >,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+    let channel_id = channel.map(|channel| channel.id());
+
+    info!(%guild_id, ?channel_id, "setting audit channel");
+
+    // Log turning it off before it's turned off, so that there's a record of
+    // why the log stopped.
+    let target = match channel_id {
+        Some(channel_id) => format!("Audit channel: {}", channel_id.mention()),
+        None => "Audit channel: none".to_owned(),
+    };
+    if channel_id.is_none() {
+        audit(ctx, "/setaudit", target.clone()).await;
+    }
+
+    ctx.data()
+        .guild_settings
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .audit_channel = channel_id;
+
+    reply_audit_channel_set(ctx, channel_id).await;
+    if channel_id.is_some() {
+        audit(ctx, "/setaudit", target).await;
+    }
+
+    Ok(())
+}
+
+/// Choose a channel to keep a running log of every session in
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn settranscript(
+    ctx: Context<'_>,
+    #[description = "Channel to log sessions in (default: don't log them)"] channel: Option<
+        serenity::Channel,
+    >,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+    let channel_id = channel.map(|channel| channel.id());
+
+    info!(%guild_id, ?channel_id, "setting transcript channel");
+
+    ctx.data()
+        .guild_settings
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .transcript_channel = channel_id;
+
+    reply_transcript_channel_set(ctx, channel_id).await;
+    audit(
+        ctx,
+        "/settranscript",
+        match channel_id {
+            Some(channel_id) => format!("Transcript channel: {}", channel_id.mention()),
+            None => "Transcript channel: none".to_owned(),
+        },
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Stop a pomo session running in any channel, given its session ID
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn forcestop(
+    ctx: Context<'_>,
+    #[description = "ID of the session, or at least the first 8 characters of it"] session: String,
+) -> Result<(), Error> {
+    let query = session.trim().to_lowercase();
+
+    if query.len() < 8 {
+        reply_force_stop_not_found(ctx).await;
+        return Ok(());
+    }
+
+    // Admins can only stop sessions in their own guild, so as far as they're
+    // concerned sessions anywhere else don't exist, including when it comes to
+    // whether an ID is ambiguous.
+    let owner = is_owner(ctx).await?;
+    let channels = guild_channels(ctx.discord(), ctx.guild_id());
+
+    let matches = ctx
+        .data()
+        .sessions
+        .lock()
+        .await
+        .iter()
+        .filter(|(channel_id, _)| owner || channels.contains(channel_id))
+        .filter(|(_, session)| session.id().to_string().starts_with(&query))
+        .map(|(channel_id, _)| *channel_id)
+        .collect::<Vec<_>>();
+
+    let channel_id = match *matches.as_slice() {
+        [channel_id] => channel_id,
+        [] => {
+            reply_force_stop_not_found(ctx).await;
+            return Ok(());
+        }
+        _ => {
+            reply_force_stop_ambiguous(ctx).await;
+            return Ok(());
+        }
+    };
+
+    // The owner can stop sessions in other guilds, which need to be told about
+    // it as if it happened there.
+    let guild_id = if channels.contains(&channel_id) {
+        ctx.guild_id()
+    } else {
+        channel_id
+            .to_channel(ctx.discord())
+            .await?
+            .guild()
+            .map(|channel| channel.guild_id)
+    };
+
+    let stopped = match ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        Some(session) => Some((session.id(), session.stop())),
+        None => None,
+    };
+
+    match stopped {
+        Some((id, Ok(()))) => {
+            info!(%id, %channel_id, "force stopped session");
+
+            let session_ctx = SessionContext {
+                discord: ctx.discord(),
+                data: ctx.data(),
+                channel_id,
+                guild_id,
+            };
+
+            say_session_force_stopped(session_ctx, ctx.author().id).await;
+            reply_force_stopped(ctx, channel_id).await;
+
+            audit(
+                ctx,
+                "/forcestop",
+                format!("Session {} in {}", id, channel_id.mention()),
+            )
+            .await;
+        }
+        Some((id, Err(_))) => reply_force_stop_failed(ctx, id).await,
+        None => reply_force_stop_not_found(ctx).await,
+    }
+
+    Ok(())
+}
+
+/// Post an entry to this server's audit log, if it has one, saying that the
+/// author used `command` on `target`.
+async fn audit(ctx: Context<'_>, command: &str, target: String) {
+    let settings = ctx.data().guild_settings(ctx.guild_id()).await;
+
+    if let Some(channel_id) = settings.audit_channel {
+        let session_ctx = SessionContext {
+            channel_id,
+            ..SessionContext::from(ctx)
+        };
+
+        say_audit_entry(
+            session_ctx,
+            command,
+            ctx.author().id,
+            ctx.channel_id(),
+            &target,
+        )
+        .await;
+    }
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Run a pomo session in a channel whenever a scheduled event is active
+#[cfg(feature = "scheduled-events")]
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn linkevent(
+    ctx: Context<'_>,
+    #[description = "ID of the scheduled event"] event: String,
+    #[description = "Channel to run the session in (default: this channel)"] channel: Option<
+        serenity::Channel,
+    >,
+    #[description = "Length of a work session in minutes (default: 25)"] work: Option<usize>,
+    #[description = "Length of a short break in minutes (default: 5)"] short: Option<usize>,
+    #[description = "Length of a long break in minutes (default: 15)"] long: Option<usize>,
+    #[description = "How many work sessions between each long break (default: 4)"] interval: Option<
+        usize,
+    >,
+) -> Result<(), Error> {
+    let event_id = match event.trim().parse() {
+        Ok(id) => serenity::ScheduledEventId(id),
+        Err(_) => {
+            reply_invalid_event_id(ctx).await;
+            return Ok(());
+        }
+    };
+
+    let config = SessionConfig::default()
+        .work_or_default(work)
+        .short_or_default(short)
+        .long_or_default(long)
+        .interval_or_default(interval)
+        .validate()?;
+
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+
+    let channel_id = match channel {
+        Some(serenity::Channel::Guild(channel)) if channel.guild_id == guild_id => channel.id,
+        Some(_) => {
+            reply_event_channel_elsewhere(ctx).await;
+            return Ok(());
+        }
+        None => ctx.channel_id(),
+    };
+
+    if let Err(error) = guild_id
+        .scheduled_event(ctx.discord(), event_id, false)
+        .await
+    {
+        info!(%event_id, ?error, "unable to find scheduled event in this guild");
+        reply_event_not_found(ctx).await;
+        return Ok(());
+    }
+
+    let link = EventLink {
+        guild_id,
+        channel_id,
+        config,
+        host: ctx.author().id,
+    };
+
+    info!(%event_id, ?link, "linking scheduled event");
+
+    {
+        let mut links = ctx.data().event_links.lock().await;
+
+        // Another guild's link is theirs to replace, not ours.
+        if links
+            .get(&event_id)
+            .map_or(false, |existing| existing.guild_id != guild_id)
+        {
+            drop(links);
+            reply_event_not_found(ctx).await;
+            return Ok(());
+        }
+
+        links.insert(event_id, link);
+    }
+
+    reply_event_linked(ctx, channel_id).await;
+
+    audit(
+        ctx,
+        "/linkevent",
+        format!("Event {} in {}", event_id, channel_id.mention()),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Stop running a pomo session whenever a scheduled event is active
+#[cfg(feature = "scheduled-events")]
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, check = "is_admin")]
+pub async fn unlinkevent(
+    ctx: Context<'_>,
+    #[description = "ID of the scheduled event"] event: String,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+
+    // Links made in other guilds are treated as if they don't exist.
+    let removed = match event.trim().parse() {
+        Ok(id) => {
+            let event_id = serenity::ScheduledEventId(id);
+            let mut links = ctx.data().event_links.lock().await;
+
+            if links
+                .get(&event_id)
+                .map_or(false, |link| link.guild_id == guild_id)
+            {
+                links.remove(&event_id)
+            } else {
+                None
+            }
+        }
+        Err(_) => None,
+    };
+
+    if removed.is_some() {
+        reply_event_unlinked(ctx).await;
+        audit(ctx, "/unlinkevent", format!("Event {}", event.trim())).await;
+    } else {
+        reply_event_not_linked(ctx).await;
+    }
+
+    Ok(())
+}
@@ -1,7 +1,25 @@
-use poise::builtins::HelpConfiguration;
-use tracing::{info, instrument};
+use chrono::{Duration, Utc};
+use poise::{
+    builtins::HelpConfiguration,
+    serenity_prelude::{ApplicationId, Permissions},
+};
+use tracing::{info, instrument, warn};
 
-use crate::{Context, Error};
+use crate::{
+    config,
+    pomo::{
+        reply::{
+            reply_about, reply_debug_dump, reply_feedback_failed, reply_feedback_sent,
+            reply_feedback_too_soon, reply_feedback_unavailable, reply_invite, reply_reload_failed,
+            reply_reloaded, say_feedback,
+        },
+        SessionContext,
+    },
+    Context, Error,
+};
+
+/// How many minutes each user has to wait between sending feedback.
+const FEEDBACK_COOLDOWN_MINUTES: i64 = 10;
 
 /// Show this help menu
 #[instrument(skip(ctx))]
@@ -27,6 +45,100 @@ pub async fn help(
     Ok(())
 }
 
+/// Show which version of the bot this is, to include in bug reports
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn about(ctx: Context<'_>) -> Result<(), Error> {
+    reply_about(ctx).await;
+
+    Ok(())
+}
+
+/// Get a link to add this bot to a server
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn invite(ctx: Context<'_>) -> Result<(), Error> {
+    reply_invite(ctx, &invite_url(ctx.data().application_id)).await;
+
+    Ok(())
+}
+
+/// The permissions the bot needs in a server to run sessions.
+fn invite_permissions() -> Permissions {
+    // Showing the phase in the topic and pinning start messages also need Manage
+    // Channels and Manage Messages, but they're opt-in, so servers that want them
+    // can grant them in the channels they want them in rather than everywhere.
+    Permissions::VIEW_CHANNEL
+        | Permissions::SEND_MESSAGES
+        | Permissions::SEND_MESSAGES_IN_THREADS
+        | Permissions::EMBED_LINKS
+}
+
+/// Build the URL for adding the application with ID `application_id` to a
+/// server, along with its slash commands.
+fn invite_url(application_id: ApplicationId) -> String {
+    format!(
+        "https://discord.com/api/oauth2/authorize?client_id={}&permissions={}&scope=bot%20applications.commands",
+        application_id,
+        invite_permissions().bits()
+    )
+}
+
+/// Send a suggestion or bug report to whoever runs this bot
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn feedback(
+    ctx: Context<'_>,
+    #[description = "What you'd like to tell whoever runs this bot"] text: String,
+) -> Result<(), Error> {
+    let feedback_channel = *ctx.data().feedback_channel.lock().await;
+
+    let feedback_channel = match feedback_channel {
+        Some(feedback_channel) => feedback_channel,
+        None => {
+            reply_feedback_unavailable(ctx).await;
+            return Ok(());
+        }
+    };
+
+    let author = ctx.author().id;
+    let now = Utc::now();
+
+    let mut feedback_sent = ctx.data().feedback_sent.lock().await;
+
+    if let Some(remaining) = feedback_sent
+        .get(&author)
+        .map(|sent| *sent + Duration::minutes(FEEDBACK_COOLDOWN_MINUTES) - now)
+        .filter(|remaining| *remaining > Duration::zero())
+    {
+        drop(feedback_sent);
+        reply_feedback_too_soon(ctx, remaining).await;
+        return Ok(());
+    }
+
+    feedback_sent.insert(author, now);
+    drop(feedback_sent);
+
+    info!("forwarding feedback");
+
+    let forward_ctx = SessionContext {
+        channel_id: feedback_channel,
+        ..SessionContext::from(ctx)
+    };
+
+    if say_feedback(forward_ctx, ctx, &text).await.is_some() {
+        reply_feedback_sent(ctx).await;
+    } else {
+        warn!(%feedback_channel, "unable to forward feedback");
+
+        // It didn't get anywhere, so it shouldn't count against them.
+        ctx.data().feedback_sent.lock().await.remove(&author);
+        reply_feedback_failed(ctx).await;
+    }
+
+    Ok(())
+}
+
 /// Register application commands in this guild or globally
 ///
 /// Run with no arguments to register in guild, run with argument "global" to
@@ -41,6 +153,97 @@ pub async fn register(ctx: Context<'_>, #[flag] global: bool) -> Result<(), Erro
     Ok(())
 }
 
+/// Re-read the bot's configuration from the environment and the `.env` file
+///
+/// Only the settings that are safe to change while the bot is running are
+/// updated (see the README). Anything else, like the token, needs a restart.
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, prefix_command, check = "is_owner", hide_in_help)]
+pub async fn reload(ctx: Context<'_>) -> Result<(), Error> {
+    info!("reloading configuration");
+
+    let result = match config::reload() {
+        Ok(config) => ctx.data().reload(&config).await,
+        Err(error) => Err(error),
+    };
+
+    match result {
+        Ok(changed) => {
+            info!(?changed, "reloaded configuration");
+            reply_reloaded(ctx, &changed).await;
+        }
+        Err(error) => {
+            warn!(%error, "unable to reload configuration");
+            reply_reload_failed(ctx, &error).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Owner-only tools for working out what's gone wrong
+#[instrument(skip(_ctx))]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    check = "is_owner",
+    hide_in_help,
+    subcommands("debug_dump")
+)]
+pub async fn debug(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Attach a JSON dump of every running and queued session, for bug reports
+#[instrument(skip(ctx))]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "dump",
+    check = "is_owner",
+    hide_in_help
+)]
+pub async fn debug_dump(ctx: Context<'_>) -> Result<(), Error> {
+    info!("dumping state");
+
+    let dump = ctx.data().dump().await;
+    let sessions = dump.sessions.len();
+    let dump = serde_json::to_string_pretty(&dump).map_err(|error| Error::Other(error.into()))?;
+
+    reply_debug_dump(ctx, dump, sessions).await;
+
+    Ok(())
+}
+
 pub async fn is_owner(ctx: Context<'_>) -> Result<bool, Error> {
     Ok(ctx.author().id == ctx.data().owner_id)
 }
+
+/// Returns whether the author is the owner of the bot, or has permission to
+/// manage the guild the command was run in.
+pub async fn is_admin(ctx: Context<'_>) -> Result<bool, Error> {
+    if is_owner(ctx).await? {
+        return Ok(true);
+    }
+
+    if let Some(guild_id) = ctx.guild_id() {
+        let member = guild_id.member(ctx.discord(), ctx.author().id).await?;
+
+        Ok(member.permissions(ctx.discord())?.manage_guild())
+    } else {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invite_url_has_permissions() {
+        assert_eq!(
+            invite_url(ApplicationId(1234)),
+            "https://discord.com/api/oauth2/authorize?client_id=1234&permissions=274877926400&scope=bot%20applications.commands"
+        );
+    }
+}
@@ -1,18 +1,65 @@
-use chrono_tz::{Tz, UTC};
-use tracing::{error, info, instrument};
+use std::{collections::HashSet, future::Future, time::Duration as StdDuration};
 
+use chrono::{DateTime, Duration, Utc};
+use poise::serenity_prelude::{self as serenity, ChannelId, ChannelType, GuildId};
+use rand::{rngs::StdRng, SeedableRng};
+use tokio::time::sleep;
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+#[cfg(feature = "phase-hooks")]
+use crate::hooks::HookEvent;
 use crate::{
+    commands::{
+        admin::stats_reset_server,
+        meta::{is_admin, is_owner},
+    },
     pomo::{
+        clock::Clock,
+        notifier::Notifier,
         reply::{
-            reply_cannot_start, reply_join_already_member, reply_join_no_session, reply_joined,
-            reply_leave_no_session, reply_leave_not_member, reply_left, reply_skip_failed,
-            reply_skip_no_session, reply_skipping_phase, reply_starting, reply_status,
-            reply_status_no_session, reply_stop_failed, reply_stop_no_session,
-            reply_stopping_session, say_phase_finished, say_session_failed, say_session_stopped,
+            edit_stop_poll_closed, pin_message, reply_accountability_set,
+            reply_adjust_interval_invalid, reply_adjust_interval_no_session, reply_at_capacity,
+            reply_break_now, reply_break_now_invalid, reply_break_now_no_session,
+            reply_break_now_not_working, reply_break_time, reply_break_time_no_session,
+            reply_cannot_start, reply_cannot_start_in_guild, reply_config, reply_config_no_session,
+            reply_config_toml, reply_cooldown, reply_defaults, reply_enrolled, reply_export,
+            reply_finish_no_session, reply_finishing, reply_guild_only, reply_health_no_session,
+            reply_health_ok, reply_health_stalled, reply_import_failed, reply_interval_adjusted,
+            reply_join_all_no_voice, reply_join_already_member, reply_join_during_break,
+            reply_join_full, reply_join_no_session, reply_joined, reply_last_session,
+            reply_last_session_never, reply_last_session_running, reply_leave_no_session,
+            reply_leave_not_member, reply_left, reply_long_break, reply_long_break_never,
+            reply_long_break_no_session, reply_move_no_session, reply_move_not_host,
+            reply_move_occupied, reply_move_same_channel, reply_not_host, reply_opt_out_set,
+            reply_phases, reply_phases_no_session, reply_preset_deleted, reply_preset_not_allowed,
+            reply_preset_not_found, reply_preset_saved, reply_presets, reply_queue_no_session,
+            reply_queued, reply_reset_failed, reply_reset_no_session, reply_reset_sequence,
+            reply_session_moved, reply_set_time_failed, reply_set_time_invalid,
+            reply_set_time_no_session, reply_silence_invalid, reply_silence_no_session,
+            reply_silence_not_member, reply_silenced, reply_skip_break_working,
+            reply_skip_confirm_first, reply_skip_failed, reply_skip_no_session,
+            reply_skipping_phase, reply_skipping_phases, reply_starting, reply_starting_silent,
+            reply_stats_reset, reply_stats_reset_nothing, reply_status, reply_status_compact,
+            reply_status_no_session, reply_stop_failed, reply_stop_no_session, reply_stop_poll,
+            reply_stop_poll_not_member, reply_stopping_session, reply_template_not_found,
+            reply_templates, reply_time_set, reply_timeline, reply_undo_no_session,
+            reply_undo_unavailable, reply_undone, reply_unfinish_not_finishing, reply_unfinished,
+            reply_unqueued, respond_stop_poll_not_member, respond_stop_poll_updated,
+            say_session_moved_here, StatusView, READY_TO_STOP_BUTTON,
         },
-        session::{PhaseResult, Session, SessionConfig, SessionError, SessionStatus},
+        session::{
+            ConfigFormat, FinishAnnouncement, Phase, PhaseResult, PhaseType, Session,
+            SessionConfig, SessionError, SessionStatus, SkippedWork,
+        },
+        store::SessionStore,
+        vote::{Consensus, Outcome, Vote},
+        SessionContext,
     },
-    Context, Error,
+    presets::{PresetOwner, PresetScope},
+    settings::SkipResponse,
+    stats::{FocusRecord, Participation},
+    Context, Data, Error,
 };
 
 /// Start a pomo session in this channel
@@ -20,203 +67,2739 @@ use crate::{
 #[poise::command(slash_command)]
 pub async fn start(
     ctx: Context<'_>,
+    #[description = "Name of a saved preset to start from, which other options override (see \
+                     /presets)"]
+    preset: Option<String>,
+    #[description = "Name of one of this server's templates to start from, instead of a preset \
+                     (see /templates)"]
+    template: Option<String>,
     #[description = "Length of a work session in minutes (default: 25)"] work: Option<usize>,
     #[description = "Length of a short break in minutes (default: 5)"] short: Option<usize>,
     #[description = "Length of a long break in minutes (default: 15)"] long: Option<usize>,
     #[description = "How many work sessions between each long break (default: 4)"] interval: Option<
         usize,
     >,
+    #[description = "Length of every other work session in minutes (default: same as work)"]
+    alternate_work: Option<usize>,
+    #[description = "Maximum number of members who can join (default: unlimited)"]
+    max_members: Option<usize>,
+    #[description = "Only let people /join during breaks (default: false)"] join_on_breaks: Option<
+        bool,
+    >,
+    #[description = "Count down the last few seconds of each work session (default: false)"]
+    countdown: Option<bool>,
+    #[description = "Announce phase changes without notification sounds, still mentioning members \
+                     (default: false)"]
+    silent_pings: Option<bool>,
+    #[description = "Reply with a one-line confirmation instead of the full details (default: \
+                     false)"]
+    silent: Option<bool>,
+    #[description = "Never take long breaks, only short ones (default: false)"] no_long: Option<
+        bool,
+    >,
+    #[description = "Show the current phase in the channel topic, if I can Manage Channel \
+                     (default: false)"]
+    topic: Option<bool>,
+    #[description = "Pin the start message until the session ends, if I can Manage Messages \
+                     (default: false)"]
+    pin: Option<bool>,
+    #[description = "Stop the session after this many hours, no matter what, up to the bot's \
+                     limit (default: 8)"]
+    max_hours: Option<u32>,
+    #[description = "How to announce the session finishing after /finish (default: Full)"]
+    finish_announcement: Option<FinishAnnouncement>,
+    #[description = "Whether skipped work sessions count towards the next long break (default: \
+                     Counted)"]
+    skipped_work: Option<SkippedWork>,
+    #[description = "Give every work session a random length, for fun (default: false)"]
+    roulette: Option<bool>,
 ) -> Result<(), Error> {
-    if ctx
-        .data()
-        .sessions
-        .lock()
-        .await
-        .contains_key(&ctx.channel_id())
-    {
-        reply_cannot_start(ctx).await;
+    let base = match (template, preset) {
+        (Some(name), _) => {
+            let template = match ctx.guild_id() {
+                Some(guild_id) => ctx.data().templates.lock().await.get(guild_id, &name),
+                None => {
+                    reply_guild_only(ctx).await;
+                    return Ok(());
+                }
+            };
 
-        Ok(())
-    } else {
-        let config = SessionConfig::default()
-            .work_or_default(work)
-            .short_or_default(short)
-            .long_or_default(long)
-            .interval_or_default(interval);
+            match template {
+                Some(config) => config,
+                None => {
+                    reply_template_not_found(ctx, &name).await;
+                    return Ok(());
+                }
+            }
+        }
+        (None, Some(name)) => {
+            let preset =
+                ctx.data()
+                    .presets
+                    .lock()
+                    .await
+                    .get(ctx.author().id, ctx.guild_id(), &name);
 
-        let mut session = config.build();
-        session.add_member(ctx.author().id);
+            match preset {
+                Some(config) => config,
+                None => {
+                    reply_preset_not_found(ctx, &name).await;
+                    return Ok(());
+                }
+            }
+        }
+        (None, None) => SessionConfig::default(),
+    };
 
-        info!(?session, "created new session");
+    let alternate_work = alternate_work.or(base.alternate_work);
+    let no_long = no_long.unwrap_or(base.no_long);
 
-        reply_starting(ctx, session.config(), session.id()).await;
+    let config = base
+        .work_or_default(work)
+        .short_or_default(short)
+        .long_or_default(long)
+        .interval_or_default(interval)
+        .alternate_work(alternate_work)
+        .no_long(no_long)
+        .validate()?;
 
-        run_session(ctx, session).await
-    }
-}
+    let mut session = config.build(ctx.author().id);
+    session.set_max_members(max_members);
+    session.set_join_on_breaks(join_on_breaks.unwrap_or(false));
+    session.set_countdown(countdown.unwrap_or(false));
+    session.set_silent_pings(silent_pings.unwrap_or(false));
+    // DMs don't have topics, so there'd be no point trying.
+    session.set_topic(topic.unwrap_or(false) && ctx.guild_id().is_some());
+    session.set_pin(pin.unwrap_or(false));
+    // A session that expires as soon as it starts is no use to anyone, so treat
+    // zero as not specifying a maximum.
+    let max_length = max_hours
+        .filter(|&hours| hours > 0)
+        .map(|hours| Duration::hours(hours.into()));
+    // MAX_SESSION_HOURS is there to stop forgotten sessions running forever, so
+    // only the owner can start a session that runs for longer than it.
+    let max_length = if is_owner(ctx).await? {
+        max_length
+    } else {
+        let limit = *ctx.data().max_session_length.lock().await;
+        max_length.map(|length| length.min(limit))
+    };
+    session.set_max_length(max_length);
+    session.set_finish_announcement(finish_announcement.unwrap_or_default());
+    session.set_skipped_work(skipped_work.unwrap_or_default());
 
-#[instrument(skip(ctx, session), fields(id = %session.id()))]
-async fn run_session(ctx: Context<'_>, session: Session) -> Result<(), Error> {
-    let id = session.id();
+    if roulette.unwrap_or(false) {
+        // Each session gets its own RNG seeded from the shared one, so that picking
+        // phase lengths doesn't need to lock the shared one from inside the session.
+        let rng = StdRng::from_rng(&mut *ctx.data().rng.lock().await)
+            .expect("unable to seed StdRng from the shared StdRng");
+        session.set_roulette(Some(rng));
+    }
 
-    let mut sessions = ctx.data().sessions.lock().await;
-    sessions.insert(ctx.channel_id(), session);
+    start_session(ctx, session, silent.unwrap_or(false)).await
+}
 
-    let phase = sessions
-        .get_mut(&ctx.channel_id())
-        .expect("session stays in sessions until we remove it")
-        .advance();
+/// Start a pomo session in this channel from a code shared with /export
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn import(
+    ctx: Context<'_>,
+    #[description = "Config code from /export"] code: String,
+) -> Result<(), Error> {
+    match SessionConfig::from_code(&code) {
+        Ok(config) => start_session(ctx, config.build(ctx.author().id), false).await,
+        Err(error) => {
+            reply_import_failed(ctx, error).await;
 
-    drop(sessions);
+            Ok(())
+        }
+    }
+}
 
-    info!(phase_type = ?phase.phase_type(), "starting first phase");
-    let mut result = phase.await;
+/// Save some settings as a preset, to start sessions from by name
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn savepreset(
+    ctx: Context<'_>,
+    #[description = "Name of the preset, to use with /start"] name: String,
+    #[description = "Length of a work session in minutes (default: 25)"] work: Option<usize>,
+    #[description = "Length of a short break in minutes (default: 5)"] short: Option<usize>,
+    #[description = "Length of a long break in minutes (default: 15)"] long: Option<usize>,
+    #[description = "How many work sessions between each long break (default: 4)"] interval: Option<
+        usize,
+    >,
+    #[description = "Length of every other work session in minutes (default: same as work)"]
+    alternate_work: Option<usize>,
+    #[description = "Never take long breaks, only short ones (default: false)"] no_long: Option<
+        bool,
+    >,
+    #[description = "Who can use the preset (default: Just me)"] scope: Option<PresetScope>,
+) -> Result<(), Error> {
+    let scope = scope.unwrap_or_default();
 
-    while let PhaseResult::Completed(finished) | PhaseResult::Skipped(finished) = result {
-        info!(?result, "finished phase");
+    let owner = match preset_owner(ctx, scope).await? {
+        Some(owner) => owner,
+        None => {
+            reply_preset_not_allowed(ctx).await;
+            return Ok(());
+        }
+    };
 
-        let mut sessions = ctx.data().sessions.lock().await;
-        let session = sessions
-            .get_mut(&ctx.channel_id())
-            .expect("session stays in sessions until we remove it");
+    let config = SessionConfig::default()
+        .work_or_default(work)
+        .short_or_default(short)
+        .long_or_default(long)
+        .interval_or_default(interval)
+        .alternate_work(alternate_work)
+        .no_long(no_long.unwrap_or(false))
+        .validate()?;
 
-        let phase = session.advance();
-        let members = session.members().iter();
+    let replaced = ctx
+        .data()
+        .presets
+        .lock()
+        .await
+        .save(owner, &name, config.clone())?
+        .is_some();
 
-        info!(phase_type = ?phase.phase_type(), "starting next phase");
+    info!(?owner, %name, replaced, "saved preset");
 
-        say_phase_finished(ctx, finished, *phase.phase_type(), members).await;
+    reply_preset_saved(ctx, &name, scope, &config, replaced).await;
 
-        drop(sessions);
+    Ok(())
+}
 
-        result = phase.await;
-    }
+/// List the presets you can start sessions from
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn presets(ctx: Context<'_>) -> Result<(), Error> {
+    let available = ctx
+        .data()
+        .presets
+        .lock()
+        .await
+        .available(ctx.author().id, ctx.guild_id());
 
-    match result {
-        PhaseResult::Stopped(_) => {
-            info!(?result, "session stopped");
+    reply_presets(ctx, &available).await;
 
-            say_session_stopped(ctx).await;
-        }
-        PhaseResult::Failed(_) => {
-            error!(?result, "session failed");
+    Ok(())
+}
 
-            say_session_failed(ctx, id).await;
-        }
-        PhaseResult::Completed(_) | PhaseResult::Skipped(_) => unreachable!(),
-    }
+/// List the templates this server's admins have made to start sessions from
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+pub async fn templates(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+    let templates = ctx.data().templates.lock().await.list(guild_id);
 
-    let mut sessions = ctx.data().sessions.lock().await;
-    sessions.remove(&ctx.channel_id());
+    reply_templates(ctx, &templates).await;
 
     Ok(())
 }
 
-/// Get the status of the current pomo session running in this channel
+/// Delete a preset saved with /savepreset
 #[instrument(skip(ctx))]
 #[poise::command(slash_command)]
-pub async fn status(
+pub async fn deletepreset(
     ctx: Context<'_>,
-    #[description = "Your time zone (example: Europe/London, default: UTC)"] timezone: Option<
-        String,
-    >,
+    #[description = "Name of the preset"] name: String,
+    #[description = "Who the preset belongs to (default: Just me)"] scope: Option<PresetScope>,
 ) -> Result<(), Error> {
-    let tz: Tz = timezone
-        .and_then(|tz_str| tz_str.parse().ok())
-        .unwrap_or(UTC);
-
-    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&ctx.channel_id()) {
-        match session.status() {
-            SessionStatus::Running {
-                phase_type,
-                phase_elapsed,
-                phase_remaining,
-                next_type,
-                long_at,
-            } => {
-                reply_status(
-                    ctx,
-                    phase_type,
-                    phase_elapsed,
-                    phase_remaining,
-                    next_type,
-                    long_at,
-                    tz,
-                )
-                .await
-            }
-            SessionStatus::NoSession => reply_status_no_session(ctx).await,
+    let owner = match preset_owner(ctx, scope.unwrap_or_default()).await? {
+        Some(owner) => owner,
+        None => {
+            reply_preset_not_allowed(ctx).await;
+            return Ok(());
         }
+    };
+
+    let deleted = ctx.data().presets.lock().await.delete(owner, &name)?;
+
+    if deleted.is_some() {
+        info!(?owner, %name, "deleted preset");
+        reply_preset_deleted(ctx, &name).await;
     } else {
-        reply_status_no_session(ctx).await;
+        reply_preset_not_found(ctx, &name).await;
     }
 
     Ok(())
 }
 
-/// Join the pomo session running in this channel to be notified when phases
-/// finish
-#[instrument(skip(ctx))]
-#[poise::command(slash_command)]
-pub async fn join(ctx: Context<'_>) -> Result<(), Error> {
-    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&ctx.channel_id()) {
-        if session.add_member(ctx.author().id) {
-            reply_joined(ctx).await;
-        } else {
-            reply_join_already_member(ctx).await;
+/// Get who presets with `scope` saved or deleted by the author belong to, or
+/// `None` if the author isn't allowed to change them.
+///
+/// Only admins can change the guild's presets.
+async fn preset_owner(ctx: Context<'_>, scope: PresetScope) -> Result<Option<PresetOwner>, Error> {
+    match (scope, ctx.guild_id()) {
+        (PresetScope::User, _) => Ok(Some(PresetOwner::User(ctx.author().id))),
+        (PresetScope::Guild, Some(guild_id)) if is_admin(ctx).await? => {
+            Ok(Some(PresetOwner::Guild(guild_id)))
         }
-    } else {
-        reply_join_no_session(ctx).await;
+        (PresetScope::Guild, _) => Ok(None),
     }
-
-    Ok(())
 }
 
-/// Leave the pomo session running in this channel to stop being notified
+/// Get a code for the config of the session in this channel, to use with
+/// /import
 #[instrument(skip(ctx))]
 #[poise::command(slash_command)]
-pub async fn leave(ctx: Context<'_>) -> Result<(), Error> {
-    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&ctx.channel_id()) {
-        if session.remove_member(ctx.author().id) {
-            reply_left(ctx).await;
-        } else {
-            reply_leave_not_member(ctx).await;
-        }
+pub async fn export(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get(&channel_id) {
+        reply_export(ctx, session.config()).await;
     } else {
-        reply_leave_no_session(ctx).await;
+        reply_config_no_session(ctx).await;
     }
 
     Ok(())
 }
 
-/// Skip the current phase of the pomo session running in this channel
-#[instrument(skip(ctx))]
-#[poise::command(slash_command)]
-pub async fn skip(ctx: Context<'_>) -> Result<(), Error> {
-    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&ctx.channel_id()) {
-        match session.skip() {
-            Ok(skipped_type) => reply_skipping_phase(ctx, skipped_type).await,
-            Err(SessionError::NotActive) => reply_skip_failed(ctx, session.id()).await,
+/// Start `session` in this channel and run it, unless there's already a session
+/// here or the channel's cooldown hasn't expired yet.
+///
+/// If `silent` is true, the reply is a one-line confirmation instead of the
+/// full details of the session.
+async fn start_session(ctx: Context<'_>, mut session: Session, silent: bool) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+    let session_ctx = SessionContext {
+        channel_id,
+        ..SessionContext::from(ctx)
+    };
+
+    let blocked = match start_blocked(session_ctx, ctx.author().id).await {
+        // Admins don't have to wait for the cooldown to expire.
+        Some(StartBlocked::Cooldown(_)) if is_admin(ctx).await? => None,
+        blocked => blocked,
+    };
+
+    match blocked {
+        Some(StartBlocked::Running(existing)) if existing == channel_id => {
+            reply_cannot_start(ctx).await;
+            Ok(())
+        }
+        Some(StartBlocked::Running(existing)) => {
+            reply_cannot_start_in_guild(ctx, existing).await;
+            Ok(())
+        }
+        Some(StartBlocked::AtCapacity) => {
+            reply_at_capacity(ctx).await;
+            Ok(())
+        }
+        Some(StartBlocked::Cooldown(remaining)) => {
+            reply_cooldown(ctx, remaining).await;
+            Ok(())
+        }
+        None => {
+            info!(?session, "created new session");
+
+            let message = if silent {
+                reply_starting_silent(ctx, &session).await
+            } else {
+                reply_starting(ctx, &session).await
+            };
+            session.set_start_message(message.map(|message| (message.channel_id, message.id)));
+
+            if session.pin() {
+                // If it can't be pinned, there's nothing to unpin later either.
+                let pinned = match session.start_message() {
+                    Some((message_channel, message_id)) => {
+                        pin_message(session_ctx, message_channel, message_id).await
+                    }
+                    None => false,
+                };
+
+                session.set_pin(pinned);
+            }
+
+            run_session(session_ctx, session).await
         }
+    }
+}
+
+/// Why a session can't be started in a channel right now.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StartBlocked {
+    /// A session is already running in this channel, or (if the guild only
+    /// allows one at a time) the given channel.
+    Running(ChannelId),
+    /// As many sessions are running as the bot allows.
+    AtCapacity,
+    /// The last session in this channel ended too recently, and another can be
+    /// started after this long.
+    Cooldown(Duration),
+}
+
+/// Check whether `host` can start a session in `ctx.channel_id`, returning
+/// why not if they can't.
+///
+/// The cooldown is checked last, so that callers who let admins skip it can
+/// ignore [`StartBlocked::Cooldown`] without missing anything else.
+pub(crate) async fn start_blocked(
+    ctx: SessionContext<'_>,
+    host: serenity::UserId,
+) -> Option<StartBlocked> {
+    let settings = ctx.data.guild_settings(ctx.guild_id).await;
+
+    // Only look the guild's channels up if they could make a difference.
+    let guild_channels = if settings.one_session_per_guild {
+        guild_channels(ctx.discord, ctx.guild_id)
     } else {
-        reply_skip_no_session(ctx).await;
+        HashSet::new()
+    };
+
+    // A session stays in the map for as long as its `run_session` task is running,
+    // even between phases when it isn't `is_running()`, so presence in the map is
+    // what decides whether a channel already has a session.
+    let running = ctx
+        .data
+        .sessions
+        .lock()
+        .await
+        .iter()
+        .map(|(channel_id, _)| *channel_id)
+        .collect::<Vec<_>>();
+
+    if let Some(existing) = settings.conflicting_session(ctx.channel_id, running, &guild_channels) {
+        Some(StartBlocked::Running(existing))
+    } else if ctx.data.at_capacity(host).await {
+        Some(StartBlocked::AtCapacity)
+    } else {
+        ctx.data
+            .last_sessions
+            .lock()
+            .await
+            .get(&ctx.channel_id)
+            .map(|ended| {
+                ended
+                    .checked_add_signed(settings.start_cooldown)
+                    .unwrap_or(chrono::MAX_DATETIME)
+                    - Utc::now()
+            })
+            .filter(|remaining| *remaining > Duration::zero())
+            .map(StartBlocked::Cooldown)
     }
+}
 
-    Ok(())
+/// Get every channel in `guild_id`, including threads, as far as the cache
+/// knows.
+pub(crate) fn guild_channels(
+    discord: &serenity::Context,
+    guild_id: Option<GuildId>,
+) -> HashSet<ChannelId> {
+    guild_id
+        .and_then(|guild_id| guild_id.to_guild_cached(discord))
+        .map(|guild| {
+            guild
+                .channels
+                .keys()
+                .copied()
+                .chain(guild.threads.iter().map(|thread| thread.id))
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
-/// Stop the pomo session currently running in this channel
+/// Get the channel whose session a command used in this channel should act on.
+///
+/// That's this channel, unless it's a thread and the guild has chosen to have
+/// threads share their parent channel's session.
+async fn session_channel(ctx: Context<'_>) -> ChannelId {
+    session_channel_for(ctx.into()).await
+}
+
+/// Get the channel whose session something done in `ctx.channel_id` should act
+/// on, like [`session_channel`] but without needing a command.
+pub(crate) async fn session_channel_for(ctx: SessionContext<'_>) -> ChannelId {
+    let settings = ctx.data.guild_settings(ctx.guild_id).await;
+
+    // Only look the channel up if it could make a difference.
+    let thread_parent = if settings.share_thread_sessions {
+        thread_parent(ctx.discord, ctx.channel_id).await
+    } else {
+        None
+    };
+
+    settings.session_channel(ctx.channel_id, thread_parent)
+}
+
+/// Get the parent channel of `channel_id`, if it's a thread.
+async fn thread_parent(discord: &serenity::Context, channel_id: ChannelId) -> Option<ChannelId> {
+    match channel_id.to_channel(discord).await {
+        Ok(serenity::Channel::Guild(channel))
+            if matches!(
+                channel.kind,
+                ChannelType::PublicThread | ChannelType::PrivateThread | ChannelType::NewsThread
+            ) =>
+        {
+            channel.parent_id
+        }
+        Ok(_) => None,
+        Err(error) => {
+            warn!(?error, "unable to check whether channel is a thread");
+            None
+        }
+    }
+}
+
+/// Show the settings a pomo session uses when none are specified
 #[instrument(skip(ctx))]
 #[poise::command(slash_command)]
-pub async fn stop(ctx: Context<'_>) -> Result<(), Error> {
-    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&ctx.channel_id()) {
-        match session.stop() {
-            Ok(()) => reply_stopping_session(ctx).await,
-            Err(SessionError::NotActive) => reply_stop_failed(ctx, session.id()).await,
+pub async fn defaults(ctx: Context<'_>) -> Result<(), Error> {
+    reply_defaults(ctx, &SessionConfig::default()).await;
+
+    Ok(())
+}
+
+/// Run `session` in the channel given by `ctx` until it stops or fails,
+/// announcing each phase change along the way.
+pub(crate) async fn run_session(ctx: SessionContext<'_>, session: Session) -> Result<(), Error> {
+    run_session_with(
+        ctx.data,
+        ctx.channel_id,
+        ctx.guild_id,
+        |channel_id| SessionContext { channel_id, ..ctx },
+        session,
+    )
+    .await
+}
+
+/// Run `session` in `channel_id` until it stops or fails, telling the notifier
+/// given by `notifier_for` for the session's channel about each phase change
+/// along the way.
+///
+/// If the session finishes (rather than being stopped) and another session has
+/// been queued up in its channel with `/queue`, that session is run next, with
+/// the same members.
+pub(crate) async fn run_session_with<N, F>(
+    data: &Data,
+    mut channel_id: ChannelId,
+    guild_id: Option<GuildId>,
+    notifier_for: F,
+    mut session: Session,
+) -> Result<(), Error>
+where
+    N: Notifier,
+    F: Fn(ChannelId) -> N,
+{
+    let mut queued = false;
+
+    loop {
+        let result = run_one_session(data, channel_id, guild_id, &notifier_for, session).await;
+
+        let (ended_in, next) = match result {
+            // Something else got into the channel between the last session ending and the
+            // queued one taking over, so the queued one gives way to it.
+            Err(Error::AlreadyRunning) if queued => {
+                info!("dropping queued session, another session started first");
+                return Ok(());
+            }
+            result => result?,
+        };
+
+        match next {
+            Some(next) => {
+                info!(next = %next.id(), "starting queued session");
+
+                notifier_for(ended_in)
+                    .queued_session_starting(next.config())
+                    .await;
+
+                channel_id = ended_in;
+                session = next;
+                queued = true;
+            }
+            None => return Ok(()),
         }
+    }
+}
+
+/// Run `session` like [`run_session_with`], but without moving on to a queued
+/// session afterwards. Instead, returns the channel the session ended in, along
+/// with the queued session that should run next, if there is one.
+///
+/// Fails with [`Error::AlreadyRunning`] without running the session if
+/// there's already a session in `channel_id`.
+///
+/// The session can be moved to another channel while it runs, so the channel
+/// it's in is looked up again after each phase, and a new notifier is used if
+/// it has changed.
+#[instrument(skip(data, notifier_for, session), fields(id = %session.id()))]
+async fn run_one_session<N, F>(
+    data: &Data,
+    mut channel_id: ChannelId,
+    guild_id: Option<GuildId>,
+    notifier_for: &F,
+    mut session: Session,
+) -> Result<(ChannelId, Option<Session>), Error>
+where
+    N: Notifier,
+    F: Fn(ChannelId) -> N,
+{
+    let id = session.id();
+    let mut notifier = notifier_for(channel_id);
+
+    // The topic is only changed if the session asks for it, so only then does it
+    // need to be put back afterwards.
+    let mut original_topic = if session.topic() {
+        notifier.topic().await
     } else {
-        reply_stop_no_session(ctx).await;
+        None
+    };
+    let mut topic_changed = false;
+
+    let finish_announcement = session.finish_announcement();
+    let stats_mode = data.guild_settings(guild_id).await.stats_mode;
+    let mut work_phases = 0;
+    let mut focus_minutes = 0;
+    let mut participation = Participation::default();
+
+    // Checking for another session and inserting this one happen under the same
+    // lock, so that two tasks can never end up running sessions in the same
+    // channel, even if they both got past the check in `start_session`.
+    let max_session_length = *data.max_session_length.lock().await;
+    let mut sessions = data.sessions.lock().await;
+
+    if sessions.contains_key(&channel_id) {
+        drop(sessions);
+        warn!("there's already a session running in this channel");
+
+        if let Some((message_channel, message_id)) =
+            session.start_message().filter(|_| session.pin())
+        {
+            notifier.unpin_message(message_channel, message_id).await;
+        }
+
+        return Err(Error::AlreadyRunning);
     }
 
-    Ok(())
+    let deadline = session.start_deadline(max_session_length);
+    let clock = session.clock();
+    let phase = session.advance();
+    let countdown = session.countdown();
+    let rolled = session.rolled();
+    let members = session.members().clone();
+
+    sessions.insert(channel_id, session);
+    drop(sessions);
+
+    info!(phase_type = ?phase.phase_type(), %deadline, "starting first phase");
+
+    notifier
+        .session_started(*phase.phase_type(), members.into_iter().collect())
+        .await;
+
+    if let Some(minutes) = rolled {
+        notifier.roulette_spun(minutes).await;
+    }
+
+    let topic = update_topic(
+        data,
+        channel_id,
+        &notifier,
+        *phase.phase_type(),
+        phase.end(),
+    );
+    let run = run_phase_until(data, id, &notifier, phase, countdown, &*clock, deadline);
+    let (changed, mut result) = alongside_topic(topic, run).await;
+    topic_changed |= changed;
+
+    loop {
+        let moved_to = match locate_session(&**data.sessions.lock().await, id) {
+            Some(current) if current != channel_id => Some(current),
+            Some(_) => None,
+            None => {
+                // Something else (like `/health`, when it thinks this task is dead) has
+                // taken the session away, so there's nothing left to do.
+                warn!("session was removed while it was running");
+
+                if topic_changed {
+                    notifier.restore_topic(original_topic).await;
+                }
+
+                return Ok((channel_id, None));
+            }
+        };
+
+        if let Some(moved_to) = moved_to {
+            info!(from = %channel_id, to = %moved_to, "session was moved");
+
+            if topic_changed {
+                notifier.restore_topic(original_topic.take()).await;
+                topic_changed = false;
+            }
+
+            channel_id = moved_to;
+            notifier = notifier_for(channel_id);
+
+            let show_topic = data
+                .sessions
+                .lock()
+                .await
+                .get(&channel_id)
+                .map_or(false, Session::topic);
+            original_topic = if show_topic {
+                notifier.topic().await
+            } else {
+                None
+            };
+        }
+
+        let finished = match result {
+            PhaseResult::Completed(finished) | PhaseResult::Skipped(finished, _) => finished,
+            PhaseResult::Stopped(..) | PhaseResult::Failed(_) => break,
+        };
+
+        info!(?result, "finished phase");
+
+        // The session could have been removed or replaced since it was found above,
+        // while the lock wasn't held.
+        let mut sessions = data.sessions.lock().await;
+        let session = match sessions
+            .get_mut(&channel_id)
+            .filter(|session| session.id() == id)
+        {
+            Some(session) => session,
+            None => {
+                drop(sessions);
+                warn!("session was removed between phases");
+
+                if topic_changed {
+                    notifier.restore_topic(original_topic).await;
+                }
+
+                return Ok((channel_id, None));
+            }
+        };
+
+        if let PhaseResult::Completed(PhaseType::Work(_)) = result {
+            work_phases += 1;
+        }
+
+        participation.record_phase(session.members());
+
+        if let Some(minutes) = stats_mode.credit(&result) {
+            focus_minutes += minutes;
+            participation.credit(session.members(), minutes);
+            record_focus(data, channel_id, guild_id, session, minutes).await;
+        }
+
+        if session.finishing() {
+            break;
+        }
+
+        let phase = session.advance();
+        let countdown = session.countdown();
+        let silent_pings = session.silent_pings();
+        let members = session.mentioned_members();
+        let start_link = session
+            .start_message()
+            .map(|(channel_id, message_id)| message_id.link(channel_id, guild_id));
+
+        info!(phase_type = ?phase.phase_type(), "starting next phase");
+
+        let rolled = session.rolled();
+        let number = session.phase_number();
+
+        notifier
+            .phase_finished(
+                finished,
+                *phase.phase_type(),
+                number,
+                members,
+                start_link,
+                silent_pings,
+            )
+            .await;
+
+        drop(sessions);
+
+        notifier.phase_changed(finished, *phase.phase_type()).await;
+
+        if let Some(minutes) = rolled {
+            notifier.roulette_spun(minutes).await;
+        }
+
+        let topic = update_topic(
+            data,
+            channel_id,
+            &notifier,
+            *phase.phase_type(),
+            phase.end(),
+        );
+        let run = run_phase_until(data, id, &notifier, phase, countdown, &*clock, deadline);
+        let (changed, next_result) = alongside_topic(topic, run).await;
+        topic_changed |= changed;
+        result = next_result;
+    }
+
+    match result {
+        PhaseResult::Stopped(phase_type, elapsed) => {
+            info!(?phase_type, %elapsed, "session stopped");
+
+            let mut expired = false;
+
+            if let Some(session) = data
+                .sessions
+                .lock()
+                .await
+                .get(&channel_id)
+                .filter(|session| session.id() == id)
+            {
+                expired = session.is_expired();
+
+                if let Some(minutes) = stats_mode.credit(&result) {
+                    participation.credit(session.members(), minutes);
+                    record_focus(data, channel_id, guild_id, session, minutes).await;
+                }
+            }
+
+            if expired {
+                notifier.session_expired().await;
+            } else {
+                notifier.session_stopped().await;
+            }
+        }
+        PhaseResult::Failed(_) => {
+            error!(?result, "session failed");
+
+            notifier.session_failed(id).await;
+        }
+        // The loop above only ends with a phase that didn't stop or fail if the
+        // session was asked to finish after it.
+        PhaseResult::Completed(_) | PhaseResult::Skipped(..) => {
+            info!(?result, "session finished");
+
+            match finish_announcement {
+                FinishAnnouncement::Full => {
+                    notifier.session_finished(work_phases, focus_minutes).await
+                }
+                FinishAnnouncement::Brief => notifier.session_finished_briefly().await,
+                FinishAnnouncement::Silent => {}
+            }
+        }
+    }
+
+    if topic_changed {
+        notifier.restore_topic(original_topic).await;
+    }
+
+    // If the session was replaced while it was stopping, the one that replaced it
+    // isn't this task's to remove.
+    let mut sessions = data.sessions.lock().await;
+    let ended = if sessions
+        .get(&channel_id)
+        .map_or(false, |session| session.id() == id)
+    {
+        sessions.remove(&channel_id)
+    } else {
+        None
+    };
+    drop(sessions);
+
+    if let Some((message_channel, message_id)) = ended
+        .as_ref()
+        .filter(|session| session.pin())
+        .and_then(Session::start_message)
+    {
+        notifier.unpin_message(message_channel, message_id).await;
+    }
+
+    // Members who left before the end still took part, so they can get a summary
+    // too.
+    let mut participants = participation.members().collect::<HashSet<_>>();
+    participants.extend(
+        ended
+            .iter()
+            .flat_map(|session| session.members().iter().copied()),
+    );
+
+    notifier
+        .session_ended(participants.iter().copied().collect())
+        .await;
+
+    for user in participants {
+        if data.user_settings(user).await.accountability {
+            notifier
+                .accountability_summary(user, participation.member(user), participation.phases())
+                .await;
+        }
+    }
+
+    let mut last_sessions = data.last_sessions.lock().await;
+    last_sessions.insert(channel_id, Utc::now());
+    drop(last_sessions);
+
+    // Only a session that finishes hands over to the queued one. Stopping a session
+    // (including when it expires) means stopping altogether, so the queue goes too.
+    let queued = data.queued_sessions.lock().await.remove(&channel_id);
+    let next = match (result, queued, ended) {
+        (PhaseResult::Completed(_) | PhaseResult::Skipped(..), Some(mut next), Some(ended)) => {
+            for &member in ended.members() {
+                next.add_member(member);
+            }
+
+            Some(next)
+        }
+        (_, Some(queued), _) => {
+            info!(queued = %queued.id(), "dropping queued session");
+            None
+        }
+        (_, None, _) => None,
+    };
+
+    Ok((channel_id, next))
+}
+
+/// Find the channel that the session with ID `id` is running in, if it's still
+/// running.
+fn locate_session(sessions: &dyn SessionStore, id: Uuid) -> Option<ChannelId> {
+    sessions
+        .iter()
+        .find(|(_, session)| session.id() == id)
+        .map(|(channel_id, _)| *channel_id)
+}
+
+/// Show a phase of type `phase_type` ending at `end` in the channel topic if
+/// the session in `channel_id` asked for that, returning whether the topic was
+/// changed.
+///
+/// If the topic can't be changed (most likely because we don't have
+/// permission), this is turned off for the rest of the session, and `notifier`
+/// is told so that it only happens once.
+async fn update_topic<N>(
+    data: &Data,
+    channel_id: ChannelId,
+    notifier: &N,
+    phase_type: PhaseType,
+    end: DateTime<Utc>,
+) -> bool
+where
+    N: Notifier,
+{
+    let enabled = data
+        .sessions
+        .lock()
+        .await
+        .get(&channel_id)
+        .map_or(false, Session::topic);
+
+    if !enabled {
+        false
+    } else if notifier.show_phase_in_topic(phase_type, end).await {
+        true
+    } else {
+        if let Some(session) = data.sessions.lock().await.get_mut(&channel_id) {
+            session.set_topic(false);
+        }
+
+        notifier.topic_disabled().await;
+
+        false
+    }
+}
+
+/// Wait for `run` to finish running a phase, while `topic` shows it in the
+/// channel topic, returning whether the topic was changed along with how the
+/// phase ended.
+///
+/// Discord only allows a couple of channel edits every ten minutes, so a topic
+/// edit can be held up for minutes at a time. Doing it alongside the phase
+/// means it can't hold up the session (or stop the phase being polled, which
+/// would make `/health` think the session is stalled). If the phase ends before
+/// the edit goes through, the edit is abandoned, since the next phase's edit
+/// would replace it anyway, and the topic is assumed to have changed so that
+/// it's put back at the end of the session.
+async fn alongside_topic<T, R>(topic: T, run: R) -> (bool, PhaseResult)
+where
+    T: Future<Output = bool>,
+    R: Future<Output = PhaseResult>,
+{
+    tokio::pin!(topic);
+    tokio::pin!(run);
+
+    let mut changed = None;
+
+    loop {
+        tokio::select! {
+            // Checking whether the topic should be shown at all doesn't need to wait for
+            // Discord, so this makes sure that's done before the phase can end.
+            biased;
+
+            topic_changed = &mut topic, if changed.is_none() => changed = Some(topic_changed),
+            result = &mut run => return (changed.unwrap_or(true), result),
+        }
+    }
+}
+
+/// Credit each member of `session` with `minutes` of focus time.
+async fn record_focus(
+    data: &Data,
+    channel_id: ChannelId,
+    guild_id: Option<GuildId>,
+    session: &Session,
+    minutes: usize,
+) {
+    let mut stats = data.stats.lock().await;
+    let now = Utc::now();
+
+    for &user_id in session.members() {
+        stats.record(FocusRecord {
+            at: now,
+            guild_id,
+            channel_id,
+            user_id,
+            minutes,
+        });
+    }
+}
+
+/// Wait for `phase` to finish, counting down its last few seconds if
+/// `countdown` is true and it's a work phase.
+///
+/// The countdown is abandoned if the phase finishes early.
+async fn run_phase<N>(notifier: &N, mut phase: Phase, countdown: bool) -> PhaseResult
+where
+    N: Notifier,
+{
+    if countdown && matches!(phase.phase_type(), PhaseType::Work(_)) {
+        let countdown = notifier.countdown(phase.end());
+
+        tokio::select! {
+            result = &mut phase => result,
+            () = countdown => phase.await,
+        }
+    } else {
+        phase.await
+    }
+}
+
+/// Run `phase` like [`run_phase`], but stop the session with ID `id` if `clock`
+/// reaches `deadline` before the phase finishes.
+async fn run_phase_until<N>(
+    data: &Data,
+    id: Uuid,
+    notifier: &N,
+    phase: Phase,
+    countdown: bool,
+    clock: &dyn Clock,
+    deadline: DateTime<Utc>,
+) -> PhaseResult
+where
+    N: Notifier,
+{
+    #[cfg(feature = "phase-hooks")]
+    let phase_type = *phase.phase_type();
+    #[cfg(feature = "phase-hooks")]
+    data.phase_hooks
+        .lock()
+        .await
+        .fire(HookEvent::Start, phase_type, id);
+
+    let run = run_phase(notifier, phase, countdown);
+    tokio::pin!(run);
+
+    let result = tokio::select! {
+        result = &mut run => result,
+        () = wait_until(clock, deadline) => {
+            info!(%deadline, "session passed its deadline, stopping it");
+
+            let mut sessions = data.sessions.lock().await;
+
+            if let Some(channel_id) = locate_session(&**sessions, id) {
+                if let Some(session) = sessions.get_mut(&channel_id) {
+                    session.stop().ok();
+                }
+            }
+
+            drop(sessions);
+
+            run.await
+        }
+    };
+
+    #[cfg(feature = "phase-hooks")]
+    data.phase_hooks
+        .lock()
+        .await
+        .fire(HookEvent::End, phase_type, id);
+
+    result
+}
+
+/// Wait until `clock` reaches `deadline`.
+async fn wait_until(clock: &dyn Clock, deadline: DateTime<Utc>) {
+    // The clock isn't necessarily the system clock (e.g. in tests), so keep
+    // checking it rather than sleeping for the whole time in one go.
+    while clock.now() < deadline {
+        let remaining = (deadline - clock.now()).to_std().unwrap_or_default();
+        sleep(remaining.min(StdDuration::from_secs(1))).await;
+    }
+}
+
+/// How many minutes a session's phases can go without being polled before the
+/// session is considered dead.
+const STALLED_MINUTES: i64 = 1;
+
+/// Check that the pomo session in this channel is actually running, and clean
+/// it up if not
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn health(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+    let mut sessions = ctx.data().sessions.lock().await;
+
+    match sessions.get_mut(&channel_id) {
+        Some(session) if session.is_stalled(Duration::minutes(STALLED_MINUTES)) => {
+            let id = session.id();
+            error!(%id, "session is stalled, removing it");
+
+            // In case the task is only very slow rather than dead, make sure it stops
+            // when it next gets the chance.
+            session.stop().ok();
+            sessions.remove(&channel_id);
+            drop(sessions);
+
+            reply_health_stalled(ctx, id).await;
+        }
+        Some(_) => {
+            drop(sessions);
+            reply_health_ok(ctx).await;
+        }
+        None => {
+            drop(sessions);
+            reply_health_no_session(ctx).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the status of the current pomo session running in this channel
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn status(
+    ctx: Context<'_>,
+    #[description = "Your time zone (example: Europe/London, default: the server's, or UTC)"]
+    timezone: Option<String>,
+    #[description = "Reply with a single line instead of the full details (default: false)"]
+    compact: Option<bool>,
+) -> Result<(), Error> {
+    let tz = ctx
+        .data()
+        .guild_settings(ctx.guild_id())
+        .await
+        .timezone_for(timezone.as_deref());
+
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        match session.status() {
+            SessionStatus::Running {
+                phase_type,
+                phase_remaining,
+                next_type,
+                ..
+            } if compact.unwrap_or(false) => {
+                reply_status_compact(ctx, phase_type, phase_remaining, next_type).await
+            }
+            SessionStatus::Running {
+                phase_type,
+                phase_elapsed,
+                phase_remaining,
+                phase_planned,
+                phase_length,
+                phase_number,
+                session_elapsed,
+                next_type,
+                long_at,
+                ending,
+            } => {
+                let status = StatusView {
+                    phase_type,
+                    phase_number,
+                    phase_elapsed,
+                    phase_remaining,
+                    phase_planned,
+                    phase_length,
+                    session_elapsed,
+                    next_type,
+                    long_at,
+                    ending,
+                };
+
+                reply_status(ctx, status, tz).await
+            }
+            SessionStatus::NoSession => reply_status_no_session(ctx).await,
+        }
+    } else {
+        reply_status_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Find out when the next long break starts in the pomo session running in this
+/// channel
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, rename = "longbreak")]
+pub async fn long_break(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        match session.status() {
+            SessionStatus::Running {
+                long_at: Some(long_at),
+                ..
+            } => reply_long_break(ctx, long_at).await,
+            SessionStatus::Running { long_at: None, .. } => reply_long_break_never(ctx).await,
+            SessionStatus::NoSession => reply_long_break_no_session(ctx).await,
+        }
+    } else {
+        reply_long_break_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Find out how much of the pomo session running in this channel has been
+/// spent on breaks, compared to working
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn breaktime(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    let time_spent = ctx
+        .data()
+        .sessions
+        .lock()
+        .await
+        .get(&channel_id)
+        .map(Session::time_spent);
+
+    match time_spent {
+        Some(time_spent) => reply_break_time(ctx, time_spent).await,
+        None => reply_break_time_no_session(ctx).await,
+    }
+
+    Ok(())
+}
+
+/// List the upcoming phases of the pomo session running in this channel
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn phases(
+    ctx: Context<'_>,
+    #[description = "How many phases to list (default: 5, maximum: 20)"] count: Option<usize>,
+) -> Result<(), Error> {
+    let count = count.unwrap_or(5).clamp(1, 20);
+
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get(&channel_id) {
+        reply_phases(ctx, &session.upcoming_phases(count)).await;
+    } else {
+        reply_phases_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// The most phases that `/timeline` shows.
+const MAX_TIMELINE_PHASES: usize = 16;
+
+/// Show the rhythm of the pomo session running in this channel up to its next
+/// long break
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn timeline(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get(&channel_id) {
+        reply_timeline(
+            ctx,
+            &session.timeline(MAX_TIMELINE_PHASES),
+            session.is_running(),
+        )
+        .await;
+    } else {
+        reply_phases_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Show the settings of the pomo session running in this channel
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn config(
+    ctx: Context<'_>,
+    #[description = "How to show the settings (default: embed)"] format: Option<ConfigFormat>,
+) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get(&channel_id) {
+        match format.unwrap_or_default() {
+            ConfigFormat::Embed => reply_config(ctx, session).await,
+            ConfigFormat::Toml => reply_config_toml(ctx, session.config()).await,
+        }
+    } else {
+        reply_config_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Find out how long it's been since a pomo session ended in this channel
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn lastsession(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if ctx.data().sessions.lock().await.contains_key(&channel_id) {
+        reply_last_session_running(ctx).await;
+    } else if let Some(ended) = ctx
+        .data()
+        .last_sessions
+        .lock()
+        .await
+        .get(&channel_id)
+        .copied()
+    {
+        reply_last_session(ctx, ended).await;
+    } else {
+        reply_last_session_never(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Join the pomo session running in this channel to be notified when phases
+/// finish
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn join(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        if session.is_member(ctx.author().id) {
+            reply_join_already_member(ctx).await;
+        } else if !session.can_join_now(ctx.author().id) {
+            reply_join_during_break(ctx).await;
+        } else if !session.has_room_for(ctx.author().id) {
+            reply_join_full(ctx, session.members().len()).await;
+        } else {
+            let status = session.status();
+            session.add_member(ctx.author().id);
+            reply_joined(ctx, status).await;
+        }
+    } else {
+        reply_join_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Add someone to the pomo session you're hosting in this channel
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+pub async fn addmember(
+    ctx: Context<'_>,
+    #[description = "Who to add"] user: serenity::User,
+) -> Result<(), Error> {
+    enroll(ctx, vec![user.id]).await
+}
+
+/// Add everyone in your voice channel to the pomo session you're hosting in
+/// this channel
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+pub async fn joinall(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().expect("command is guild_only");
+    let host = ctx.author().id;
+
+    let users = ctx
+        .discord()
+        .cache
+        .guild_field(guild_id, |guild| {
+            let channel_id = guild.voice_states.get(&host)?.channel_id?;
+
+            Some(
+                guild
+                    .voice_states
+                    .values()
+                    .filter(|state| state.channel_id == Some(channel_id))
+                    .filter(|state| {
+                        !state
+                            .member
+                            .as_ref()
+                            .map_or(false, |member| member.user.bot)
+                    })
+                    .map(|state| state.user_id)
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten();
+
+    match users {
+        Some(users) => enroll(ctx, users).await,
+        None => {
+            reply_join_all_no_voice(ctx).await;
+
+            Ok(())
+        }
+    }
+}
+
+/// Add `users` to the session in this channel, as long as the author of `ctx`
+/// is its host, skipping anyone who has opted out with /optout.
+async fn enroll(ctx: Context<'_>, users: Vec<serenity::UserId>) -> Result<(), Error> {
+    let mut opted_out = HashSet::new();
+    for &user in &users {
+        if !ctx.data().user_settings(user).await.auto_enroll {
+            opted_out.insert(user);
+        }
+    }
+
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        if session.host() != ctx.author().id {
+            reply_not_host(ctx).await;
+        } else {
+            let enrollment = session.enroll(users, |user| opted_out.contains(&user));
+            reply_enrolled(ctx, &enrollment).await;
+        }
+    } else {
+        reply_join_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Choose whether session hosts can add you to their sessions with /addmember
+/// and /joinall
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn optout(
+    ctx: Context<'_>,
+    #[description = "Whether to opt out of being added by hosts (default: true)"] opt_out: Option<
+        bool,
+    >,
+) -> Result<(), Error> {
+    let opt_out = opt_out.unwrap_or(true);
+
+    ctx.data()
+        .user_settings
+        .lock()
+        .await
+        .entry(ctx.author().id)
+        .or_default()
+        .auto_enroll = !opt_out;
+
+    reply_opt_out_set(ctx, opt_out).await;
+
+    Ok(())
+}
+
+/// Choose whether to get a DM summarising what you took part in when a session
+/// you're in ends
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn accountability(
+    ctx: Context<'_>,
+    #[description = "Whether to get a summary at the end of each session (default: true)"]
+    enabled: Option<bool>,
+) -> Result<(), Error> {
+    let enabled = enabled.unwrap_or(true);
+
+    ctx.data()
+        .user_settings
+        .lock()
+        .await
+        .entry(ctx.author().id)
+        .or_default()
+        .accountability = enabled;
+
+    reply_accountability_set(ctx, enabled).await;
+
+    Ok(())
+}
+
+/// Manage the focus time stats recorded about you
+#[instrument(skip(_ctx))]
+#[poise::command(slash_command, subcommands("stats_reset", "stats_reset_server"))]
+pub async fn stats(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Delete all the focus time recorded for you, in every server
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, rename = "reset")]
+pub async fn stats_reset(ctx: Context<'_>) -> Result<(), Error> {
+    let user = ctx.author().id;
+    let deleted = ctx.data().stats.lock().await.delete_user(user);
+
+    match deleted {
+        Some(minutes) => {
+            info!(%user, minutes, "reset user stats");
+            reply_stats_reset(ctx, minutes).await;
+        }
+        None => reply_stats_reset_nothing(ctx).await,
+    }
+
+    Ok(())
+}
+
+/// Line up a pomo session to start as soon as the one running in this channel
+/// finishes
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn queue(
+    ctx: Context<'_>,
+    #[description = "Length of a work session in minutes (default: 25)"] work: Option<usize>,
+    #[description = "Length of a short break in minutes (default: 5)"] short: Option<usize>,
+    #[description = "Length of a long break in minutes (default: 15)"] long: Option<usize>,
+    #[description = "How many work sessions between each long break (default: 4)"] interval: Option<
+        usize,
+    >,
+) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if ctx.data().sessions.lock().await.contains_key(&channel_id) {
+        let session = SessionConfig::default()
+            .work_or_default(work)
+            .short_or_default(short)
+            .long_or_default(long)
+            .interval_or_default(interval)
+            .validate()?
+            .build(ctx.author().id);
+
+        reply_queued(ctx, session.config()).await;
+
+        ctx.data()
+            .queued_sessions
+            .lock()
+            .await
+            .insert(channel_id, session);
+    } else {
+        reply_queue_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Cancel the pomo session queued up in this channel
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn unqueue(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    let queued = ctx
+        .data()
+        .queued_sessions
+        .lock()
+        .await
+        .remove(&channel_id)
+        .is_some();
+
+    reply_unqueued(ctx, queued).await;
+
+    Ok(())
+}
+
+/// Leave the pomo session running in this channel to stop being notified
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn leave(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        if session.remove_member(ctx.author().id) {
+            reply_left(ctx).await;
+        } else {
+            reply_leave_not_member(ctx).await;
+        }
+    } else {
+        reply_leave_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Stop being pinged by the pomo session running in this channel for a while
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn silence(
+    ctx: Context<'_>,
+    #[description = "How many phase changes to stay quiet for, up to 20"] phases: usize,
+) -> Result<(), Error> {
+    if !(1..=MAX_SILENCE_PHASES).contains(&phases) {
+        reply_silence_invalid(ctx, MAX_SILENCE_PHASES).await;
+        return Ok(());
+    }
+
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        if session.is_member(ctx.author().id) {
+            session.silence(ctx.author().id, phases);
+
+            // The phase after the silenced ones is the first they'll be pinged
+            // for again.
+            let until = session.upcoming_phase_start(phases);
+
+            reply_silenced(ctx, phases, until).await;
+        } else {
+            reply_silence_not_member(ctx).await;
+        }
+    } else {
+        reply_silence_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// The most phase changes that can be silenced at once.
+const MAX_SILENCE_PHASES: usize = 20;
+
+/// Skip the current phase of the pomo session running in this channel
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn skip(
+    ctx: Context<'_>,
+    #[description = "How many phases to skip, up to 10 (default: 1)"] count: Option<usize>,
+    #[description = "Skip the first phase of the session without being asked if you're sure \
+                     (default: false)"]
+    confirm: Option<bool>,
+) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+    let first_skip = ctx.data().guild_settings(ctx.guild_id()).await.first_skip;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        let response = first_skip.response(session.is_first_phase(), confirm.unwrap_or(false));
+
+        if response == SkipResponse::AskToConfirm {
+            reply_skip_confirm_first(ctx).await;
+            return Ok(());
+        }
+
+        let count = count.unwrap_or(1).clamp(1, MAX_SKIP_COUNT);
+
+        match session.skip_many(count) {
+            Ok((skipped, next)) if skipped.len() == 1 => {
+                let first_phase = response == SkipResponse::SkipFirst;
+                reply_skipping_phase(ctx, skipped[0], next, first_phase, SKIP_UNDO_SECONDS).await
+            }
+            Ok((skipped, next)) => {
+                reply_skipping_phases(ctx, &skipped, next, SKIP_UNDO_SECONDS).await
+            }
+            Err(SessionError::NotActive) => reply_skip_failed(ctx, session.id()).await,
+        }
+    } else {
+        reply_skip_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Skip the current phase of the pomo session running in this channel, but only
+/// if it's a break
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn skipbreak(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        match session.status() {
+            SessionStatus::Running {
+                phase_type: PhaseType::Short(_) | PhaseType::Long(_),
+                ..
+            } => match session.skip_and_report_next() {
+                Ok((skipped, next)) => {
+                    reply_skipping_phase(ctx, skipped, next, false, SKIP_UNDO_SECONDS).await
+                }
+                Err(SessionError::NotActive) => reply_skip_failed(ctx, session.id()).await,
+            },
+            SessionStatus::Running {
+                phase_type: PhaseType::Work(_),
+                ..
+            } => reply_skip_break_working(ctx).await,
+            SessionStatus::NoSession => reply_skip_failed(ctx, session.id()).await,
+        }
+    } else {
+        reply_skip_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// How many seconds after a phase is skipped that the skip can be undone.
+const SKIP_UNDO_SECONDS: i64 = 30;
+
+/// The most phases that can be skipped at once.
+const MAX_SKIP_COUNT: usize = 10;
+
+/// Go back to the phase that was just skipped, with the time it had left
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn undo(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        match session.undo_skip(Duration::seconds(SKIP_UNDO_SECONDS)) {
+            Some((resumed, remaining)) => reply_undone(ctx, resumed, remaining).await,
+            None => reply_undo_unavailable(ctx, SKIP_UNDO_SECONDS).await,
+        }
+    } else {
+        reply_undo_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Move the pomo session running in this channel to another channel, keeping
+/// its members and progress
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, rename = "move")]
+pub async fn move_session(
+    ctx: Context<'_>,
+    #[description = "Channel to move the session to"] channel: serenity::Channel,
+) -> Result<(), Error> {
+    let from = session_channel(ctx).await;
+    let to = channel.id();
+
+    let host = ctx
+        .data()
+        .sessions
+        .lock()
+        .await
+        .get(&from)
+        .map(Session::host);
+
+    let host = match host {
+        Some(host) => host,
+        None => {
+            reply_move_no_session(ctx).await;
+            return Ok(());
+        }
+    };
+
+    if host != ctx.author().id && !is_admin(ctx).await? {
+        reply_move_not_host(ctx).await;
+    } else if to == from {
+        reply_move_same_channel(ctx).await;
+    } else {
+        let mut sessions = ctx.data().sessions.lock().await;
+
+        if sessions.contains_key(&to) {
+            drop(sessions);
+            reply_move_occupied(ctx).await;
+        } else if let Some(session) = sessions.remove(&from) {
+            // The task running the session finds it again by its ID, so it just
+            // needs to be stored under the new channel.
+            sessions.insert(to, session);
+            drop(sessions);
+
+            let mut queued_sessions = ctx.data().queued_sessions.lock().await;
+            if let Some(queued) = queued_sessions.remove(&from) {
+                queued_sessions.insert(to, queued);
+            }
+            drop(queued_sessions);
+
+            info!(%from, %to, "moved session");
+
+            reply_session_moved(ctx, to).await;
+            say_session_moved_here(
+                SessionContext {
+                    channel_id: to,
+                    ..SessionContext::from(ctx)
+                },
+                from,
+            )
+            .await;
+        } else {
+            // It must have ended while we were checking whether the author is an
+            // admin.
+            drop(sessions);
+            reply_move_no_session(ctx).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Take a break from the current work phase, and carry on with it afterwards
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn breaknow(
+    ctx: Context<'_>,
+    #[description = "Length of the break in minutes, up to a day (default: the session's short \
+                     break)"]
+    minutes: Option<usize>,
+) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        let minutes = minutes.unwrap_or(session.config().short);
+
+        if !(1..=MAX_BREAK_NOW_MINUTES).contains(&minutes) {
+            reply_break_now_invalid(ctx, MAX_BREAK_NOW_MINUTES).await;
+            return Ok(());
+        }
+
+        match session.current_phase_type() {
+            Some(PhaseType::Work(_)) => match session.break_now(minutes) {
+                Ok(resuming) => reply_break_now(ctx, minutes, resuming).await,
+                Err(SessionError::NotActive) => reply_skip_failed(ctx, session.id()).await,
+            },
+            Some(PhaseType::Short(_) | PhaseType::Long(_)) => {
+                reply_break_now_not_working(ctx).await
+            }
+            None => reply_skip_failed(ctx, session.id()).await,
+        }
+    } else {
+        reply_break_now_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// The most minutes a break from `/breaknow` can last.
+const MAX_BREAK_NOW_MINUTES: usize = 24 * 60;
+
+/// The most minutes the current phase can be set to have left.
+const MAX_SET_TIME_MINUTES: usize = 24 * 60;
+
+/// Set exactly how long the current phase of the pomo session in this channel
+/// has left
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, rename = "settime")]
+pub async fn set_time(
+    ctx: Context<'_>,
+    #[description = "How many minutes the current phase should have left"] minutes: usize,
+) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        if !(1..=MAX_SET_TIME_MINUTES).contains(&minutes) {
+            reply_set_time_invalid(ctx, MAX_SET_TIME_MINUTES).await;
+            return Ok(());
+        }
+
+        match (
+            session.current_phase_type(),
+            session.set_remaining(Duration::minutes(minutes as i64)),
+        ) {
+            (Some(phase_type), Ok(end)) => reply_time_set(ctx, phase_type, end).await,
+            _ => reply_set_time_failed(ctx, session.id()).await,
+        }
+    } else {
+        reply_set_time_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Restart the phase sequence of the pomo session running in this channel from
+/// the first work phase
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn reset(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        match session.reset_sequence() {
+            Ok(skipped_type) => reply_reset_sequence(ctx, skipped_type).await,
+            Err(SessionError::NotActive) => reply_reset_failed(ctx, session.id()).await,
+        }
+    } else {
+        reply_reset_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Change how many work sessions there are between each long break, from now on
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn adjust_interval(
+    ctx: Context<'_>,
+    #[description = "How many work sessions between each long break"] interval: usize,
+) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        match session.adjust_interval(interval) {
+            Ok(()) => {
+                let long_at = match session.status() {
+                    SessionStatus::Running { long_at, .. } => long_at,
+                    SessionStatus::NoSession => None,
+                };
+
+                reply_interval_adjusted(ctx, session.config(), long_at).await;
+            }
+            Err(error) => reply_adjust_interval_invalid(ctx, error).await,
+        }
+    } else {
+        reply_adjust_interval_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// End the pomo session running in this channel once the current phase is over
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn finish(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        session.set_finishing(true);
+        reply_finishing(ctx, session.current_phase_type()).await;
+    } else {
+        reply_finish_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Keep the pomo session running in this channel going after all, after using
+/// /finish
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn unfinish(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        if session.finishing() {
+            session.set_finishing(false);
+            reply_unfinished(ctx).await;
+        } else {
+            reply_unfinish_not_finishing(ctx).await;
+        }
+    } else {
+        reply_finish_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// Stop the pomo session currently running in this channel
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+pub async fn stop(ctx: Context<'_>) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+
+    if let Some(session) = ctx.data().sessions.lock().await.get_mut(&channel_id) {
+        match session.stop() {
+            Ok(()) => reply_stopping_session(ctx).await,
+            Err(SessionError::NotActive) => reply_stop_failed(ctx, session.id()).await,
+        }
+    } else {
+        reply_stop_no_session(ctx).await;
+    }
+
+    Ok(())
+}
+
+/// How long members have to vote on whether they're ready to stop.
+const STOP_POLL_SECONDS: i64 = 120;
+
+/// Ask this session's members whether they're ready to stop, and stop it if
+/// enough are
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, rename = "readytostop")]
+pub async fn ready_to_stop(
+    ctx: Context<'_>,
+    #[description = "Who needs to be ready (default: everyone)"] consensus: Option<Consensus>,
+) -> Result<(), Error> {
+    let channel_id = session_channel(ctx).await;
+    let consensus = consensus.unwrap_or_default();
+
+    let (id, members) = match ctx.data().sessions.lock().await.get(&channel_id) {
+        Some(session) if session.is_member(ctx.author().id) => {
+            (session.id(), session.members().clone())
+        }
+        Some(_) => {
+            reply_stop_poll_not_member(ctx).await;
+            return Ok(());
+        }
+        None => {
+            reply_stop_no_session(ctx).await;
+            return Ok(());
+        }
+    };
+
+    let mut vote = Vote::new(members, consensus);
+    let closes = Utc::now() + Duration::seconds(STOP_POLL_SECONDS);
+
+    let mut message = match reply_stop_poll(ctx, vote.tally(), consensus, closes).await {
+        Some(message) => message,
+        None => return Ok(()),
+    };
+
+    while vote.outcome().is_none() {
+        let remaining = match (closes - Utc::now()).to_std() {
+            Ok(remaining) => remaining,
+            Err(_) => break,
+        };
+
+        let interaction = match serenity::CollectComponentInteraction::new(ctx.discord())
+            .message_id(message.id)
+            .timeout(remaining)
+            .await
+        {
+            Some(interaction) => interaction,
+            None => break,
+        };
+
+        let ready = interaction.data.custom_id == READY_TO_STOP_BUTTON;
+
+        if vote.cast(interaction.user.id, ready) {
+            respond_stop_poll_updated(ctx, &interaction, vote.tally(), consensus, closes).await;
+        } else {
+            respond_stop_poll_not_member(ctx, &interaction).await;
+        }
+    }
+
+    let outcome = vote.outcome();
+
+    if outcome == Some(Outcome::Passed) {
+        // The session might have been stopped or replaced while the vote was
+        // going on, in which case there's nothing left to stop.
+        if let Some(session) = ctx
+            .data()
+            .sessions
+            .lock()
+            .await
+            .get_mut(&channel_id)
+            .filter(|session| session.id() == id)
+        {
+            if let Err(error) = session.stop() {
+                warn!(?error, "unable to stop session after stop poll");
+            }
+        }
+    }
+
+    edit_stop_poll_closed(ctx, &mut message, outcome, vote.tally()).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration as StdDuration,
+    };
+
+    use async_trait::async_trait;
+    use chrono::DateTime;
+    use poise::serenity_prelude::{ApplicationId, MessageId, UserId};
+    use tokio::time::sleep;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{pomo::clock::FakeClock, stats::MemberParticipation};
+
+    #[derive(Debug, PartialEq)]
+    enum Notification {
+        PhaseFinished {
+            finished: PhaseType,
+            next: PhaseType,
+        },
+        Countdown,
+        Topic(PhaseType),
+        RestoredTopic,
+        TopicDisabled,
+        Finished {
+            work_phases: usize,
+            focus_minutes: usize,
+        },
+        FinishedBriefly,
+        Stopped,
+        Expired,
+        Failed,
+        QueuedSessionStarting,
+        Unpinned,
+        RouletteSpun(usize),
+        Accountability {
+            user: UserId,
+            participation: MemberParticipation,
+            total_phases: usize,
+        },
+    }
+
+    /// Records everything it's told, in order.
+    #[derive(Clone, Default)]
+    struct RecordingNotifier(Arc<Mutex<Vec<Notification>>>);
+
+    impl RecordingNotifier {
+        fn push(&self, notification: Notification) {
+            self.0.lock().unwrap().push(notification);
+        }
+
+        fn len(&self) -> usize {
+            self.0.lock().unwrap().len()
+        }
+
+        /// Wait until at least `count` notifications have been recorded.
+        async fn wait_for(&self, count: usize) {
+            while self.len() < count {
+                sleep(StdDuration::from_millis(10)).await;
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn session_started(&self, _first: PhaseType, _members: Vec<UserId>) {}
+
+        async fn phase_finished(
+            &self,
+            finished: PhaseType,
+            next: PhaseType,
+            _number: Option<usize>,
+            _members: Vec<UserId>,
+            _start_link: Option<String>,
+            _silent: bool,
+        ) {
+            self.push(Notification::PhaseFinished { finished, next });
+        }
+
+        async fn phase_changed(&self, _finished: PhaseType, _next: PhaseType) {}
+
+        async fn countdown(&self, _end: DateTime<Utc>) {
+            self.push(Notification::Countdown);
+            std::future::pending().await
+        }
+
+        async fn topic(&self) -> Option<String> {
+            None
+        }
+
+        async fn show_phase_in_topic(&self, phase_type: PhaseType, _end: DateTime<Utc>) -> bool {
+            self.push(Notification::Topic(phase_type));
+            true
+        }
+
+        async fn restore_topic(&self, _topic: Option<String>) {
+            self.push(Notification::RestoredTopic);
+        }
+
+        async fn topic_disabled(&self) {
+            self.push(Notification::TopicDisabled);
+        }
+
+        async fn session_finished(&self, work_phases: usize, focus_minutes: usize) {
+            self.push(Notification::Finished {
+                work_phases,
+                focus_minutes,
+            });
+        }
+
+        async fn session_finished_briefly(&self) {
+            self.push(Notification::FinishedBriefly);
+        }
+
+        async fn session_stopped(&self) {
+            self.push(Notification::Stopped);
+        }
+
+        async fn session_expired(&self) {
+            self.push(Notification::Expired);
+        }
+
+        async fn session_failed(&self, _id: Uuid) {
+            self.push(Notification::Failed);
+        }
+
+        async fn queued_session_starting(&self, _config: &SessionConfig) {
+            self.push(Notification::QueuedSessionStarting);
+        }
+
+        async fn unpin_message(&self, _channel_id: ChannelId, _message_id: MessageId) {
+            self.push(Notification::Unpinned);
+        }
+
+        async fn roulette_spun(&self, minutes: usize) {
+            self.push(Notification::RouletteSpun(minutes));
+        }
+
+        async fn accountability_summary(
+            &self,
+            user: UserId,
+            participation: MemberParticipation,
+            total_phases: usize,
+        ) {
+            self.push(Notification::Accountability {
+                user,
+                participation,
+                total_phases,
+            });
+        }
+
+        async fn session_ended(&self, _participants: Vec<UserId>) {}
+    }
+
+    #[tokio::test]
+    async fn full_session() {
+        let data = Data::new(ApplicationId(1), UserId(1), Duration::hours(8));
+        let channel_id = ChannelId(1);
+        let clock = Arc::new(FakeClock::new());
+        let notifier = RecordingNotifier::default();
+
+        let mut session = SessionConfig::default()
+            .work(1)
+            .short(1)
+            .long(2)
+            .interval(2)
+            .build(UserId(1));
+        session.set_clock(clock.clone());
+
+        let drive = async {
+            wait_for_session(&data, channel_id, None).await;
+
+            for count in 1..=3 {
+                clock.advance(Duration::minutes(1));
+                notifier.wait_for(count).await;
+            }
+
+            data.sessions
+                .lock()
+                .await
+                .get_mut(&channel_id)
+                .expect("session is still running")
+                .stop()
+                .expect("a phase is running");
+            notifier.wait_for(4).await;
+        };
+
+        let (result, ()) = tokio::join!(
+            run_session_with(&data, channel_id, None, |_| notifier.clone(), session),
+            drive
+        );
+        assert!(result.is_ok());
+
+        assert_eq!(
+            *notifier.0.lock().unwrap(),
+            vec![
+                Notification::PhaseFinished {
+                    finished: PhaseType::Work(1),
+                    next: PhaseType::Short(1),
+                },
+                Notification::PhaseFinished {
+                    finished: PhaseType::Short(1),
+                    next: PhaseType::Work(1),
+                },
+                Notification::PhaseFinished {
+                    finished: PhaseType::Work(1),
+                    next: PhaseType::Long(2),
+                },
+                Notification::Stopped,
+            ]
+        );
+        assert!(!data.sessions.lock().await.contains_key(&channel_id));
+    }
+
+    #[tokio::test]
+    async fn topic_is_restored() {
+        let data = Data::new(ApplicationId(1), UserId(1), Duration::hours(8));
+        let channel_id = ChannelId(1);
+        let notifier = RecordingNotifier::default();
+
+        let mut session = SessionConfig::default().work(1).build(UserId(1));
+        session.set_topic(true);
+
+        let drive = async {
+            notifier.wait_for(1).await;
+
+            data.sessions
+                .lock()
+                .await
+                .get_mut(&channel_id)
+                .expect("session is still running")
+                .stop()
+                .expect("a phase is running");
+        };
+
+        let (result, ()) = tokio::join!(
+            run_session_with(&data, channel_id, None, |_| notifier.clone(), session),
+            drive
+        );
+        assert!(result.is_ok());
+
+        assert_eq!(
+            *notifier.0.lock().unwrap(),
+            vec![
+                Notification::Topic(PhaseType::Work(1)),
+                Notification::Stopped,
+                Notification::RestoredTopic,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_topic_does_not_hold_up_phase() {
+        let completed = || async { PhaseResult::Completed(PhaseType::Work(25)) };
+
+        let (changed, result) = alongside_topic(std::future::pending(), completed()).await;
+        assert!(
+            changed,
+            "an abandoned edit should count as changing the topic"
+        );
+        assert!(matches!(
+            result,
+            PhaseResult::Completed(PhaseType::Work(25))
+        ));
+
+        let (changed, _) = alongside_topic(async { false }, completed()).await;
+        assert!(!changed);
+    }
+
+    #[tokio::test]
+    async fn pinned_message_is_unpinned() {
+        for pin in [false, true] {
+            let data = Data::new(ApplicationId(1), UserId(1), Duration::hours(8));
+            let channel_id = ChannelId(1);
+            let notifier = RecordingNotifier::default();
+
+            let mut session = SessionConfig::default().build(UserId(1));
+            session.set_start_message(Some((channel_id, MessageId(1))));
+            session.set_pin(pin);
+
+            let drive = async {
+                wait_for_session(&data, channel_id, None).await;
+
+                data.sessions
+                    .lock()
+                    .await
+                    .get_mut(&channel_id)
+                    .expect("session is still running")
+                    .stop()
+                    .expect("a phase is running");
+            };
+
+            let (result, ()) = tokio::join!(
+                run_session_with(&data, channel_id, None, |_| notifier.clone(), session),
+                drive
+            );
+            assert!(result.is_ok());
+
+            let expected = if pin {
+                vec![Notification::Stopped, Notification::Unpinned]
+            } else {
+                vec![Notification::Stopped]
+            };
+            assert_eq!(*notifier.0.lock().unwrap(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn session_expires() {
+        let data = Data::new(ApplicationId(1), UserId(1), Duration::hours(8));
+        let channel_id = ChannelId(1);
+        let clock = Arc::new(FakeClock::new());
+        let notifier = RecordingNotifier::default();
+
+        let mut session = SessionConfig::default().work(5).build(UserId(1));
+        session.set_clock(clock.clone());
+        session.set_max_length(Some(Duration::minutes(2)));
+
+        let drive = async {
+            wait_for_session(&data, channel_id, None).await;
+
+            clock.advance(Duration::minutes(2));
+            notifier.wait_for(1).await;
+        };
+
+        let (result, ()) = tokio::join!(
+            run_session_with(&data, channel_id, None, |_| notifier.clone(), session),
+            drive
+        );
+        assert!(result.is_ok());
+
+        assert_eq!(*notifier.0.lock().unwrap(), vec![Notification::Expired]);
+        assert!(!data.sessions.lock().await.contains_key(&channel_id));
+    }
+
+    #[tokio::test]
+    async fn finish_announcement() {
+        for (announcement, expected) in [
+            (
+                FinishAnnouncement::Full,
+                Some(Notification::Finished {
+                    work_phases: 1,
+                    focus_minutes: 1,
+                }),
+            ),
+            (
+                FinishAnnouncement::Brief,
+                Some(Notification::FinishedBriefly),
+            ),
+            (FinishAnnouncement::Silent, None),
+        ] {
+            let data = Data::new(ApplicationId(1), UserId(1), Duration::hours(8));
+            let channel_id = ChannelId(1);
+            let clock = Arc::new(FakeClock::new());
+            let notifier = RecordingNotifier::default();
+
+            let mut session = SessionConfig::default().work(1).build(UserId(1));
+            session.set_clock(clock.clone());
+            session.set_finish_announcement(announcement);
+
+            let drive = async {
+                wait_for_session(&data, channel_id, None).await;
+
+                data.sessions
+                    .lock()
+                    .await
+                    .get_mut(&channel_id)
+                    .expect("session is still running")
+                    .set_finishing(true);
+                clock.advance(Duration::minutes(1));
+            };
+
+            let (result, ()) = tokio::join!(
+                run_session_with(&data, channel_id, None, |_| notifier.clone(), session),
+                drive
+            );
+            assert!(result.is_ok());
+
+            assert_eq!(
+                *notifier.0.lock().unwrap(),
+                expected.into_iter().collect::<Vec<_>>(),
+                "wrong announcement for {:?}",
+                announcement
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn moved_session_keeps_running() {
+        let data = Data::new(ApplicationId(1), UserId(1), Duration::hours(8));
+        let from = ChannelId(1);
+        let to = ChannelId(2);
+        let clock = Arc::new(FakeClock::new());
+        let notifier = RecordingNotifier::default();
+
+        let mut session = SessionConfig::default().work(1).short(1).build(UserId(1));
+        session.set_clock(clock.clone());
+
+        let drive = async {
+            wait_for_session(&data, from, None).await;
+
+            let mut sessions = data.sessions.lock().await;
+            let session = sessions.remove(&from).expect("session is still running");
+            sessions.insert(to, session);
+            drop(sessions);
+
+            clock.advance(Duration::minutes(1));
+            notifier.wait_for(1).await;
+
+            data.sessions
+                .lock()
+                .await
+                .get_mut(&to)
+                .expect("session is running in the new channel")
+                .stop()
+                .expect("a phase is running");
+            notifier.wait_for(2).await;
+        };
+
+        let (result, ()) = tokio::join!(
+            run_session_with(&data, from, None, |_| notifier.clone(), session),
+            drive
+        );
+        assert!(result.is_ok());
+
+        assert_eq!(
+            *notifier.0.lock().unwrap(),
+            vec![
+                Notification::PhaseFinished {
+                    finished: PhaseType::Work(1),
+                    next: PhaseType::Short(1),
+                },
+                Notification::Stopped,
+            ]
+        );
+        assert!(data.sessions.lock().await.is_empty());
+
+        let last_sessions = data.last_sessions.lock().await;
+        assert!(last_sessions.contains_key(&to));
+        assert!(!last_sessions.contains_key(&from));
+    }
+
+    /// Wait until a session with an ID other than `not` is running in
+    /// `channel_id`.
+    async fn wait_for_session(data: &Data, channel_id: ChannelId, not: Option<Uuid>) {
+        while !data
+            .sessions
+            .lock()
+            .await
+            .get(&channel_id)
+            .map_or(false, |session| {
+                session.is_running() && Some(session.id()) != not
+            })
+        {
+            sleep(StdDuration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn queued_session_starts_after_finish() {
+        let data = Data::new(ApplicationId(1), UserId(1), Duration::hours(8));
+        let channel_id = ChannelId(1);
+        let clock = Arc::new(FakeClock::new());
+        let notifier = RecordingNotifier::default();
+
+        let mut session = SessionConfig::default().work(1).build(UserId(1));
+        session.set_clock(clock.clone());
+        session.add_member(UserId(2));
+        let first = session.id();
+
+        let mut queued = SessionConfig::default().work(2).build(UserId(3));
+        queued.set_clock(clock.clone());
+        let second = queued.id();
+
+        let drive = async {
+            wait_for_session(&data, channel_id, None).await;
+            data.queued_sessions.lock().await.insert(channel_id, queued);
+
+            data.sessions
+                .lock()
+                .await
+                .get_mut(&channel_id)
+                .expect("session is still running")
+                .set_finishing(true);
+            clock.advance(Duration::minutes(1));
+
+            wait_for_session(&data, channel_id, Some(first)).await;
+
+            let mut sessions = data.sessions.lock().await;
+            let session = sessions
+                .get_mut(&channel_id)
+                .expect("queued session is running");
+            assert_eq!(session.id(), second);
+            assert_eq!(session.config().work, 2);
+            assert!(session.is_member(UserId(2)), "members weren't carried over");
+            session.stop().expect("a phase is running");
+        };
+
+        let (result, ()) = tokio::join!(
+            run_session_with(&data, channel_id, None, |_| notifier.clone(), session),
+            drive
+        );
+        assert!(result.is_ok());
+
+        assert_eq!(
+            *notifier.0.lock().unwrap(),
+            vec![
+                Notification::Finished {
+                    work_phases: 1,
+                    focus_minutes: 1,
+                },
+                Notification::QueuedSessionStarting,
+                Notification::Stopped,
+            ]
+        );
+        assert!(data.queued_sessions.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn stop_cancels_queued_session() {
+        let data = Data::new(ApplicationId(1), UserId(1), Duration::hours(8));
+        let channel_id = ChannelId(1);
+        let notifier = RecordingNotifier::default();
+
+        let session = SessionConfig::default().build(UserId(1));
+        let queued = SessionConfig::default().build(UserId(1));
+
+        let drive = async {
+            wait_for_session(&data, channel_id, None).await;
+            data.queued_sessions.lock().await.insert(channel_id, queued);
+
+            data.sessions
+                .lock()
+                .await
+                .get_mut(&channel_id)
+                .expect("session is still running")
+                .stop()
+                .expect("a phase is running");
+        };
+
+        let (result, ()) = tokio::join!(
+            run_session_with(&data, channel_id, None, |_| notifier.clone(), session),
+            drive
+        );
+        assert!(result.is_ok());
+
+        assert_eq!(*notifier.0.lock().unwrap(), vec![Notification::Stopped]);
+        assert!(data.sessions.lock().await.is_empty());
+        assert!(data.queued_sessions.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn concurrent_starts_in_one_channel() {
+        let data = Data::new(ApplicationId(1), UserId(1), Duration::hours(8));
+        let channel_id = ChannelId(1);
+        let notifier = RecordingNotifier::default();
+
+        let first = SessionConfig::default().build(UserId(1));
+        let second = SessionConfig::default().build(UserId(2));
+        let first_id = first.id();
+
+        let drive = async {
+            wait_for_session(&data, channel_id, None).await;
+
+            let mut sessions = data.sessions.lock().await;
+            let session = sessions
+                .get_mut(&channel_id)
+                .expect("session is still running");
+            assert_eq!(
+                session.id(),
+                first_id,
+                "the second session replaced the first"
+            );
+            session.stop().expect("a phase is running");
+        };
+
+        let (first_result, second_result, ()) = tokio::join!(
+            run_session_with(&data, channel_id, None, |_| notifier.clone(), first),
+            run_session_with(&data, channel_id, None, |_| notifier.clone(), second),
+            drive
+        );
+
+        assert!(first_result.is_ok());
+        assert!(matches!(second_result, Err(Error::AlreadyRunning)));
+
+        assert_eq!(*notifier.0.lock().unwrap(), vec![Notification::Stopped]);
+        assert!(data.sessions.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn accountability_summaries() {
+        let data = Data::new(ApplicationId(1), UserId(1), Duration::hours(8));
+        let channel_id = ChannelId(1);
+        let clock = Arc::new(FakeClock::new());
+        let notifier = RecordingNotifier::default();
+
+        for user in [UserId(1), UserId(3)] {
+            data.user_settings
+                .lock()
+                .await
+                .entry(user)
+                .or_default()
+                .accountability = true;
+        }
+
+        let mut session = SessionConfig::default().work(1).short(1).build(UserId(1));
+        session.set_clock(clock.clone());
+        session.add_member(UserId(2));
+        session.add_member(UserId(3));
+
+        let drive = async {
+            wait_for_session(&data, channel_id, None).await;
+
+            clock.advance(Duration::minutes(1));
+            notifier.wait_for(1).await;
+
+            let mut sessions = data.sessions.lock().await;
+            let session = sessions
+                .get_mut(&channel_id)
+                .expect("session is still running");
+            session.remove_member(UserId(3));
+            session.set_finishing(true);
+            drop(sessions);
+
+            clock.advance(Duration::minutes(1));
+        };
+
+        let (result, ()) = tokio::join!(
+            run_session_with(&data, channel_id, None, |_| notifier.clone(), session),
+            drive
+        );
+        assert!(result.is_ok());
+
+        let notifications = notifier.0.lock().unwrap();
+        let mut summaries = notifications
+            .iter()
+            .filter_map(|notification| match notification {
+                Notification::Accountability {
+                    user,
+                    participation,
+                    total_phases,
+                } => Some((*user, *participation, *total_phases)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        summaries.sort_by_key(|(user, ..)| *user);
+
+        assert_eq!(
+            summaries,
+            vec![
+                (
+                    UserId(1),
+                    MemberParticipation {
+                        phases: 2,
+                        focus_minutes: 1,
+                    },
+                    2
+                ),
+                (
+                    UserId(3),
+                    MemberParticipation {
+                        phases: 1,
+                        focus_minutes: 1,
+                    },
+                    2
+                ),
+            ],
+            "only opted-in members should get summaries, including ones who left"
+        );
+    }
+
+    #[tokio::test]
+    async fn session_replaced_while_running() {
+        let data = Data::new(ApplicationId(1), UserId(1), Duration::hours(8));
+        let channel_id = ChannelId(1);
+        let clock = Arc::new(FakeClock::new());
+        let notifier = RecordingNotifier::default();
+
+        let mut session = SessionConfig::default().build(UserId(1));
+        session.set_clock(clock.clone());
+        let original = session.id();
+
+        let replacement = SessionConfig::default().build(UserId(2));
+        let replacement_id = replacement.id();
+
+        let drive = async {
+            wait_for_session(&data, channel_id, None).await;
+
+            // Stop the session and swap another one in under the same lock, like a
+            // reload of saved sessions could.
+            let mut sessions = data.sessions.lock().await;
+            sessions
+                .get_mut(&channel_id)
+                .expect("session is still running")
+                .stop()
+                .expect("a phase is running");
+            sessions.remove(&channel_id);
+            sessions.insert(channel_id, replacement);
+        };
+
+        let (result, ()) = tokio::join!(
+            run_session_with(&data, channel_id, None, |_| notifier.clone(), session),
+            drive
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(
+            data.sessions.lock().await.get(&channel_id).map(Session::id),
+            Some(replacement_id),
+            "session {} shouldn't have removed the session that replaced it",
+            original
+        );
+    }
 }
@@ -0,0 +1,336 @@
+use std::{
+    collections::HashMap,
+    env::{var, VarError},
+    str::FromStr,
+    time::Duration,
+};
+
+#[cfg(feature = "daily-stats")]
+use chrono::NaiveTime;
+#[cfg(feature = "daily-stats")]
+use chrono_tz::Tz;
+
+#[cfg(feature = "phase-hooks")]
+use crate::hooks::PhaseHooks;
+use crate::{aliases::TextAliases, pomo::session::PhaseType, Error, PomocopError};
+
+/// The thumbnail shown when a work phase starts, unless another is configured.
+const DEFAULT_WORK_THUMBNAIL: &str = "https://twemoji.maxcdn.com/v/latest/72x72/1f345.png";
+
+/// The thumbnail shown when a break starts, unless another is configured.
+const DEFAULT_BREAK_THUMBNAIL: &str = "https://twemoji.maxcdn.com/v/latest/72x72/2615.png";
+
+/// The longest that edits to prefix commands can be tracked for. Every tracked
+/// message is kept in memory, so this stops it from growing without bound.
+const MAX_EDIT_TRACKER_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+/// The most times that connecting to Discord at startup can be retried.
+const MAX_START_RETRIES: u32 = 100;
+
+/// The longest to wait between attempts to connect to Discord at startup, no
+/// matter how many times it's been retried.
+const MAX_START_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Configuration for the bot, read from environment variables at startup.
+pub struct Config {
+    pub application_id: String,
+    pub owner_id: String,
+    pub prefix: String,
+    /// Whether to request the privileged `MESSAGE_CONTENT` intent, which
+    /// prefix commands need to see the prefix.
+    pub message_content: bool,
+    pub token: String,
+    /// How many times to retry connecting to Discord at startup before giving
+    /// up.
+    pub start_retries: u32,
+    /// How long to wait before the first retry. Each subsequent retry waits
+    /// twice as long as the previous one, up to [`MAX_START_RETRY_DELAY`].
+    pub start_retry_delay: Duration,
+    /// How long after a prefix command is sent that editing it runs the
+    /// command again.
+    pub edit_tracker_timespan: Duration,
+    /// The longest any session can run for, unless the host gives it a
+    /// different maximum when starting it.
+    pub max_session_length: Duration,
+    /// The most sessions that can run at once across every server, or `None`
+    /// if there's no limit.
+    pub max_sessions: Option<usize>,
+    /// How long after someone's pinged about a phase change that they won't be
+    /// pinged again.
+    pub ping_cooldown: Duration,
+    /// The ID of the channel to send feedback from `/feedback` to.
+    pub feedback_channel_id: Option<u64>,
+    pub phase_thumbnails: PhaseThumbnails,
+    /// Phrases that can be sent to the bot instead of some commands.
+    pub text_aliases: TextAliases,
+    /// The time of day to post the previous day's stats at.
+    #[cfg(feature = "daily-stats")]
+    pub daily_stats_time: NaiveTime,
+    /// The time zone that `daily_stats_time` is in.
+    #[cfg(feature = "daily-stats")]
+    pub daily_stats_timezone: Tz,
+    /// What to run at the start and end of each phase.
+    #[cfg(feature = "phase-hooks")]
+    pub phase_hooks: PhaseHooks,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, Error> {
+        Self::from_vars(&Env::default())
+    }
+
+    /// Read the configuration from `env`.
+    fn from_vars(env: &Env) -> Result<Self, Error> {
+        Ok(Self {
+            application_id: required(env, "APPLICATION_ID")?,
+            owner_id: required(env, "OWNER_ID")?,
+            prefix: env.var("PREFIX").unwrap_or_else(|_| "|".into()),
+            message_content: var_or(env, "MESSAGE_CONTENT", false)?,
+            token: required(env, "TOKEN")?,
+            start_retries: start_retries(env)?,
+            start_retry_delay: start_retry_delay(env)?,
+            edit_tracker_timespan: edit_tracker_timespan(env)?,
+            max_session_length: Duration::from_secs(var_or(env, "MAX_SESSION_HOURS", 8)? * 60 * 60),
+            max_sessions: match env.var("MAX_SESSIONS") {
+                Ok(value) => Some(
+                    value
+                        .parse()
+                        .map_err(|error| PomocopError::config("MAX_SESSIONS", error))?,
+                ),
+                Err(_) => None,
+            },
+            ping_cooldown: Duration::from_secs(var_or(env, "PING_COOLDOWN_SECONDS", 0)?),
+            feedback_channel_id: match env.var("FEEDBACK_CHANNEL_ID") {
+                Ok(value) => Some(
+                    value
+                        .parse()
+                        .map_err(|error| PomocopError::config("FEEDBACK_CHANNEL_ID", error))?,
+                ),
+                Err(_) => None,
+            },
+            phase_thumbnails: PhaseThumbnails {
+                work: thumbnail(env, "WORK_THUMBNAIL_URL", DEFAULT_WORK_THUMBNAIL)?,
+                breaks: thumbnail(env, "BREAK_THUMBNAIL_URL", DEFAULT_BREAK_THUMBNAIL)?,
+            },
+            text_aliases: match env.var("TEXT_ALIASES") {
+                Ok(value) => TextAliases::parse(&value)
+                    .map_err(|error| PomocopError::config("TEXT_ALIASES", error))?,
+                Err(_) => TextAliases::default(),
+            },
+            #[cfg(feature = "daily-stats")]
+            daily_stats_time: match env.var("DAILY_STATS_TIME") {
+                Ok(value) => NaiveTime::parse_from_str(&value, "%H:%M")
+                    .map_err(|error| PomocopError::config("DAILY_STATS_TIME", error))?,
+                Err(_) => NaiveTime::from_hms(0, 0, 0),
+            },
+            #[cfg(feature = "daily-stats")]
+            daily_stats_timezone: match env.var("DAILY_STATS_TIMEZONE") {
+                Ok(value) => value
+                    .parse::<Tz>()
+                    .map_err(|error| PomocopError::config("DAILY_STATS_TIMEZONE", error))?,
+                Err(_) => chrono_tz::UTC,
+            },
+            #[cfg(feature = "phase-hooks")]
+            phase_hooks: PhaseHooks::new(
+                env.var("PHASE_HOOK_COMMAND").ok().map(Into::into),
+                match env.var("PHASE_HOOK_URL") {
+                    Ok(value) => Some(
+                        value
+                            .parse()
+                            .map_err(|error| PomocopError::config("PHASE_HOOK_URL", error))?,
+                    ),
+                    Err(_) => None,
+                },
+            ),
+        })
+    }
+
+    /// Get how long to wait before retrying to connect to Discord, when it's
+    /// already been retried `attempt` times.
+    pub fn retry_delay(&self, attempt: u32) -> Duration {
+        2u32.checked_pow(attempt)
+            .and_then(|factor| self.start_retry_delay.checked_mul(factor))
+            .map_or(MAX_START_RETRY_DELAY, |delay| {
+                delay.min(MAX_START_RETRY_DELAY)
+            })
+    }
+}
+
+/// The images to show in the announcement of each type of phase starting.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhaseThumbnails {
+    pub work: Option<String>,
+    /// The image for both short and long breaks.
+    pub breaks: Option<String>,
+}
+
+impl PhaseThumbnails {
+    /// Get the URL of the image to show when a phase of type `phase_type`
+    /// starts, if there is one.
+    pub fn for_phase(&self, phase_type: PhaseType) -> Option<&str> {
+        match phase_type {
+            PhaseType::Work(_) => self.work.as_deref(),
+            PhaseType::Short(_) | PhaseType::Long(_) => self.breaks.as_deref(),
+        }
+    }
+}
+
+impl Default for PhaseThumbnails {
+    fn default() -> Self {
+        Self {
+            work: Some(DEFAULT_WORK_THUMBNAIL.to_owned()),
+            breaks: Some(DEFAULT_BREAK_THUMBNAIL.to_owned()),
+        }
+    }
+}
+
+/// Get the thumbnail URL in the environment variable `key`, or `default` if it
+/// isn't set. Setting it to nothing means there's no thumbnail at all.
+fn thumbnail(env: &Env, key: &'static str, default: &str) -> Result<Option<String>, Error> {
+    match env.var(key) {
+        Ok(value) if value.is_empty() => Ok(None),
+        Ok(value) if value.starts_with("https://") || value.starts_with("http://") => {
+            Ok(Some(value))
+        }
+        Ok(_) => Err(PomocopError::config(key, "thumbnails must be http(s) URLs")),
+        Err(_) => Ok(Some(default.to_owned())),
+    }
+}
+
+/// Get how many times to retry connecting to Discord at startup, which must be
+/// at most [`MAX_START_RETRIES`].
+fn start_retries(env: &Env) -> Result<u32, Error> {
+    let retries = var_or(env, "START_RETRIES", 5)?;
+
+    if retries <= MAX_START_RETRIES {
+        Ok(retries)
+    } else {
+        Err(PomocopError::config(
+            "START_RETRIES",
+            format!("must be at most {}", MAX_START_RETRIES),
+        ))
+    }
+}
+
+/// Get how long to wait before the first retry, which must be at most
+/// [`MAX_START_RETRY_DELAY`].
+fn start_retry_delay(env: &Env) -> Result<Duration, Error> {
+    let delay = Duration::from_secs(var_or(env, "START_RETRY_DELAY", 1)?);
+
+    if delay <= MAX_START_RETRY_DELAY {
+        Ok(delay)
+    } else {
+        Err(PomocopError::config(
+            "START_RETRY_DELAY",
+            format!(
+                "must be at most {} seconds",
+                MAX_START_RETRY_DELAY.as_secs()
+            ),
+        ))
+    }
+}
+
+/// Get how long to track edits to prefix commands for, which must be at least
+/// a second and at most [`MAX_EDIT_TRACKER_SECONDS`].
+fn edit_tracker_timespan(env: &Env) -> Result<Duration, Error> {
+    let seconds = var_or(env, "EDIT_TRACKER_SECONDS", 60 * 60)?;
+
+    if (1..=MAX_EDIT_TRACKER_SECONDS).contains(&seconds) {
+        Ok(Duration::from_secs(seconds))
+    } else {
+        Err(PomocopError::config(
+            "EDIT_TRACKER_SECONDS",
+            format!("must be between 1 and {} seconds", MAX_EDIT_TRACKER_SECONDS),
+        ))
+    }
+}
+
+/// Read the `.env` file again, and then read the configuration like
+/// [`Config::from_env`], but with the values in the file taking precedence over
+/// the environment.
+///
+/// Otherwise, changes to the file wouldn't make a difference, since the
+/// variables were already set from it at startup. The file is read into an
+/// [`Env`] rather than into the environment, because changing the environment
+/// while other threads might be reading it isn't safe.
+pub fn reload() -> Result<Config, Error> {
+    let mut file = HashMap::new();
+
+    match dotenv::dotenv_iter() {
+        Ok(vars) => {
+            for var in vars {
+                let (key, value) = var.map_err(|error| PomocopError::config(".env", error))?;
+                file.insert(key, value);
+            }
+        }
+        Err(error) if error.not_found() => {}
+        Err(error) => return Err(PomocopError::config(".env", error)),
+    }
+
+    Config::from_vars(&Env { file })
+}
+
+/// Where the configuration is read from: the environment, along with any
+/// variables from the `.env` file that should take precedence over it.
+#[derive(Debug, Default)]
+struct Env {
+    file: HashMap<String, String>,
+}
+
+impl Env {
+    /// Get the value of the variable `key`.
+    fn var(&self, key: &str) -> Result<String, VarError> {
+        match self.file.get(key) {
+            Some(value) => Ok(value.clone()),
+            None => var(key),
+        }
+    }
+}
+
+/// Get the value of the environment variable `key`, which must be set.
+fn required(env: &Env, key: &'static str) -> Result<String, Error> {
+    env.var(key)
+        .map_err(|error| PomocopError::config(key, error))
+}
+
+/// Parse the value of the environment variable `key`, or return `default` if
+/// it isn't set.
+fn var_or<T>(env: &Env, key: &'static str, default: T) -> Result<T, Error>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match env.var(key) {
+        Ok(value) => value
+            .parse()
+            .map_err(|error| PomocopError::config(key, error)),
+        Err(_) => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_takes_precedence() {
+        let env = Env {
+            file: HashMap::from([("POMOCOP_TEST_PREFIX".to_owned(), "!".to_owned())]),
+        };
+
+        assert_eq!(env.var("POMOCOP_TEST_PREFIX"), Ok("!".to_owned()));
+        assert_eq!(var_or(&env, "POMOCOP_TEST_PREFIX", '|').unwrap(), '!');
+        assert_eq!(
+            env.var("POMOCOP_TEST_UNSET"),
+            Err(VarError::NotPresent),
+            "anything not in the file should come from the environment"
+        );
+        assert!(matches!(
+            required(&env, "POMOCOP_TEST_UNSET"),
+            Err(PomocopError::Config {
+                key: "POMOCOP_TEST_UNSET",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,108 @@
+//! Posting a summary of the previous day's focus time to each channel once a
+//! day.
+
+use std::{sync::Arc, time::Duration as StdDuration};
+
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use poise::{serenity_prelude as serenity, Framework};
+use tokio::time::interval;
+use tracing::{info, instrument};
+
+use crate::{pomo::reply::say_daily_stats, Data, Error};
+
+/// How often to check whether a daily reset is due.
+const CHECK_INTERVAL: StdDuration = StdDuration::from_secs(60);
+
+/// Post the previous day's stats to every opted-in channel each day at `time`
+/// in `tz`, for as long as the bot is running.
+///
+/// Rather than sleeping until the next reset, this regularly checks whether
+/// the most recent reset time has passed since the last summary was posted.
+/// That way, a reset that happened while the bot was offline is caught up on
+/// when it comes back.
+#[instrument(skip(framework, http))]
+pub async fn run(
+    framework: Arc<Framework<Data, Error>>,
+    http: serenity::Http,
+    time: NaiveTime,
+    tz: Tz,
+) {
+    let data = framework.user_data().await;
+    let mut checks = interval(CHECK_INTERVAL);
+
+    loop {
+        checks.tick().await;
+        announce_if_due(&http, data, last_reset_time(Utc::now(), time, tz)).await;
+    }
+}
+
+/// If the day ending at `reset` hasn't been summarised yet, summarise it and
+/// post the summaries to each channel that's opted in.
+async fn announce_if_due(http: &serenity::Http, data: &Data, reset: DateTime<Utc>) {
+    let summaries = {
+        let mut stats = data.stats.lock().await;
+
+        if matches!(stats.last_reset(), Some(last_reset) if last_reset >= reset) {
+            return;
+        }
+
+        let summaries = stats.summaries(reset - Duration::days(1), reset);
+        stats.reset(reset);
+
+        summaries
+    };
+
+    info!(%reset, channels = summaries.len(), "daily stats reset");
+
+    for summary in summaries {
+        if data.guild_settings(summary.guild_id).await.daily_stats {
+            say_daily_stats(http, &summary).await;
+        }
+    }
+}
+
+/// Get the most recent time at or before `now` that the clock in `tz` read
+/// `time`.
+fn last_reset_time(now: DateTime<Utc>, time: NaiveTime, tz: Tz) -> DateTime<Utc> {
+    let today = now.with_timezone(&tz).date().naive_local();
+
+    [today, today.pred()]
+        .iter()
+        .filter_map(|date| {
+            // If `time` is skipped over on this date (e.g. by a DST change),
+            // there's no reset on this date.
+            tz.from_local_datetime(&date.and_time(time))
+                .earliest()
+                .map(|reset| reset.with_timezone(&Utc))
+        })
+        .find(|reset| *reset <= now)
+        .unwrap_or_else(|| now - Duration::days(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::{Europe::London, UTC};
+
+    use super::*;
+
+    #[test]
+    fn last_reset_time_is_not_in_the_future() {
+        let midnight = NaiveTime::from_hms(0, 0, 0);
+        let now = Utc.ymd(2022, 6, 1).and_hms(12, 0, 0);
+
+        assert_eq!(
+            last_reset_time(now, midnight, UTC),
+            Utc.ymd(2022, 6, 1).and_hms(0, 0, 0)
+        );
+        assert_eq!(
+            last_reset_time(now, NaiveTime::from_hms(18, 0, 0), UTC),
+            Utc.ymd(2022, 5, 31).and_hms(18, 0, 0)
+        );
+        assert_eq!(
+            last_reset_time(now, midnight, London),
+            Utc.ymd(2022, 5, 31).and_hms(23, 0, 0),
+            "reset time was not in the right time zone"
+        );
+    }
+}
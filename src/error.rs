@@ -0,0 +1,100 @@
+//! The errors that commands and the rest of the bot can fail with.
+
+use poise::serenity_prelude as serenity;
+use thiserror::Error;
+
+use crate::{
+    pomo::session::{ConfigError, SessionError},
+    presets::PresetError,
+    templates::TemplateError,
+};
+
+/// An error from some other library that doesn't have its own variant.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Error)]
+pub enum PomocopError {
+    /// An environment variable needed to configure the bot is missing or
+    /// invalid.
+    #[error("environment variable {key} is missing or invalid")]
+    Config {
+        key: &'static str,
+        #[source]
+        source: BoxError,
+    },
+    /// Something went wrong while setting up the bot, after it was configured.
+    #[error("unable to set up the bot")]
+    Setup(#[source] BoxError),
+    #[error(transparent)]
+    Session(#[from] SessionError),
+    /// A session was about to run in a channel that already has one running.
+    #[error("there is already a session running in this channel")]
+    AlreadyRunning,
+    #[error(transparent)]
+    SessionConfig(#[from] ConfigError),
+    #[error(transparent)]
+    Preset(#[from] PresetError),
+    #[error(transparent)]
+    Template(#[from] TemplateError),
+    /// Something went wrong while talking to Discord.
+    #[error("error talking to Discord")]
+    Discord(#[from] serenity::Error),
+    #[error(transparent)]
+    Other(#[from] BoxError),
+}
+
+impl PomocopError {
+    /// Create a [`PomocopError::Config`] for the environment variable `key`.
+    pub fn config<E>(key: &'static str, source: E) -> Self
+    where
+        E: Into<BoxError>,
+    {
+        Self::Config {
+            key,
+            source: source.into(),
+        }
+    }
+
+    /// Get a message that explains this error to the user whose command caused
+    /// it.
+    pub fn user_message(&self) -> String {
+        match self {
+            PomocopError::Config { .. } | PomocopError::Setup(_) => {
+                "Something is wrong with how I've been set up. Tell whoever runs me.".to_owned()
+            }
+            PomocopError::Session(SessionError::NotActive) => {
+                "There's no phase running right now. Try again in a moment.".to_owned()
+            }
+            PomocopError::AlreadyRunning => {
+                "Someone beat you to it, there's already a session running here.".to_owned()
+            }
+            PomocopError::SessionConfig(error) => {
+                format!("Those settings don't work: {}.", error)
+            }
+            PomocopError::Preset(error) => format!("That preset doesn't work: {}.", error),
+            PomocopError::Template(error) => format!("That template doesn't work: {}.", error),
+            PomocopError::Discord(_) => {
+                "I couldn't get through to Discord. Try again in a bit.".to_owned()
+            }
+            PomocopError::Other(_) => "Something went wrong, and I have no idea what.".to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_message() {
+        assert_eq!(
+            PomocopError::from(ConfigError::ZeroInterval).user_message(),
+            "Those settings don't work: the long break interval must be at least 1."
+        );
+        assert_eq!(
+            PomocopError::config("TOKEN", "not set").user_message(),
+            PomocopError::Setup("oops".into()).user_message(),
+            "configuration and setup errors should look the same to users"
+        );
+    }
+}
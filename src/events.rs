@@ -0,0 +1,123 @@
+//! Starting and stopping sessions to follow the lifecycle of Discord scheduled
+//! events.
+
+use poise::{
+    serenity_prelude::{self as serenity, ScheduledEvent, ScheduledEventStatus},
+    Event,
+};
+use tracing::{info, instrument, warn};
+
+use crate::{
+    commands::pomo::{run_session, start_blocked, StartBlocked},
+    pomo::{reply::say_session_starting, session::SessionConfig, SessionContext},
+    Data, Error,
+};
+
+/// A link between a scheduled event and a channel that a session should run
+/// in while the event is active.
+#[derive(Clone, Debug)]
+pub struct EventLink {
+    /// The guild the event was linked in, which the event and the channel both
+    /// belong to.
+    pub guild_id: serenity::GuildId,
+    pub channel_id: serenity::ChannelId,
+    pub config: SessionConfig,
+    /// The user who linked the event, who will be the host of the session.
+    pub host: serenity::UserId,
+}
+
+pub async fn handle_event(
+    discord: &serenity::Context,
+    event: &Event<'_>,
+    data: &Data,
+) -> Result<(), Error> {
+    match event {
+        Event::GuildScheduledEventUpdate { event } => match event.status {
+            ScheduledEventStatus::Active => start_linked_session(discord, data, event).await,
+            ScheduledEventStatus::Completed | ScheduledEventStatus::Canceled => {
+                stop_linked_session(data, event).await;
+                Ok(())
+            }
+            _ => Ok(()),
+        },
+        Event::GuildScheduledEventDelete { event } => {
+            stop_linked_session(data, event).await;
+
+            let mut links = data.event_links.lock().await;
+            if links
+                .get(&event.id)
+                .map_or(false, |link| link.guild_id == event.guild_id)
+            {
+                links.remove(&event.id);
+            }
+
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[instrument(skip(discord, data, event), fields(event_id = %event.id))]
+async fn start_linked_session(
+    discord: &serenity::Context,
+    data: &Data,
+    event: &ScheduledEvent,
+) -> Result<(), Error> {
+    let link = match linked(data, event).await {
+        Some(link) => link,
+        None => return Ok(()),
+    };
+
+    let ctx = SessionContext {
+        discord,
+        data,
+        channel_id: link.channel_id,
+        guild_id: Some(link.guild_id),
+    };
+
+    // Only admins can link events, and they're exempt from the cooldown, so the
+    // session gets the same treatment.
+    match start_blocked(ctx, link.host).await {
+        Some(StartBlocked::Cooldown(_)) | None => {}
+        Some(blocked) => {
+            warn!(channel_id = %link.channel_id, ?blocked, "unable to start linked session");
+            return Ok(());
+        }
+    }
+
+    let mut session = link.config.build(link.host);
+
+    info!(?session, "starting session for scheduled event");
+
+    let message = say_session_starting(ctx, &session).await;
+    session.set_start_message(message.map(|message| (message.channel_id, message.id)));
+
+    run_session(ctx, session).await
+}
+
+#[instrument(skip(data, event), fields(event_id = %event.id))]
+async fn stop_linked_session(data: &Data, event: &ScheduledEvent) {
+    let channel_id = match linked(data, event).await {
+        Some(link) => link.channel_id,
+        None => return,
+    };
+
+    if let Some(session) = data.sessions.lock().await.get_mut(&channel_id) {
+        info!(id = %session.id(), "stopping session for scheduled event");
+
+        if let Err(error) = session.stop() {
+            warn!(?error, "unable to stop session for scheduled event");
+        }
+    }
+}
+
+/// Get the link for `event`, if it has one and it was linked in the same guild
+/// as the event.
+async fn linked(data: &Data, event: &ScheduledEvent) -> Option<EventLink> {
+    data.event_links
+        .lock()
+        .await
+        .get(&event.id)
+        .filter(|link| link.guild_id == event.guild_id)
+        .cloned()
+}
@@ -0,0 +1,185 @@
+//! Running a command or sending a request whenever a phase starts or ends, so
+//! that self-hosters can hook the bot up to things on their own machine (e.g.
+//! turning on Do Not Disturb during work sessions).
+
+use std::{path::PathBuf, process::Stdio, time::Duration};
+
+use reqwest::{header::CONTENT_TYPE, Client, Url};
+use serde_json::{json, Value};
+use tokio::{process::Command, time::timeout};
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+use crate::pomo::session::PhaseType;
+
+/// How long a hook can take before it's given up on.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Which end of a phase a hook is being run for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookEvent {
+    Start,
+    End,
+}
+
+impl HookEvent {
+    fn name(self) -> &'static str {
+        match self {
+            HookEvent::Start => "start",
+            HookEvent::End => "end",
+        }
+    }
+}
+
+/// The hooks to run at the start and end of each phase.
+#[derive(Clone, Debug, Default)]
+pub struct PhaseHooks {
+    /// A program to run, which is given the event, phase type and session ID as
+    /// its arguments.
+    command: Option<PathBuf>,
+    /// A URL to POST the event, phase type and session ID to, as JSON.
+    url: Option<Url>,
+    client: Client,
+}
+
+// The client is only there to reuse connections, so it doesn't make a
+// difference to whether two sets of hooks are the same.
+impl PartialEq for PhaseHooks {
+    fn eq(&self, other: &Self) -> bool {
+        self.command == other.command && self.url == other.url
+    }
+}
+
+impl PhaseHooks {
+    pub fn new(command: Option<PathBuf>, url: Option<Url>) -> Self {
+        Self {
+            command,
+            url,
+            client: Client::new(),
+        }
+    }
+
+    /// Run the hooks for `event` on a phase of type `phase_type` in the session
+    /// with ID `id`.
+    ///
+    /// The hooks run in the background, and anything that goes wrong with them
+    /// is only logged, so they can never hold up or break a session.
+    pub fn fire(&self, event: HookEvent, phase_type: PhaseType, id: Uuid) {
+        if let Some(command) = self.command.clone() {
+            tokio::spawn(run_command(command, event, phase_type, id));
+        }
+
+        if let Some(url) = self.url.clone() {
+            tokio::spawn(send_request(
+                self.client.clone(),
+                url,
+                event,
+                phase_type,
+                id,
+            ));
+        }
+    }
+}
+
+/// Run `command` directly (not through a shell) with the details of the event
+/// as its arguments.
+///
+/// Only values the bot controls are passed, never anything a user typed, and
+/// since no shell is involved they can't be interpreted as anything but
+/// arguments anyway.
+#[instrument]
+async fn run_command(command: PathBuf, event: HookEvent, phase_type: PhaseType, id: Uuid) {
+    let child = Command::new(&command)
+        .args(args(event, phase_type, id))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(error) => {
+            warn!(%error, "unable to run phase hook command");
+            return;
+        }
+    };
+
+    match timeout(HOOK_TIMEOUT, child.wait()).await {
+        Ok(Ok(status)) if status.success() => debug!("phase hook command succeeded"),
+        Ok(Ok(status)) => warn!(%status, "phase hook command failed"),
+        Ok(Err(error)) => warn!(%error, "unable to wait for phase hook command"),
+        Err(_) => warn!("phase hook command timed out"),
+    }
+}
+
+/// POST the details of the event to `url` as JSON.
+#[instrument(skip(client))]
+async fn send_request(client: Client, url: Url, event: HookEvent, phase_type: PhaseType, id: Uuid) {
+    let response = client
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(payload(event, phase_type, id).to_string())
+        .timeout(HOOK_TIMEOUT)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+
+    match response {
+        Ok(_) => debug!("phase hook request succeeded"),
+        Err(error) => warn!(%error, "phase hook request failed"),
+    }
+}
+
+/// The arguments to give the hook command.
+fn args(event: HookEvent, phase_type: PhaseType, id: Uuid) -> [String; 3] {
+    [
+        event.name().to_owned(),
+        phase_name(phase_type).to_owned(),
+        id.to_string(),
+    ]
+}
+
+/// The body of the request sent to the hook URL.
+fn payload(event: HookEvent, phase_type: PhaseType, id: Uuid) -> Value {
+    json!({
+        "event": event.name(),
+        "phase": phase_name(phase_type),
+        "session": id.to_string(),
+    })
+}
+
+fn phase_name(phase_type: PhaseType) -> &'static str {
+    match phase_type {
+        PhaseType::Work(_) => "work",
+        PhaseType::Short(_) => "short_break",
+        PhaseType::Long(_) => "long_break",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn args_and_payload() {
+        let id = Uuid::nil();
+
+        assert_eq!(
+            args(HookEvent::Start, PhaseType::Short(5), id),
+            [
+                "start".to_owned(),
+                "short_break".to_owned(),
+                "00000000-0000-0000-0000-000000000000".to_owned(),
+            ]
+        );
+        assert_eq!(
+            payload(HookEvent::End, PhaseType::Work(25), id),
+            json!({
+                "event": "end",
+                "phase": "work",
+                "session": "00000000-0000-0000-0000-000000000000",
+            })
+        );
+    }
+}
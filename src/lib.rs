@@ -1,90 +1,917 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
+use chrono::{DateTime, Utc};
 use poise::{
-    serenity_prelude::{self as serenity, GatewayIntents, UserId},
-    EditTracker, FrameworkBuilder, FrameworkError, FrameworkOptions, PrefixFrameworkOptions,
+    serenity_prelude::{
+        self as serenity, ApplicationId, GatewayError, GatewayIntents, GuildId, UserId,
+    },
+    EditTracker, Framework, FrameworkBuilder, FrameworkError, FrameworkOptions,
+    PrefixFrameworkOptions,
 };
-use rand::{rngs::StdRng, thread_rng, SeedableRng};
+use rand::{distributions::WeightedIndex, rngs::StdRng, thread_rng, Rng, SeedableRng};
+use serde::Serialize;
 use serenity::ChannelId;
-use tokio::sync::Mutex;
-use tracing::{error, info, instrument};
+use tokio::{sync::Mutex, time::sleep};
+use tracing::{error, info, instrument, warn};
 
-use crate::pomo::session::Session;
+pub use crate::error::PomocopError;
+use crate::{
+    aliases::TextAliases,
+    config::{Config, PhaseThumbnails},
+    pomo::{
+        reply::{phrases, reply_command_error, reply_guild_only},
+        session::{Session, SessionDump},
+        store::SessionStore,
+    },
+    presets::Presets,
+    settings::{GuildSettings, UserSettings},
+    stats::Stats,
+    templates::Templates,
+};
 
+pub mod aliases;
 pub mod commands;
+pub mod config;
+#[cfg(feature = "daily-stats")]
+pub mod daily;
+pub mod error;
+#[cfg(feature = "scheduled-events")]
+pub mod events;
+#[cfg(feature = "phase-hooks")]
+pub mod hooks;
 pub mod pomo;
+pub mod presets;
+pub mod settings;
+pub mod stats;
+pub mod templates;
 
 // Types used by all command functions
-pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub type Error = PomocopError;
 pub type Context<'a> = poise::Context<'a, Data, Error>;
 pub type PrefixContext<'a> = poise::PrefixContext<'a, Data, Error>;
 
 // Custom user data passed to all command functions
 pub struct Data {
-    pub sessions: Mutex<HashMap<ChannelId, Session>>,
+    pub sessions: Mutex<Box<dyn SessionStore>>,
+    /// When the most recent session in each channel ended.
+    pub last_sessions: Mutex<HashMap<ChannelId, DateTime<Utc>>>,
+    /// Sessions to start in each channel when the session running there
+    /// finishes.
+    pub queued_sessions: Mutex<HashMap<ChannelId, Session>>,
+    pub guild_settings: Mutex<HashMap<GuildId, GuildSettings>>,
+    pub user_settings: Mutex<HashMap<UserId, UserSettings>>,
+    pub stats: Mutex<Stats>,
+    /// Session configs saved by users and guilds to start sessions from.
+    pub presets: Mutex<Presets>,
+    /// Session configs curated by each guild's admins.
+    pub templates: Mutex<Templates>,
+    /// Scheduled events that should have sessions run while they're active.
+    #[cfg(feature = "scheduled-events")]
+    pub event_links: Mutex<HashMap<serenity::ScheduledEventId, events::EventLink>>,
     pub rng: Mutex<StdRng>,
+    /// The index of the phrase most recently picked from each list of
+    /// phrases, by the list's address, so that the same one isn't picked twice
+    /// in a row.
+    pub last_phrases: Mutex<HashMap<usize, usize>>,
+    /// The ID of the bot's application, as registered with Discord.
+    pub application_id: ApplicationId,
     pub owner_id: serenity::UserId,
+    /// The longest a session can run for, unless its host says otherwise.
+    ///
+    /// This and the other settings behind locks below can be changed while
+    /// the bot is running, with `/reload`.
+    pub max_session_length: Mutex<chrono::Duration>,
+    /// The most sessions that can run at once, if there's a limit.
+    pub max_sessions: Mutex<Option<usize>>,
+    /// How long after someone's pinged about a phase change that they won't be
+    /// pinged again.
+    pub ping_cooldown: Mutex<chrono::Duration>,
+    /// When each user was last pinged about a phase change.
+    pub last_pinged: Mutex<HashMap<UserId, DateTime<Utc>>>,
+    /// Where to send messages from `/feedback`, if anywhere.
+    pub feedback_channel: Mutex<Option<ChannelId>>,
+    /// The images to show when each type of phase starts.
+    pub phase_thumbnails: Mutex<PhaseThumbnails>,
+    /// Phrases that can be sent to the bot instead of some commands.
+    pub text_aliases: TextAliases,
+    /// When each user last sent feedback.
+    pub feedback_sent: Mutex<HashMap<UserId, DateTime<Utc>>>,
+    /// What to run at the start and end of each phase.
+    #[cfg(feature = "phase-hooks")]
+    pub phase_hooks: Mutex<hooks::PhaseHooks>,
 }
 
-#[instrument(skip(token))]
-pub async fn run(
-    _application_id: String,
-    owner_id: String,
-    prefix: String,
-    token: String,
-) -> Result<(), Error> {
+/// The state of [`Data`] as it's included in `/debug dump`.
+#[derive(Debug, Serialize)]
+pub struct DataDump {
+    /// When the snapshot was taken.
+    pub taken: DateTime<Utc>,
+    pub sessions: BTreeMap<ChannelId, SessionDump>,
+    pub queued_sessions: BTreeMap<ChannelId, SessionDump>,
+    pub last_sessions: BTreeMap<ChannelId, DateTime<Utc>>,
+    pub max_sessions: Option<usize>,
+    /// The longest a session can run for by default, in seconds.
+    pub max_session_length: i64,
+    /// The ping cooldown, in seconds.
+    pub ping_cooldown: i64,
+    pub guilds_with_settings: usize,
+    pub users_with_settings: usize,
+}
+
+impl Data {
+    pub fn new(
+        application_id: ApplicationId,
+        owner_id: UserId,
+        max_session_length: chrono::Duration,
+    ) -> Self {
+        Self {
+            sessions: Mutex::new(Box::new(HashMap::new())),
+            last_sessions: Mutex::new(HashMap::new()),
+            queued_sessions: Mutex::new(HashMap::new()),
+            guild_settings: Mutex::new(HashMap::new()),
+            user_settings: Mutex::new(HashMap::new()),
+            stats: Mutex::new(Stats::default()),
+            presets: Mutex::new(Presets::default()),
+            templates: Mutex::new(Templates::default()),
+            #[cfg(feature = "scheduled-events")]
+            event_links: Mutex::new(HashMap::new()),
+            rng: Mutex::new(
+                StdRng::from_rng(thread_rng()).expect("unable to seed StdRng from ThreadRng"),
+            ),
+            last_phrases: Mutex::new(HashMap::new()),
+            application_id,
+            owner_id,
+            max_session_length: Mutex::new(max_session_length),
+            max_sessions: Mutex::new(None),
+            ping_cooldown: Mutex::new(chrono::Duration::zero()),
+            last_pinged: Mutex::new(HashMap::new()),
+            feedback_channel: Mutex::new(None),
+            phase_thumbnails: Mutex::new(PhaseThumbnails::default()),
+            text_aliases: TextAliases::default(),
+            feedback_sent: Mutex::new(HashMap::new()),
+            #[cfg(feature = "phase-hooks")]
+            phase_hooks: Mutex::new(hooks::PhaseHooks::default()),
+        }
+    }
+
+    /// Get the settings for `guild`, or the default settings if `guild` is
+    /// `None` (e.g. in a DM) or hasn't changed any.
+    pub async fn guild_settings(&self, guild: Option<GuildId>) -> GuildSettings {
+        match guild {
+            Some(guild) => self
+                .guild_settings
+                .lock()
+                .await
+                .get(&guild)
+                .cloned()
+                .unwrap_or_default(),
+            None => GuildSettings::default(),
+        }
+    }
+
+    /// Returns whether as many sessions are already running as the bot allows,
+    /// so that `user` can't start another one. The owner can always start one.
+    pub async fn at_capacity(&self, user: UserId) -> bool {
+        if user == self.owner_id {
+            return false;
+        }
+
+        match *self.max_sessions.lock().await {
+            Some(max) => self.sessions.lock().await.len() >= max,
+            None => false,
+        }
+    }
+
+    /// Filter `members` down to the ones who can be pinged about a phase change
+    /// at `now`, and remember that they were.
+    ///
+    /// Anyone who was pinged less than `ping_cooldown` ago is left out, so that
+    /// skipping through several phases in a row doesn't ping everyone for each
+    /// one. They aren't pinged later instead, so they won't hear about the
+    /// phases they were left out of.
+    pub async fn pings_due(&self, members: Vec<UserId>, now: DateTime<Utc>) -> Vec<UserId> {
+        let cooldown = *self.ping_cooldown.lock().await;
+        let mut last_pinged = self.last_pinged.lock().await;
+
+        last_pinged.retain(|_, last| now - *last < cooldown);
+
+        members
+            .into_iter()
+            .filter(|member| {
+                if last_pinged.contains_key(member) {
+                    false
+                } else {
+                    last_pinged.insert(*member, now);
+                    true
+                }
+            })
+            .collect()
+    }
+
+    /// Get a snapshot of the sessions and limits the bot is working with, for
+    /// `/debug dump`.
+    ///
+    /// Each lock is only held for long enough to copy out what's needed, so
+    /// the snapshot may not be perfectly consistent if sessions are changing
+    /// while it's taken.
+    pub async fn dump(&self) -> DataDump {
+        DataDump {
+            taken: Utc::now(),
+            sessions: self
+                .sessions
+                .lock()
+                .await
+                .iter()
+                .map(|(channel, session)| (*channel, session.dump()))
+                .collect(),
+            queued_sessions: self
+                .queued_sessions
+                .lock()
+                .await
+                .iter()
+                .map(|(channel, session)| (*channel, session.dump()))
+                .collect(),
+            last_sessions: self
+                .last_sessions
+                .lock()
+                .await
+                .iter()
+                .map(|(channel, ended)| (*channel, *ended))
+                .collect(),
+            max_sessions: *self.max_sessions.lock().await,
+            max_session_length: self.max_session_length.lock().await.num_seconds(),
+            ping_cooldown: self.ping_cooldown.lock().await.num_seconds(),
+            guilds_with_settings: self.guild_settings.lock().await.len(),
+            users_with_settings: self.user_settings.lock().await.len(),
+        }
+    }
+
+    /// Update the settings that can be changed while the bot is running to the
+    /// ones in `config`, returning the environment variables whose settings
+    /// changed.
+    pub async fn reload(&self, config: &Config) -> Result<Vec<&'static str>, Error> {
+        let mut changed = Vec::new();
+
+        let max_session_length = chrono::Duration::from_std(config.max_session_length)
+            .map_err(|error| PomocopError::config("MAX_SESSION_HOURS", error))?;
+        if update(
+            &mut *self.max_session_length.lock().await,
+            max_session_length,
+        ) {
+            changed.push("MAX_SESSION_HOURS");
+        }
+
+        if update(&mut *self.max_sessions.lock().await, config.max_sessions) {
+            changed.push("MAX_SESSIONS");
+        }
+
+        let ping_cooldown = chrono::Duration::from_std(config.ping_cooldown)
+            .map_err(|error| PomocopError::config("PING_COOLDOWN_SECONDS", error))?;
+        if update(&mut *self.ping_cooldown.lock().await, ping_cooldown) {
+            changed.push("PING_COOLDOWN_SECONDS");
+        }
+
+        let feedback_channel = config.feedback_channel_id.map(ChannelId);
+        if update(&mut *self.feedback_channel.lock().await, feedback_channel) {
+            changed.push("FEEDBACK_CHANNEL_ID");
+        }
+
+        let mut phase_thumbnails = self.phase_thumbnails.lock().await;
+        if update(
+            &mut phase_thumbnails.work,
+            config.phase_thumbnails.work.clone(),
+        ) {
+            changed.push("WORK_THUMBNAIL_URL");
+        }
+        if update(
+            &mut phase_thumbnails.breaks,
+            config.phase_thumbnails.breaks.clone(),
+        ) {
+            changed.push("BREAK_THUMBNAIL_URL");
+        }
+        drop(phase_thumbnails);
+
+        #[cfg(feature = "phase-hooks")]
+        {
+            if update(
+                &mut *self.phase_hooks.lock().await,
+                config.phase_hooks.clone(),
+            ) {
+                changed.push("PHASE_HOOK_COMMAND/PHASE_HOOK_URL");
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Pick one of `phrases` at random, or [`phrases::FALLBACK`] if there
+    /// aren't any to pick from.
+    ///
+    /// The phrase picked from `phrases` last time isn't picked again, unless
+    /// it's the only one. Lists are told apart by their address, so they should
+    /// be `static`s like the ones in [`phrases`].
+    ///
+    /// The RNG is only locked for long enough to pick an index, so that
+    /// replies being sent at the same time don't hold each other up.
+    pub async fn pick<'a>(&self, phrases: &[&'a str]) -> &'a str {
+        if phrases.is_empty() {
+            warn!("no phrases to pick from, using the fallback");
+            return phrases::FALLBACK;
+        }
+
+        let list = phrases.as_ptr() as usize;
+        let last = self.last_phrase(list, phrases.len()).await;
+
+        let index = match last {
+            // Pick from every phrase but the last one, by skipping over it.
+            Some(last) if phrases.len() > 1 => {
+                let index = self.rng.lock().await.gen_range(0..phrases.len() - 1);
+                if index >= last {
+                    index + 1
+                } else {
+                    index
+                }
+            }
+            _ => self.rng.lock().await.gen_range(0..phrases.len()),
+        };
+
+        self.last_phrases.lock().await.insert(list, index);
+        phrases[index]
+    }
+
+    /// Pick one of `phrases` at random, where each phrase is as likely to be
+    /// picked as its weight says (so that some can be rarer than others), or
+    /// [`phrases::FALLBACK`] if there aren't any that can be picked.
+    ///
+    /// A phrase with a weight of 0 is never picked. Like [`Data::pick`], the
+    /// phrase picked last time isn't picked again, unless nothing else can be.
+    pub async fn pick_weighted<'a>(&self, phrases: &[(&'a str, u32)]) -> &'a str {
+        let list = phrases.as_ptr() as usize;
+        let last = self.last_phrase(list, phrases.len()).await;
+
+        let weights_without = |skip: Option<usize>| {
+            WeightedIndex::new(phrases.iter().enumerate().map(|(index, (_, weight))| {
+                if Some(index) == skip {
+                    0
+                } else {
+                    *weight
+                }
+            }))
+        };
+
+        let weights = match weights_without(last).or_else(|_| weights_without(None)) {
+            Ok(weights) => weights,
+            Err(error) => {
+                warn!(%error, "no phrases to pick from, using the fallback");
+                return phrases::FALLBACK;
+            }
+        };
+
+        let index = self.rng.lock().await.sample(&weights);
+
+        self.last_phrases.lock().await.insert(list, index);
+        phrases[index].0
+    }
+
+    /// Get the index of the phrase most recently picked from the list of `len`
+    /// phrases at the address `list`, if one has been.
+    async fn last_phrase(&self, list: usize, len: usize) -> Option<usize> {
+        self.last_phrases
+            .lock()
+            .await
+            .get(&list)
+            .copied()
+            .filter(|&last| last < len)
+    }
+
+    /// Get the settings for `user`, or the default settings if they haven't
+    /// changed any.
+    pub async fn user_settings(&self, user: UserId) -> UserSettings {
+        self.user_settings
+            .lock()
+            .await
+            .get(&user)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[instrument(skip(config))]
+pub async fn run(config: Config) -> Result<(), Error> {
     info!("starting pomocop");
 
-    let options = FrameworkOptions {
+    let mut attempt = 0;
+
+    loop {
+        let framework = build_framework(&config).await?;
+
+        #[cfg(feature = "daily-stats")]
+        let daily_stats = tokio::spawn(daily::run(
+            framework.clone(),
+            serenity::Http::new(&config.token),
+            config.daily_stats_time,
+            config.daily_stats_timezone,
+        ));
+
+        let result = framework.start().await;
+
+        // The framework is rebuilt on each attempt, so this needs to be too.
+        #[cfg(feature = "daily-stats")]
+        daily_stats.abort();
+
+        match result {
+            Ok(()) => return Ok(()),
+            Err(error @ serenity::Error::Gateway(GatewayError::InvalidAuthentication)) => {
+                // No amount of retrying is going to make a bad token work.
+                error!(
+                    ?error,
+                    "unable to authenticate with Discord; is TOKEN correct?"
+                );
+                return Err(error.into());
+            }
+            Err(error) if attempt < config.start_retries => {
+                let delay = config.retry_delay(attempt);
+                attempt += 1;
+
+                warn!(
+                    ?error,
+                    attempt,
+                    ?delay,
+                    "unable to start framework, retrying"
+                );
+                sleep(delay).await;
+            }
+            Err(error) => {
+                error!(?error, "unable to start framework, giving up");
+                return Err(error.into());
+            }
+        }
+    }
+}
+
+async fn build_framework(config: &Config) -> Result<Arc<Framework<Data, Error>>, Error> {
+    #[allow(unused_mut)]
+    let mut options = FrameworkOptions {
         prefix_options: PrefixFrameworkOptions {
-            prefix: Some(prefix),
-            edit_tracker: Some(EditTracker::for_timespan(Duration::from_secs(3600))),
+            // Without the content of messages we can't see the prefix, so only
+            // allow prefix commands by mentioning the bot (mentions always
+            // include the content).
+            prefix: config.message_content.then(|| config.prefix.clone()),
+            edit_tracker: Some(EditTracker::for_timespan(config.edit_tracker_timespan)),
             ..Default::default()
         },
         on_error: |error| Box::pin(on_error(error)),
+        listener: |discord, event, _framework, data| Box::pin(handle_event(discord, event, data)),
         commands: vec![
             commands::meta::help(),
+            commands::meta::about(),
+            commands::meta::invite(),
+            commands::meta::feedback(),
             commands::meta::register(),
+            commands::meta::reload(),
+            commands::meta::debug(),
+            commands::admin::setcooldown(),
+            commands::admin::settidy(),
+            commands::admin::settips(),
+            commands::admin::setstatsmode(),
+            commands::admin::setthreads(),
+            commands::admin::setonesession(),
+            commands::admin::setfirstskip(),
+            commands::admin::setmentions(),
+            commands::admin::setaudit(),
+            commands::admin::settranscript(),
+            commands::admin::setguildtz(),
+            commands::admin::template(),
+            commands::admin::forcestop(),
             commands::pomo::start(),
+            commands::pomo::import(),
+            commands::pomo::queue(),
+            commands::pomo::unqueue(),
+            commands::pomo::export(),
             commands::pomo::status(),
+            commands::pomo::long_break(),
+            commands::pomo::breaktime(),
+            commands::pomo::health(),
+            commands::pomo::phases(),
+            commands::pomo::timeline(),
+            commands::pomo::config(),
+            commands::pomo::defaults(),
+            commands::pomo::savepreset(),
+            commands::pomo::presets(),
+            commands::pomo::templates(),
+            commands::pomo::deletepreset(),
+            commands::pomo::lastsession(),
             commands::pomo::join(),
+            commands::pomo::addmember(),
+            commands::pomo::joinall(),
+            commands::pomo::optout(),
+            commands::pomo::accountability(),
+            commands::pomo::stats(),
             commands::pomo::leave(),
+            commands::pomo::move_session(),
+            commands::pomo::silence(),
             commands::pomo::skip(),
+            commands::pomo::skipbreak(),
+            commands::pomo::undo(),
+            commands::pomo::breaknow(),
+            commands::pomo::set_time(),
+            commands::pomo::reset(),
+            commands::pomo::adjust_interval(),
+            commands::pomo::finish(),
+            commands::pomo::unfinish(),
+            commands::pomo::ready_to_stop(),
             commands::pomo::stop(),
         ],
         ..Default::default()
     };
 
+    #[cfg(feature = "scheduled-events")]
+    {
+        options.commands.extend(vec![
+            commands::admin::linkevent(),
+            commands::admin::unlinkevent(),
+        ]);
+    }
+
+    #[cfg(feature = "daily-stats")]
+    options.commands.push(commands::admin::dailystats());
+
+    let intents = if config.message_content {
+        GatewayIntents::non_privileged() | GatewayIntents::MESSAGE_CONTENT
+    } else {
+        warn!(
+            "not requesting the MESSAGE_CONTENT intent, so prefix commands (e.g. `register`) will \
+             only work by mentioning the bot instead of using the prefix"
+        );
+        GatewayIntents::non_privileged()
+    };
+
+    #[cfg(feature = "scheduled-events")]
+    let intents = intents | GatewayIntents::GUILD_SCHEDULED_EVENTS;
+
+    let application_id = config.application_id.clone();
+    let owner_id = config.owner_id.clone();
+    let max_session_length = chrono::Duration::from_std(config.max_session_length)
+        .map_err(|error| PomocopError::config("MAX_SESSION_HOURS", error))?;
+    let max_sessions = config.max_sessions;
+    let ping_cooldown = chrono::Duration::from_std(config.ping_cooldown)
+        .map_err(|error| PomocopError::config("PING_COOLDOWN_SECONDS", error))?;
+    let feedback_channel = config.feedback_channel_id.map(ChannelId);
+    let phase_thumbnails = config.phase_thumbnails.clone();
+    let text_aliases = config
+        .text_aliases
+        .clone()
+        .with_prefix(config.message_content.then(|| config.prefix.clone()));
+    // A phrase that starts with a prefix command's name would run the command as
+    // well.
+    text_aliases
+        .check_conflicts(
+            options
+                .commands
+                .iter()
+                .filter(|command| command.prefix_action.is_some())
+                .map(|command| command.name),
+        )
+        .map_err(|error| PomocopError::config("TEXT_ALIASES", error))?;
+    #[cfg(feature = "phase-hooks")]
+    let phase_hooks = config.phase_hooks.clone();
+
     let framework = FrameworkBuilder::<Data, Error>::default()
         .options(options)
-        .token(token)
-        .intents(GatewayIntents::non_privileged() | GatewayIntents::MESSAGE_CONTENT)
-        .user_data_setup(move |_ctx, _ready, _framework| {
+        .token(&config.token)
+        .intents(intents)
+        .user_data_setup(move |_ctx, ready, _framework| {
             Box::pin(async move {
-                Ok(Data {
-                    sessions: Mutex::new(HashMap::new()),
-                    rng: Mutex::new(
-                        StdRng::from_rng(thread_rng())
-                            .expect("unable to seed StdRng from ThreadRng"),
-                    ),
-                    owner_id: UserId(owner_id.parse()?),
-                })
+                let application_id = ApplicationId(
+                    application_id
+                        .parse()
+                        .map_err(|error| PomocopError::config("APPLICATION_ID", error))?,
+                );
+
+                // Commands are registered for whichever application the token belongs to, so
+                // anything built from `APPLICATION_ID` (like `/invite`) would quietly point
+                // somewhere else if they didn't match.
+                if application_id != ready.application.id {
+                    return Err(PomocopError::config(
+                        "APPLICATION_ID",
+                        format!(
+                            "the token belongs to application {}, not {}",
+                            ready.application.id, application_id
+                        ),
+                    ));
+                }
+
+                let owner_id = owner_id
+                    .parse()
+                    .map_err(|error| PomocopError::config("OWNER_ID", error))?;
+
+                let mut data = Data::new(application_id, UserId(owner_id), max_session_length);
+                *data.max_sessions.get_mut() = max_sessions;
+                *data.ping_cooldown.get_mut() = ping_cooldown;
+                *data.feedback_channel.get_mut() = feedback_channel;
+                *data.phase_thumbnails.get_mut() = phase_thumbnails;
+                data.text_aliases = text_aliases;
+
+                #[cfg(feature = "phase-hooks")]
+                {
+                    *data.phase_hooks.get_mut() = phase_hooks;
+                }
+
+                Ok(data)
             })
         })
         .build()
         .await?;
 
-    framework.start().await?;
+    Ok(framework)
+}
+
+/// Handle everything from Discord that isn't a command being used.
+async fn handle_event(
+    discord: &serenity::Context,
+    event: &poise::Event<'_>,
+    data: &Data,
+) -> Result<(), Error> {
+    if let poise::Event::Message { new_message } = event {
+        aliases::handle_message(discord, new_message, data).await?;
+    }
+
+    #[cfg(feature = "scheduled-events")]
+    events::handle_event(discord, event, data).await?;
 
     Ok(())
 }
 
+/// Set `current` to `new`, returning whether that changed it.
+fn update<T: PartialEq>(current: &mut T, new: T) -> bool {
+    if *current == new {
+        false
+    } else {
+        *current = new;
+        true
+    }
+}
+
 pub async fn on_error(error: FrameworkError<'_, Data, Error>) {
     match error {
         FrameworkError::Setup { error } => panic!("failed to start bot: {:?}", error),
         FrameworkError::Command { error, ctx } => {
-            error!(?error, command = %ctx.command().name, "error in command")
+            match &error {
+                // These are most likely caused by a session changing at the same time as the
+                // command, rather than anything actually being wrong.
+                PomocopError::Session(_)
+                | PomocopError::SessionConfig(_)
+                | PomocopError::Preset(_)
+                | PomocopError::Template(_) => {
+                    warn!(?error, command = %ctx.command().name, "error in command")
+                }
+                _ => error!(?error, command = %ctx.command().name, "error in command"),
+            }
+
+            reply_command_error(ctx, error.user_message()).await;
         }
+        FrameworkError::GuildOnly { ctx } => reply_guild_only(ctx).await,
         _ => error!("other error"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::pomo::session::SessionConfig;
+
+    #[tokio::test]
+    async fn pick_from_empty_phrases() {
+        let data = Data::new(ApplicationId(1), UserId(1), chrono::Duration::hours(8));
+
+        assert_eq!(data.pick(&[]).await, phrases::FALLBACK);
+        assert_eq!(data.pick(&["only"]).await, "only");
+    }
+
+    #[tokio::test]
+    async fn pick_avoids_repeats() {
+        let data = Data::new(ApplicationId(1), UserId(1), chrono::Duration::hours(8));
+
+        static PHRASES: &[&str] = &["a", "b", "c"];
+        static WEIGHTED: &[(&str, u32)] = &[("a", 1), ("b", 5)];
+
+        let mut last = data.pick(PHRASES).await;
+        for _ in 0..100 {
+            let picked = data.pick(PHRASES).await;
+            assert_ne!(
+                picked, last,
+                "the same phrase shouldn't be picked twice in a row"
+            );
+            last = picked;
+        }
+
+        let mut last = data.pick_weighted(WEIGHTED).await;
+        for _ in 0..100 {
+            let picked = data.pick_weighted(WEIGHTED).await;
+            assert_ne!(picked, last, "weights shouldn't make a phrase repeat");
+            last = picked;
+        }
+
+        static ONLY: &[&str] = &["only"];
+
+        assert_eq!(data.pick(ONLY).await, "only");
+        assert_eq!(
+            data.pick(ONLY).await,
+            "only",
+            "a phrase with nothing to alternate with should still be picked"
+        );
+    }
+
+    #[tokio::test]
+    async fn pick_weighted() {
+        let data = Data::new(ApplicationId(1), UserId(1), chrono::Duration::hours(8));
+
+        for _ in 0..100 {
+            assert_eq!(
+                data.pick_weighted(&[("never", 0), ("always", 1)]).await,
+                "always"
+            );
+        }
+
+        assert_eq!(data.pick_weighted(&[]).await, phrases::FALLBACK);
+        assert_eq!(
+            data.pick_weighted(&[("never", 0)]).await,
+            phrases::FALLBACK,
+            "there's nothing to pick if every weight is 0"
+        );
+    }
+
+    /// A config with the default value of every setting.
+    fn config() -> Config {
+        Config {
+            application_id: "1".to_owned(),
+            owner_id: "1".to_owned(),
+            prefix: "|".to_owned(),
+            message_content: false,
+            token: "token".to_owned(),
+            start_retries: 5,
+            start_retry_delay: Duration::from_secs(1),
+            edit_tracker_timespan: Duration::from_secs(60 * 60),
+            max_session_length: Duration::from_secs(8 * 60 * 60),
+            max_sessions: None,
+            ping_cooldown: Duration::from_secs(0),
+            feedback_channel_id: None,
+            phase_thumbnails: PhaseThumbnails::default(),
+            text_aliases: TextAliases::default(),
+            #[cfg(feature = "daily-stats")]
+            daily_stats_time: chrono::NaiveTime::from_hms(0, 0, 0),
+            #[cfg(feature = "daily-stats")]
+            daily_stats_timezone: chrono_tz::UTC,
+            #[cfg(feature = "phase-hooks")]
+            phase_hooks: hooks::PhaseHooks::default(),
+        }
+    }
+
+    #[test]
+    fn retry_delay() {
+        let config = config();
+
+        assert_eq!(config.retry_delay(0), Duration::from_secs(1));
+        assert_eq!(config.retry_delay(3), Duration::from_secs(8));
+        assert_eq!(
+            config.retry_delay(20),
+            Duration::from_secs(5 * 60),
+            "the delay should stop growing at 5 minutes"
+        );
+        assert_eq!(
+            config.retry_delay(u32::MAX),
+            Duration::from_secs(5 * 60),
+            "huge numbers of retries shouldn't overflow"
+        );
+    }
+
+    #[tokio::test]
+    async fn reload_reports_changes() {
+        let data = Data::new(ApplicationId(1), UserId(1), chrono::Duration::hours(8));
+        let mut config = config();
+
+        assert_eq!(data.reload(&config).await.unwrap(), Vec::<&str>::new());
+
+        config.max_session_length = Duration::from_secs(2 * 60 * 60);
+        config.max_sessions = Some(100);
+        config.phase_thumbnails.breaks = None;
+
+        assert_eq!(
+            data.reload(&config).await.unwrap(),
+            vec!["MAX_SESSION_HOURS", "MAX_SESSIONS", "BREAK_THUMBNAIL_URL"]
+        );
+        assert_eq!(
+            *data.max_session_length.lock().await,
+            chrono::Duration::hours(2)
+        );
+        assert_eq!(data.phase_thumbnails.lock().await.breaks, None);
+    }
+
+    #[tokio::test]
+    async fn at_capacity() {
+        let data = Data::new(ApplicationId(1), UserId(1), chrono::Duration::hours(8));
+        let user = UserId(2);
+
+        data.sessions
+            .lock()
+            .await
+            .insert(ChannelId(1), SessionConfig::default().build(user));
+
+        assert!(!data.at_capacity(user).await, "there's no limit by default");
+
+        *data.max_sessions.lock().await = Some(2);
+        assert!(!data.at_capacity(user).await);
+
+        *data.max_sessions.lock().await = Some(1);
+        assert!(data.at_capacity(user).await);
+        assert!(
+            !data.at_capacity(data.owner_id).await,
+            "the owner should be exempt from the limit"
+        );
+    }
+
+    #[tokio::test]
+    async fn pings_due() {
+        let data = Data::new(ApplicationId(1), UserId(1), chrono::Duration::hours(8));
+        let now = Utc::now();
+
+        assert_eq!(
+            data.pings_due(vec![UserId(1), UserId(2)], now).await,
+            vec![UserId(1), UserId(2)]
+        );
+        assert_eq!(
+            data.pings_due(vec![UserId(1), UserId(2)], now).await,
+            vec![UserId(1), UserId(2)],
+            "there shouldn't be a cooldown by default"
+        );
+
+        let data = Data::new(ApplicationId(1), UserId(1), chrono::Duration::hours(8));
+        *data.ping_cooldown.lock().await = chrono::Duration::seconds(30);
+
+        assert_eq!(
+            data.pings_due(vec![UserId(1), UserId(2)], now).await,
+            vec![UserId(1), UserId(2)]
+        );
+        assert_eq!(
+            data.pings_due(
+                vec![UserId(1), UserId(3)],
+                now + chrono::Duration::seconds(10)
+            )
+            .await,
+            vec![UserId(3)],
+            "someone pinged 10 seconds ago shouldn't be pinged again"
+        );
+        assert_eq!(
+            data.pings_due(
+                vec![UserId(1), UserId(3)],
+                now + chrono::Duration::seconds(30)
+            )
+            .await,
+            vec![UserId(1)],
+            "the cooldown should start from the last ping that went through"
+        );
+
+        *data.ping_cooldown.lock().await = chrono::Duration::zero();
+        assert_eq!(
+            data.pings_due(
+                vec![UserId(1), UserId(3)],
+                now + chrono::Duration::seconds(31)
+            )
+            .await,
+            vec![UserId(1), UserId(3)],
+            "a cooldown of 0 should never leave anyone out"
+        );
+    }
+
+    #[tokio::test]
+    async fn dump() {
+        let data = Data::new(ApplicationId(1), UserId(1), chrono::Duration::hours(8));
+        let mut session = SessionConfig::default().build(UserId(2));
+        session.add_member(UserId(3));
+
+        data.sessions.lock().await.insert(ChannelId(1), session);
+        data.queued_sessions
+            .lock()
+            .await
+            .insert(ChannelId(1), SessionConfig::default().build(UserId(4)));
+
+        let dump = serde_json::to_value(data.dump().await).unwrap();
+
+        assert_eq!(dump["sessions"].as_object().unwrap().len(), 1);
+        assert_eq!(dump["queued_sessions"].as_object().unwrap().len(), 1);
+        assert_eq!(
+            dump["sessions"]["1"]["members"], 2,
+            "members should be counted, not listed"
+        );
+        assert_eq!(
+            dump["sessions"]["1"]["current_phase"],
+            serde_json::Value::Null
+        );
+        assert_eq!(dump["max_session_length"], 8 * 60 * 60);
+    }
+}
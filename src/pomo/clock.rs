@@ -0,0 +1,53 @@
+use std::fmt::Debug;
+
+use chrono::{DateTime, Utc};
+
+/// A source of the current time.
+///
+/// Sessions get the time from one of these rather than straight from the
+/// system, so that tests can drive them with a clock they control.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock, which is what every session uses outside of tests.
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+#[cfg(test)]
+pub use fake::FakeClock;
+
+#[cfg(test)]
+mod fake {
+    use std::sync::Mutex;
+
+    use chrono::{DateTime, Duration, Utc};
+
+    use super::Clock;
+
+    /// A clock that only moves when it's told to.
+    #[derive(Debug)]
+    pub struct FakeClock(Mutex<DateTime<Utc>>);
+
+    impl FakeClock {
+        pub fn new() -> Self {
+            Self(Mutex::new(Utc::now()))
+        }
+
+        pub fn advance(&self, duration: Duration) {
+            *self.0.lock().unwrap() += duration;
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.0.lock().unwrap()
+        }
+    }
+}
@@ -1,2 +1,32 @@
+use poise::serenity_prelude::{self as serenity, ChannelId, GuildId};
+
+use crate::{Context, Data};
+
+pub mod clock;
+pub mod notifier;
 pub mod reply;
 pub mod session;
+pub mod store;
+pub mod vote;
+
+/// Everything needed to run a session and post messages about it in its
+/// channel, regardless of whether it was started by a command or something
+/// else (like a scheduled event).
+#[derive(Clone, Copy)]
+pub struct SessionContext<'a> {
+    pub discord: &'a serenity::Context,
+    pub data: &'a Data,
+    pub channel_id: ChannelId,
+    pub guild_id: Option<GuildId>,
+}
+
+impl<'a> From<Context<'a>> for SessionContext<'a> {
+    fn from(ctx: Context<'a>) -> Self {
+        Self {
+            discord: ctx.discord(),
+            data: ctx.data(),
+            channel_id: ctx.channel_id(),
+            guild_id: ctx.guild_id(),
+        }
+    }
+}
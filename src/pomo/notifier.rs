@@ -0,0 +1,248 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude::{ChannelId, MessageId, UserId};
+use uuid::Uuid;
+
+use crate::{
+    pomo::{
+        reply::{
+            dm_accountability_summary, get_topic, log_transcript, phase_topic, say_countdown,
+            say_phase_finished, say_queued_session_starting, say_roulette_spun,
+            say_session_expired, say_session_failed, say_session_finished,
+            say_session_finished_briefly, say_session_stopped, say_topic_disabled, set_topic,
+            transcript_members, unpin_message,
+        },
+        session::{PhaseType, SessionConfig},
+        SessionContext,
+    },
+    stats::MemberParticipation,
+};
+
+/// Something that can be told about everything that happens while a session
+/// runs.
+///
+/// Running a session only ever announces things through one of these, so
+/// that it can be run without Discord (e.g. in tests).
+#[async_trait]
+pub trait Notifier: Sync {
+    /// The session has started its `first` phase, with `members` in it.
+    async fn session_started(&self, first: PhaseType, members: Vec<UserId>);
+
+    /// The `finished` phase is over, and the `next` phase has started. `number`
+    /// is the number of the `next` phase, as given by
+    /// [`Session::phase_number`](crate::pomo::session::Session::phase_number).
+    /// If `silent` is set, `members` should still be mentioned, but without
+    /// their devices notifying them.
+    async fn phase_finished(
+        &self,
+        finished: PhaseType,
+        next: PhaseType,
+        number: Option<usize>,
+        members: Vec<UserId>,
+        start_link: Option<String>,
+        silent: bool,
+    );
+
+    /// The `finished` phase is over and the `next` phase has started, as
+    /// [`Notifier::phase_finished`] was told. Unlike that, this is only told
+    /// once the session has been let go of, so it can take its time.
+    async fn phase_changed(&self, finished: PhaseType, next: PhaseType);
+
+    /// Count down the last few seconds before `end`. This is cancelled if
+    /// the phase finishes first, so it doesn't need to return before `end`.
+    async fn countdown(&self, end: DateTime<Utc>);
+
+    /// Get the current topic of the session's channel, so that it can be
+    /// restored later.
+    async fn topic(&self) -> Option<String>;
+
+    /// Show that `phase_type` has started, and ends at `end`, in the channel
+    /// topic. Returns whether it was possible to change the topic.
+    async fn show_phase_in_topic(&self, phase_type: PhaseType, end: DateTime<Utc>) -> bool;
+
+    /// Put the channel topic back to what it was before the session, or clear
+    /// it if there wasn't one.
+    async fn restore_topic(&self, topic: Option<String>);
+
+    /// The channel topic couldn't be changed, so the session won't try again.
+    async fn topic_disabled(&self);
+
+    /// The session ended because it was asked to finish after a phase, having
+    /// completed `work_phases` work phases that were worth `focus_minutes`
+    /// minutes of focus time altogether.
+    async fn session_finished(&self, work_phases: usize, focus_minutes: usize);
+
+    /// The session ended because it was asked to finish after a phase, and
+    /// only wants a brief announcement of that.
+    async fn session_finished_briefly(&self);
+
+    /// The session was stopped.
+    async fn session_stopped(&self);
+
+    /// The session was stopped because it ran past its deadline.
+    async fn session_expired(&self);
+
+    /// The session with the given ID stopped because something went wrong.
+    async fn session_failed(&self, id: Uuid);
+
+    /// The session finished, and a session with `config` that was queued up
+    /// to follow it is starting.
+    async fn queued_session_starting(&self, config: &SessionConfig);
+
+    /// Unpin the message that was pinned when the session started.
+    async fn unpin_message(&self, channel_id: ChannelId, message_id: MessageId);
+
+    /// The roulette picked `minutes` as the length of the work phase that just
+    /// started.
+    async fn roulette_spun(&self, minutes: usize);
+
+    /// The session ended, and `user` wants to know what they took part in,
+    /// out of the `total_phases` phases that finished.
+    async fn accountability_summary(
+        &self,
+        user: UserId,
+        participation: MemberParticipation,
+        total_phases: usize,
+    );
+
+    /// The session is over, however it ended, and `participants` took part in
+    /// it at some point.
+    async fn session_ended(&self, participants: Vec<UserId>);
+}
+
+/// Announces everything in the session's channel.
+#[async_trait]
+impl Notifier for SessionContext<'_> {
+    // The session's channel was already told about the session starting by
+    // whatever started it, so this only goes in the transcript.
+    async fn session_started(&self, first: PhaseType, members: Vec<UserId>) {
+        log_transcript(
+            *self,
+            &format!(
+                "Session started with a {}. Members: {}.",
+                first.description(),
+                transcript_members(&members)
+            ),
+        )
+        .await;
+    }
+
+    async fn phase_finished(
+        &self,
+        finished: PhaseType,
+        next: PhaseType,
+        number: Option<usize>,
+        members: Vec<UserId>,
+        start_link: Option<String>,
+        silent: bool,
+    ) {
+        say_phase_finished(
+            *self,
+            finished,
+            next,
+            number,
+            members.iter(),
+            start_link,
+            silent,
+        )
+        .await;
+    }
+
+    // The transcript is another message to send, so it's kept out of
+    // `phase_finished`, which is told while every session is locked.
+    async fn phase_changed(&self, finished: PhaseType, next: PhaseType) {
+        log_transcript(
+            *self,
+            &format!(
+                "Finished a {}, starting a {}.",
+                finished.description(),
+                next.description()
+            ),
+        )
+        .await;
+    }
+
+    async fn countdown(&self, end: DateTime<Utc>) {
+        say_countdown(*self, end).await;
+    }
+
+    async fn topic(&self) -> Option<String> {
+        get_topic(*self).await
+    }
+
+    async fn show_phase_in_topic(&self, phase_type: PhaseType, end: DateTime<Utc>) -> bool {
+        set_topic(*self, &phase_topic(phase_type, end)).await
+    }
+
+    async fn restore_topic(&self, topic: Option<String>) {
+        set_topic(*self, topic.as_deref().unwrap_or_default()).await;
+    }
+
+    async fn topic_disabled(&self) {
+        say_topic_disabled(*self).await;
+    }
+
+    async fn session_finished(&self, work_phases: usize, focus_minutes: usize) {
+        say_session_finished(*self, work_phases, focus_minutes).await;
+        log_transcript(
+            *self,
+            &format!(
+                "Session finished after {} work sessions and {} minutes of focus.",
+                work_phases, focus_minutes
+            ),
+        )
+        .await;
+    }
+
+    async fn session_finished_briefly(&self) {
+        say_session_finished_briefly(*self).await;
+        log_transcript(*self, "Session finished.").await;
+    }
+
+    async fn session_stopped(&self) {
+        say_session_stopped(*self).await;
+        log_transcript(*self, "Session stopped.").await;
+    }
+
+    async fn session_expired(&self) {
+        say_session_expired(*self).await;
+        log_transcript(*self, "Session stopped for running too long.").await;
+    }
+
+    async fn session_failed(&self, id: Uuid) {
+        say_session_failed(*self, id).await;
+        log_transcript(*self, &format!("Session `{}` failed.", id)).await;
+    }
+
+    async fn queued_session_starting(&self, config: &SessionConfig) {
+        say_queued_session_starting(*self, config).await;
+    }
+
+    async fn unpin_message(&self, channel_id: ChannelId, message_id: MessageId) {
+        unpin_message(*self, channel_id, message_id).await;
+    }
+
+    async fn roulette_spun(&self, minutes: usize) {
+        say_roulette_spun(*self, minutes).await;
+    }
+
+    async fn accountability_summary(
+        &self,
+        user: UserId,
+        participation: MemberParticipation,
+        total_phases: usize,
+    ) {
+        dm_accountability_summary(*self, user, participation, total_phases).await;
+    }
+
+    async fn session_ended(&self, participants: Vec<UserId>) {
+        log_transcript(
+            *self,
+            &format!(
+                "Session over. Participants: {}.",
+                transcript_members(&participants)
+            ),
+        )
+        .await;
+    }
+}
@@ -1,25 +1,67 @@
-use std::ops::Deref;
-
 use chrono::{DateTime, Duration, Utc};
 use chrono_tz::Tz;
 use hhmmss::Hhmmss;
 use indoc::formatdoc;
 use poise::{serenity_prelude as serenity, CreateReply};
-use rand::seq::SliceRandom;
-use serenity::{Color, CreateEmbed, CreateMessage, MessageBuilder, UserId};
-use tracing::{error, instrument};
+use rand::Rng;
+use serenity::{
+    ChannelId, Color, CreateEmbed, CreateMessage, Mentionable, Message, MessageBuilder, MessageId,
+    RoleId, UserId,
+};
+use tap::TapFallible;
+use tokio::time::sleep;
+use tracing::{debug, error, instrument, warn};
 use uuid::Uuid;
 
+#[cfg(feature = "daily-stats")]
+use crate::stats::StatsSummary;
 use crate::{
-    pomo::session::{PhaseType, SessionConfig},
-    Context,
+    aliases::AliasOutcome,
+    commands::pomo::StartBlocked,
+    pomo::{
+        session::{
+            ConfigCodeError, ConfigError, Enrollment, FinishAnnouncement, PhaseType, Session,
+            SessionConfig, SessionEnding, SessionStatus, TimeSpent, ROULETTE_MINUTES,
+        },
+        vote::{Consensus, Outcome, Tally},
+        SessionContext,
+    },
+    presets::{AvailablePreset, PresetScope},
+    settings::{FirstSkip, GuildSettings, Pings},
+    stats::{MemberParticipation, StatsMode},
+    Context, Error,
 };
 
+mod limits;
 pub mod phrases;
 
 const GREEN: Color = Color::from_rgb(29, 131, 41);
 const RED: Color = Color::from_rgb(205, 46, 2);
 
+/// The code Discord's API gives when a channel has too many pinned messages.
+const MAX_PINS_ERROR_CODE: isize = 30003;
+
+/// The codes Discord's API gives when a channel is gone or we aren't allowed
+/// to post in it: Unknown Channel, Missing Access and Missing Permissions.
+const CHANNEL_UNUSABLE_ERROR_CODES: [isize; 3] = [10003, 50001, 50013];
+
+/// The message flag that stops Discord from making a sound or sending a push
+/// notification for a message, which serenity doesn't have a name for yet.
+const SUPPRESS_NOTIFICATIONS: u64 = 1 << 12;
+
+// Announcements of the next phase are coloured by what kind of phase it is, so
+// that members can tell at a glance whether they should be working. The work
+// colour is kept well away from `RED` so it doesn't look like an error.
+const WORK: Color = Color::from_rgb(219, 68, 128);
+const BREAK: Color = Color::from_rgb(52, 120, 196);
+
+fn phase_color(phase_type: PhaseType) -> Color {
+    match phase_type {
+        PhaseType::Work(_) => WORK,
+        PhaseType::Short(_) | PhaseType::Long(_) => BREAK,
+    }
+}
+
 fn no_footer<B>(builder: B) -> impl FnOnce(&mut CreateEmbed) -> &mut CreateEmbed
 where
     B: FnOnce(&mut CreateEmbed) -> &mut CreateEmbed,
@@ -76,225 +118,479 @@ where
             });
 
         // Then let the caller change what they like
-        builder(embed)
+        let embed = builder(embed);
+
+        // And finally make sure it can actually be sent
+        limits::fit_embed(embed);
+
+        embed
+    }
+}
+
+/// Add fields describing the phase lengths and long break interval in `config`
+/// to `embed`.
+fn config_fields<'a>(embed: &'a mut CreateEmbed, config: &SessionConfig) -> &'a mut CreateEmbed {
+    embed
+        .field(
+            format!("{} Work", PhaseType::Work(config.work).emoji()),
+            match config.alternate_work {
+                Some(alternate_work) => format!(
+                    "{} minutes, then {} minutes, alternating",
+                    config.work, alternate_work
+                ),
+                None => format!("{} minutes", config.work),
+            },
+            true,
+        )
+        .field(
+            format!("{} Short Break", PhaseType::Short(config.short).emoji()),
+            format!("{} minutes", config.short),
+            true,
+        )
+        .field(
+            format!("{} Long Break", PhaseType::Long(config.long).emoji()),
+            format!("{} minutes", config.long),
+            true,
+        )
+        .field(
+            "Interval",
+            if config.no_long {
+                "No long breaks".to_owned()
+            } else {
+                format!("Every {} work phases", config.interval)
+            },
+            false,
+        )
+}
+
+/// Describe which work phase or break a phase of type `phase_type` is, given
+/// its number from
+/// [`Session::phase_number`](crate::pomo::session::Session::phase_number).
+fn describe_phase_number(phase_type: PhaseType, number: usize) -> String {
+    match phase_type {
+        PhaseType::Work(_) => format!("Pomodoro #{}", number),
+        PhaseType::Short(_) | PhaseType::Long(_) => format!("Break #{}", number),
+    }
+}
+
+/// Describe `config` in one line, for when there are several to list.
+pub fn config_summary(config: &SessionConfig) -> String {
+    let work = match config.alternate_work {
+        Some(alternate_work) => format!("{}/{}", config.work, alternate_work),
+        None => config.work.to_string(),
+    };
+
+    if config.no_long {
+        format!("{} minutes of work, {} minute breaks", work, config.short)
+    } else {
+        format!(
+            "{} minutes of work, {} minute breaks, {} minute long break every {} work phases",
+            work, config.short, config.long, config.interval
+        )
+    }
+}
+
+/// Add fields describing the config of `session`, and any other settings it
+/// has, to `embed`.
+fn session_fields<'a>(embed: &'a mut CreateEmbed, session: &Session) -> &'a mut CreateEmbed {
+    let embed = config_fields(embed, session.config());
+
+    if session.roulette() {
+        embed.field(
+            "Roulette",
+            format!(
+                "Every work phase lasts a random {} to {} minutes instead. Place your bets.",
+                ROULETTE_MINUTES.start(),
+                ROULETTE_MINUTES.end()
+            ),
+            false,
+        );
+    }
+
+    if let Some(max) = session.max_members() {
+        embed.field("Max Members", max, false)
+    } else {
+        embed
     }
 }
 
 /// Returns the URL of the current user's avatar, if it succeeded in being
 /// found. If it couldn't be found, just returns `None` because I can't be
 /// bothered.
-async fn get_avatar_url(ctx: Context<'_>) -> Option<String> {
-    ctx.discord()
-        .http
-        .get_current_user()
+async fn get_avatar_url(http: &serenity::Http) -> Option<String> {
+    http.get_current_user()
         .await
         .ok()
         .and_then(|user| user.avatar_url())
 }
 
+/// If `ctx` is a prefix command, make sure `reply` isn't marked as ephemeral.
+///
+/// Discord only supports ephemeral messages as responses to interactions, so
+/// an "ephemeral" reply to a prefix command would be visible to everyone
+/// anyway. Doing this explicitly means it doesn't happen silently.
+fn without_ephemeral_for_prefix<'a, 'b>(
+    ctx: Context<'_>,
+    reply: &'a mut CreateReply<'b>,
+) -> &'a mut CreateReply<'b> {
+    if matches!(ctx, poise::Context::Prefix(_)) && reply.ephemeral {
+        debug!(
+            command = %ctx.command().name,
+            "prefix commands can't have ephemeral replies, sending a normal message"
+        );
+
+        reply.ephemeral(false)
+    } else {
+        reply
+    }
+}
+
+/// Reply to the command that `ctx` came from.
+///
+/// Replies can only be ephemeral in response to slash commands, so if `ctx` is
+/// a prefix command, a reply built as ephemeral is sent as a normal message
+/// instead (see [`without_ephemeral_for_prefix`]).
 async fn send_reply<M>(ctx: Context<'_>, make_builder: M)
 where
     M: for<'a, 'b> FnOnce(Option<String>, &'a mut CreateReply<'b>) -> &'a mut CreateReply<'b>,
 {
-    let avatar_url = get_avatar_url(ctx).await;
+    let avatar_url = get_avatar_url(&ctx.discord().http).await;
 
-    let result = poise::send_reply(ctx, |reply| make_builder(avatar_url, reply)).await;
+    let result = poise::send_reply(ctx, |reply| {
+        without_ephemeral_for_prefix(ctx, make_builder(avatar_url, reply))
+    })
+    .await;
 
     if let Err(error) = result {
         error!(?error, "unable to send reply");
     }
 }
 
-async fn send_message<M>(ctx: Context<'_>, make_builder: M)
+/// Like [`send_reply`], but if the guild has tidy mode on, the reply is
+/// deleted once the guild's tidy delay has passed.
+///
+/// This is for confirmations that everyone can see but nobody needs to keep
+/// seeing. Anything that's still useful later shouldn't use this.
+async fn send_tidy_reply<M>(ctx: Context<'_>, make_builder: M)
+where
+    M: for<'a, 'b> FnOnce(Option<String>, &'a mut CreateReply<'b>) -> &'a mut CreateReply<'b>,
+{
+    let delay = ctx
+        .data()
+        .guild_settings(ctx.guild_id())
+        .await
+        .tidy_delay
+        .and_then(|delay| delay.to_std().ok());
+
+    match delay {
+        Some(delay) => {
+            if let Some(message) = send_reply_and_get_message(ctx, make_builder).await {
+                let http = ctx.discord().http.clone();
+
+                tokio::spawn(async move {
+                    sleep(delay).await;
+
+                    message
+                        .channel_id
+                        .delete_message(&http, message.id)
+                        .await
+                        .tap_err(|error| warn!(?error, "unable to tidy up reply"))
+                        .ok();
+                });
+            }
+        }
+        None => send_reply(ctx, make_builder).await,
+    }
+}
+
+/// Like [`send_reply`], but also fetches and returns the message that was sent,
+/// if both sending and fetching it succeeded.
+async fn send_reply_and_get_message<M>(ctx: Context<'_>, make_builder: M) -> Option<Message>
+where
+    M: for<'a, 'b> FnOnce(Option<String>, &'a mut CreateReply<'b>) -> &'a mut CreateReply<'b>,
+{
+    let avatar_url = get_avatar_url(&ctx.discord().http).await;
+
+    let handle = match poise::send_reply(ctx, |reply| {
+        without_ephemeral_for_prefix(ctx, make_builder(avatar_url, reply))
+    })
+    .await
+    {
+        Ok(handle) => handle?,
+        Err(error) => {
+            error!(?error, "unable to send reply");
+            return None;
+        }
+    };
+
+    handle
+        .message()
+        .await
+        .tap_err(|error| warn!(?error, "unable to fetch sent reply"))
+        .ok()
+}
+
+async fn send_message<M>(ctx: SessionContext<'_>, make_builder: M) -> Option<Message>
 where
     M: for<'a, 'b> FnOnce(Option<String>, &'a mut CreateMessage<'b>) -> &'a mut CreateMessage<'b>,
 {
-    let avatar_url = get_avatar_url(ctx).await;
+    let avatar_url = get_avatar_url(&ctx.discord.http).await;
 
-    let result = ctx
-        .channel_id()
-        .send_message(&ctx.discord().http, |message| {
+    ctx.channel_id
+        .send_message(&ctx.discord.http, |message| {
             make_builder(avatar_url, message)
         })
-        .await;
-
-    if let Err(error) = result {
-        error!(?error, "unable to send message");
-    }
+        .await
+        .tap_err(|error| error!(?error, "unable to send message"))
+        .ok()
 }
 
+/// Reply with a description of the session that's starting, returning the
+/// message that was sent if possible.
 #[instrument(skip(ctx))]
-pub async fn reply_starting(ctx: Context<'_>, config: &SessionConfig, id: Uuid) {
-    let mut rng = &mut *ctx.data().rng.lock().await;
-    let phrase = phrases::STARTING_SESSION
-        .choose(&mut rng)
-        .expect("the list of phrases is not empty")
-        .deref()
-        .to_owned();
+pub async fn reply_starting(ctx: Context<'_>, session: &Session) -> Option<Message> {
+    let phrase = ctx.data().pick_weighted(phrases::STARTING_SESSION).await;
+    let settings = ctx.data().guild_settings(ctx.guild_id()).await;
 
-    send_reply(ctx, |avatar_url, reply| {
-        reply
-            .embed(green_embed(avatar_url, |embed| {
-                embed
-                    .title("Starting Session")
-                    .description(formatdoc! { "
-                        {}
+    send_reply_and_get_message(ctx, |avatar_url, reply| {
+        reply.embed(green_embed(avatar_url, |embed| {
+            starting_embed(embed, session, phrase, &settings)
+        }))
+    })
+    .await
+}
 
-                        This session will run until the `/stop` command is used. Use `/skip` to skip the rest of the current phase and start the next one.
-                        ",
-                        phrase
-                    })
-                    .field("Work", format!("{} minutes", config.work), true)
-                    .field("Short Break", format!("{} minutes", config.short), true)
-                    .field("Long Break", format!("{} minutes", config.long), true)
-                    .field(
-                        "Interval",
-                        format!("Every {} work phases", config.interval),
-                        false,
-                    )
-                    .field("Session ID", id, false)
-            }))
+/// Reply with a one-line confirmation that a session is starting, returning
+/// the message that was sent if possible.
+#[instrument(skip(ctx))]
+pub async fn reply_starting_silent(ctx: Context<'_>, session: &Session) -> Option<Message> {
+    send_reply_and_get_message(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            embed.description(format!("Session `{}` started.", session.id()))
+        })))
     })
-    .await;
+    .await
 }
 
+/// Announce that a session is starting in the session's channel, without
+/// replying to a command, returning the message that was sent if possible.
 #[instrument(skip(ctx))]
-pub async fn reply_cannot_start(ctx: Context<'_>) {
-    send_reply(ctx, |avatar_url, reply| {
-        reply.embed(red_embed(avatar_url, |embed| {
-            embed.title("Unable to Start Session").description(formatdoc! {"
-                Session is already running, now GET TO WORK.
+pub async fn say_session_starting(ctx: SessionContext<'_>, session: &Session) -> Option<Message> {
+    let phrase = ctx.data.pick_weighted(phrases::STARTING_SESSION).await;
+    let settings = ctx.data.guild_settings(ctx.guild_id).await;
 
-                Only one session can be running in each channel at a time. Try running `/stop` to stop the running session, or run this command again in a different channel.
-                 ",
-            })
+    send_message(ctx, |avatar_url, message| {
+        message.embed(green_embed(avatar_url, |embed| {
+            starting_embed(embed, session, phrase, &settings)
         }))
     })
-    .await;
+    .await
 }
 
-#[instrument(skip(ctx, members))]
-pub async fn say_phase_finished<I, M>(
-    ctx: Context<'_>,
-    finished: PhaseType,
-    next: PhaseType,
-    members: I,
-) where
-    I: Iterator<Item = M>,
-    M: AsRef<UserId>,
-{
-    let mentions = members
-        .fold(&mut MessageBuilder::new(), |builder, member| {
-            builder.mention(member.as_ref()).push(" ")
-        })
-        .build();
+fn starting_embed<'a>(
+    embed: &'a mut CreateEmbed,
+    session: &Session,
+    phrase: &str,
+    settings: &GuildSettings,
+) -> &'a mut CreateEmbed {
+    let embed = embed
+        .title("Starting Session")
+        .description(formatdoc! { "
+            {}
 
-    let phrases = match next {
-        PhaseType::Work(_) => phrases::STARTING_WORK,
-        PhaseType::Short(_) => phrases::STARTING_SHORT_BREAK,
-        PhaseType::Long(_) => phrases::STARTING_LONG_BREAK,
-    };
+            This session will run until the `/stop` command is used. Use `/skip` to skip the rest of the current phase and start the next one.
+            ",
+            phrase
+        });
 
-    let mut rng = &mut *ctx.data().rng.lock().await;
-    let phrase = phrases
-        .choose(&mut rng)
-        .expect("the list of phrases is not empty")
-        .deref()
-        .to_owned();
+    let embed = session_fields(embed, session);
 
-    send_message(ctx, |avatar_url, message| {
-        message
-            .content(mentions.trim())
+    if let Some(note) = session.config().sequence_note() {
+        embed.field("Note", note, false);
+    }
+
+    if let Some(limit) = settings.mention_limit {
+        let instead = match settings.mention_role {
+            Some(role) => format!("I'll ping {} instead", role.mention()),
+            None => "I won't ping anyone".to_owned(),
+        };
+
+        let pings = match settings.pings_for(session.members().len()) {
+            Pings::Members => format!(
+                "I'll ping everyone one by one, until there are more than {} of you. Then {}.",
+                limit, instead
+            ),
+            Pings::Role(_) | Pings::Nobody => format!(
+                "There are too many of you to ping one by one, so {}.",
+                instead
+            ),
+        };
+
+        embed.field("Pings", pings, false);
+    }
+
+    match session.finish_announcement() {
+        FinishAnnouncement::Full => {}
+        FinishAnnouncement::Brief => {
+            embed.field(
+                "When Finished",
+                "I'll keep the announcement short. Don't expect a party.",
+                false,
+            );
+        }
+        FinishAnnouncement::Silent => {
+            embed.field(
+                "When Finished",
+                "I won't announce anything, so don't wait around for me.",
+                false,
+            );
+        }
+    }
+
+    embed.field("Session ID", session.id(), false)
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_phases(ctx: Context<'_>, phases: &[(PhaseType, DateTime<Utc>)]) {
+    let schedule = phases
+        .iter()
+        .enumerate()
+        .map(|(i, (phase_type, start))| {
+            format!(
+                "`{}.` {}, starting <t:{}:R>",
+                i + 1,
+                phase_type.description(),
+                start.timestamp()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
             .embed(green_embed(avatar_url, |embed| {
-                embed
-                    .title(":rotating_light: WEE WOO :rotating_light: WEE WOO :rotating_light:")
-                    .description(format!("Starting a {}. {}", next.description(), phrase))
-                    .field("Just Finished", finished.description(), false)
+                embed.title("Upcoming Phases").description(schedule)
             }))
     })
     .await;
 }
 
+/// Reply with `timeline` as a strip of emoji, where the first phase is the one
+/// that's running if `running` is true.
 #[instrument(skip(ctx))]
-pub async fn reply_status(
-    ctx: Context<'_>,
-    phase_type: PhaseType,
-    phase_elapsed: Duration,
-    phase_remaining: Duration,
-    next_type: PhaseType,
-    long_at: DateTime<Utc>,
-    tz: Tz,
-) {
+pub async fn reply_timeline(ctx: Context<'_>, timeline: &[PhaseType], running: bool) {
+    let strip = timeline.iter().map(PhaseType::emoji).collect::<String>();
+
+    // If it didn't get as far as a long break, there's more to come.
+    let strip = match timeline.last() {
+        Some(PhaseType::Long(_)) => strip,
+        _ => format!("{}…", strip),
+    };
+
+    let note = if running {
+        "The first one is now. Plan accordingly."
+    } else {
+        "Nothing's running yet, so this is where it starts."
+    };
+
     send_reply(ctx, |avatar_url, reply| {
         reply
             .ephemeral(true)
-            .embed(green_embed(avatar_url, |embed| {
+            .embed(no_footer(green_embed(avatar_url, |embed| {
                 embed
-                    .title("Status")
-                    .field("Phase", phase_type.description(), false)
-                    .field("Elapsed", phase_elapsed.hhmmss(), true)
-                    .field("Remaining", phase_remaining.hhmmss(), true)
-                    .field("Next", next_type.description(), true)
-                    .field(
-                        "Next Long Break",
-                        format!(
-                            "{} ({}), {} from now",
-                            long_at.with_timezone(&tz).format("%H:%M:%S"),
-                            tz,
-                            (long_at - Utc::now()).hhmmss()
-                        ),
-                        false,
-                    )
-            }))
+                    .title("Timeline")
+                    .description(format!("{}\n\n{}", strip, note))
+            })))
     })
     .await;
 }
 
 #[instrument(skip(ctx))]
-pub async fn reply_status_no_session(ctx: Context<'_>) {
+pub async fn reply_phases_no_session(ctx: Context<'_>) {
     send_reply(ctx, |avatar_url, reply| {
         reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
             embed
                 .title("No Session")
-                .description("I can't tell you the status of a session that doesn't exist, genius.")
+                .description("The future is looking pretty empty. Try `/start`ing a session.")
         }))
     })
     .await;
 }
 
 #[instrument(skip(ctx))]
-pub async fn reply_joined(ctx: Context<'_>) {
+pub async fn reply_config(ctx: Context<'_>, session: &Session) {
     send_reply(ctx, |avatar_url, reply| {
         reply
             .ephemeral(true)
             .embed(green_embed(avatar_url, |embed| {
-                embed.title("Session Joined").description(
-                    "You will now be pinged when the phase changes. Use `/leave` to leave again.",
-                )
+                let embed = embed.title("Session Settings");
+
+                session_fields(embed, session).field("Session ID", session.id(), false)
             }))
     })
     .await;
 }
 
 #[instrument(skip(ctx))]
-pub async fn reply_join_already_member(ctx: Context<'_>) {
+pub async fn reply_config_toml(ctx: Context<'_>, config: &SessionConfig) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed
+                    .title("Session Settings")
+                    .description(format!("```toml\n{}```", config.to_toml()))
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_export(ctx: Context<'_>, config: &SessionConfig) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Config Code").description(format!(
+                    "Use `/import` with this code to start a session just like this one:\n\n`{}`",
+                    config.to_code()
+                ))
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_import_failed(ctx: Context<'_>, error: ConfigCodeError) {
+    let description = match error {
+        ConfigCodeError::Malformed => {
+            "That's not a config code. Did you copy the whole thing?".to_owned()
+        }
+        ConfigCodeError::UnsupportedVersion(_) => "That code is from a different version of me, \
+                                                   and I can't read it. Ask for a new one."
+            .to_owned(),
+        ConfigCodeError::Invalid(error) => {
+            format!("That code describes a broken config: {}.", error)
+        }
+    };
+
     send_reply(ctx, |avatar_url, reply| {
         reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
-            embed.title("Already a Member").description(
-                "You are already a member of this session, idiot. Use `/leave` to leave.",
-            )
+            embed
+                .title("Failed to Import Config")
+                .description(description)
         }))
     })
     .await;
 }
 
 #[instrument(skip(ctx))]
-pub async fn reply_join_no_session(ctx: Context<'_>) {
+pub async fn reply_config_no_session(ctx: Context<'_>) {
     send_reply(ctx, |avatar_url, reply| {
         reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
             embed.title("No Session").description(
-                "You can't join a session if there is no session! I can see you're paying \
-                 attention...",
+                "There's no session here. If you want to know the defaults, try `/defaults`.",
             )
         }))
     })
@@ -302,102 +598,2361 @@ pub async fn reply_join_no_session(ctx: Context<'_>) {
 }
 
 #[instrument(skip(ctx))]
-pub async fn reply_left(ctx: Context<'_>) {
+pub async fn reply_about(ctx: Context<'_>) {
     send_reply(ctx, |avatar_url, reply| {
         reply
             .ephemeral(true)
             .embed(green_embed(avatar_url, |embed| {
-                embed.title("Session Left").description(
-                    "You will no longer be pinged when the phase changes. Use `/join` to join \
-                     again.",
+                embed
+                    .title("About Pomocop")
+                    .description(
+                        "If you're here to report a bug, include all of this. I know it's \
+                         tedious. Do it anyway.",
+                    )
+                    .field("Version", env!("CARGO_PKG_VERSION"), true);
+
+                // Only present if the commit was known when the bot was built.
+                if let Some(commit) = option_env!("POMOCOP_GIT_COMMIT") {
+                    embed.field("Commit", format!("`{}`", commit), true);
+                }
+
+                embed.field("poise", env!("POISE_VERSION"), true).field(
+                    "serenity",
+                    env!("SERENITY_VERSION"),
+                    true,
                 )
             }))
     })
     .await;
 }
 
+/// Forward feedback given with `/feedback` in `from` to the feedback channel,
+/// along with where it came from.
+#[instrument(skip(ctx, from))]
+pub async fn say_feedback(
+    ctx: SessionContext<'_>,
+    from: Context<'_>,
+    text: &str,
+) -> Option<Message> {
+    let author = from.author();
+
+    send_message(ctx, |avatar_url, message| {
+        message.embed(no_footer(green_embed(avatar_url, |embed| {
+            let embed = embed
+                .title("Feedback")
+                .description(text)
+                .field(
+                    "From",
+                    format!("{} ({})", author.mention(), author.tag()),
+                    true,
+                )
+                .field("Channel", from.channel_id().mention(), true);
+
+            match from.guild_id() {
+                Some(guild_id) => embed.field("Server", format!("`{}`", guild_id), true),
+                None => embed.field("Server", "None (sent in a DM)", true),
+            }
+        })))
+    })
+    .await
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_feedback_sent(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.description(
+                    "Your feedback has been passed on. I'm sure someone will read it eventually.",
+                )
+            })))
+    })
+    .await;
+}
+
 #[instrument(skip(ctx))]
-pub async fn reply_leave_not_member(ctx: Context<'_>) {
+pub async fn reply_feedback_too_soon(ctx: Context<'_>, remaining: Duration) {
     send_reply(ctx, |avatar_url, reply| {
         reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
-            embed.title("Not a Member").description(
-                "You are not a member of this session, bird-brain. Use `/join` to join.",
-            )
+            embed.title("Slow Down").description(format!(
+                "You only just sent some feedback. Collect your thoughts and try again in {}.",
+                remaining.hhmmss()
+            ))
         }))
     })
     .await;
 }
 
 #[instrument(skip(ctx))]
-pub async fn reply_leave_no_session(ctx: Context<'_>) {
+pub async fn reply_feedback_unavailable(ctx: Context<'_>) {
     send_reply(ctx, |avatar_url, reply| {
         reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
-            embed
-                .title("No Session")
-                .description("Nice try, there has to be a session running for you to leave it.")
+            embed.title("Nobody's Listening").description(
+                "Whoever runs me hasn't set up anywhere for feedback to go. Click on the link in \
+                 the title and file an issue instead.",
+            )
         }))
     })
     .await;
 }
 
 #[instrument(skip(ctx))]
-pub async fn reply_skipping_phase(ctx: Context<'_>, skipped: PhaseType) {
-    let phrases = match skipped {
-        PhaseType::Work(_) => phrases::SKIPPING_WORK,
-        PhaseType::Short(_) | PhaseType::Long(_) => phrases::SKIPPING_BREAK,
-    };
-
-    let mut rng = &mut *ctx.data().rng.lock().await;
-    let phrase = phrases
-        .choose(&mut rng)
-        .expect("the list of phrases is not empty")
-        .deref()
-        .to_owned();
-
+pub async fn reply_feedback_failed(ctx: Context<'_>) {
     send_reply(ctx, |avatar_url, reply| {
-        reply.embed(no_footer(green_embed(avatar_url, |embed| {
-            embed.description(format!("Skipping {}. {}", skipped.description(), phrase))
-        })))
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Feedback Not Sent").description(
+                "I couldn't pass your feedback on. Try again later, or click on the link in the \
+                 title and file an issue instead.",
+            )
+        }))
     })
     .await;
 }
 
 #[instrument(skip(ctx))]
-pub async fn reply_skip_failed(ctx: Context<'_>, id: Uuid) {
+pub async fn reply_invite(ctx: Context<'_>, url: &str) {
     send_reply(ctx, |avatar_url, reply| {
-        reply.embed(red_embed(avatar_url, |embed| {
-            embed
-                .title("Failed to Skip Phase")
-                .description(formatdoc! { "
-                    It may have completed on its own. Please check if the phase already advanced, and if not, try again.
-
-                    A bug report would be appreciated. Please click on the link in the title of this embed, and quote the session ID below in your report. Thank you!
-                    ",
-                })
-                .field("Session ID", id, false)
-        }))
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.title("Invite Pomocop").url(url).description(
+                    "Click the title to add me to a server. I can't imagine why you'd want to \
+                     inflict me on anyone else, but go ahead.",
+                )
+            })))
     })
     .await;
 }
 
 #[instrument(skip(ctx))]
-pub async fn reply_skip_no_session(ctx: Context<'_>) {
+pub async fn reply_command_error(ctx: Context<'_>, message: String) {
     send_reply(ctx, |avatar_url, reply| {
-        reply.embed(red_embed(avatar_url, |embed| {
-            embed.title("Failed to Skip Phase").description(
-                "I'm not even running a session and you're already trying to get out of work?",
-            )
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Something Went Wrong").description(message)
         }))
     })
     .await;
 }
 
 #[instrument(skip(ctx))]
-pub async fn reply_stopping_session(ctx: Context<'_>) {
+pub async fn reply_guild_only(ctx: Context<'_>) {
     send_reply(ctx, |avatar_url, reply| {
-        reply.embed(no_footer(green_embed(avatar_url, |embed| {
-            embed.description("Stopping session...")
-        })))
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Server Only").description(
+                "Please use this in a server channel. A session in your DMs is just you and me, \
+                 and I'm not sharing.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_queued(ctx: Context<'_>, config: &SessionConfig) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(green_embed(avatar_url, |embed| {
+            let embed = embed.title("Session Queued").description(
+                "This session will start as soon as the current one finishes with `/finish`. If \
+                 it's stopped instead, the queued session is cancelled too. Use `/unqueue` to \
+                 cancel it yourself.",
+            );
+
+            config_fields(embed, config)
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_queue_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("No Session").description(
+                "There's nothing running for this to queue up behind. Just use `/start`, genius.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_unqueued(ctx: Context<'_>, queued: bool) {
+    send_reply(ctx, |avatar_url, reply| {
+        if queued {
+            reply.embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.description("Queued session cancelled. One session is plenty, apparently.")
+            })))
+        } else {
+            reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+                embed
+                    .title("Nothing Queued")
+                    .description("There's no session queued up here to cancel.")
+            }))
+        }
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn say_queued_session_starting(ctx: SessionContext<'_>, config: &SessionConfig) {
+    send_message(ctx, |avatar_url, message| {
+        message.embed(green_embed(avatar_url, |embed| {
+            let embed = embed.title("Starting Queued Session").description(
+                "No rest for the wicked. The session that was queued up is starting now, with all \
+                 the same members.",
+            );
+
+            config_fields(embed, config)
+        }))
+    })
+    .await;
+}
+
+/// Send `user` a DM summarising what they took part in, out of the
+/// `total_phases` phases of the session that just ended.
+///
+/// Plenty of people have DMs from server members turned off, so failing to send
+/// it is only worth a warning.
+#[instrument(skip(ctx))]
+pub async fn dm_accountability_summary(
+    ctx: SessionContext<'_>,
+    user: UserId,
+    participation: MemberParticipation,
+    total_phases: usize,
+) {
+    let closing = ctx.data.pick(phrases::ACCOUNTABILITY_CLOSING).await;
+    let avatar_url = get_avatar_url(&ctx.discord.http).await;
+
+    let channel = match user.create_dm_channel(&ctx.discord.http).await {
+        Ok(channel) => channel,
+        Err(error) => {
+            warn!(?error, "unable to open DM for accountability summary");
+            return;
+        }
+    };
+
+    channel
+        .send_message(&ctx.discord.http, |message| {
+            message.embed(green_embed(avatar_url, |embed| {
+                embed
+                    .title("Your Session")
+                    .description(format!(
+                        "The session in {} is over. Here's how you did. {}",
+                        ctx.channel_id.mention(),
+                        closing
+                    ))
+                    .field(
+                        "Phases",
+                        format!("{} of {}", participation.phases, total_phases),
+                        true,
+                    )
+                    .field(
+                        "Focus Time",
+                        Duration::minutes(participation.focus_minutes as i64).hhmmss(),
+                        true,
+                    )
+                    .field(
+                        "Completion",
+                        format!("{}%", participation.completion_percent(total_phases)),
+                        true,
+                    )
+                    .footer(|footer| footer.text("Use /accountability to stop these."))
+            }))
+        })
+        .await
+        .tap_err(|error| warn!(?error, "unable to send accountability summary"))
+        .ok();
+}
+
+#[instrument(skip(ctx))]
+pub async fn say_roulette_spun(ctx: SessionContext<'_>, minutes: usize) {
+    send_message(ctx, |avatar_url, message| {
+        message.embed(no_footer(embed_with_defaults(
+            avatar_url,
+            phase_color(PhaseType::Work(minutes)),
+            |embed| {
+                embed.description(format!(
+                    ":slot_machine: The wheel spins... and spins... and lands on **{} minutes** \
+                     of work. No refunds.",
+                    minutes
+                ))
+            },
+        )))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_defaults(ctx: Context<'_>, config: &SessionConfig) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                let embed = embed.title("Default Settings").description(
+                    "This is what you get if you can't be bothered to pick your own settings when \
+                     running `/start`.",
+                );
+
+                config_fields(embed, config)
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_preset_saved(
+    ctx: Context<'_>,
+    name: &str,
+    scope: PresetScope,
+    config: &SessionConfig,
+    replaced: bool,
+) {
+    let who = match scope {
+        PresetScope::User => "you",
+        PresetScope::Guild => "everyone in this server",
+    };
+    let replaced = if replaced {
+        " It replaced the old one with the same name, which is gone forever."
+    } else {
+        ""
+    };
+
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                let embed = embed.title("Preset Saved").description(format!(
+                    "Saved `{0}` for {1}. Start a session from it with `/start preset:{0}`.{2}",
+                    name.trim().to_lowercase(),
+                    who,
+                    replaced
+                ));
+
+                config_fields(embed, config)
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_presets(ctx: Context<'_>, presets: &[AvailablePreset]) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                let embed = embed.title("Presets").description(
+                    "Start a session from any of these with `/start preset:<name>`, or save your \
+                     own with `/savepreset`.",
+                );
+
+                presets.iter().fold(embed, |embed, preset| {
+                    let scope = match preset.scope {
+                        None => "built in",
+                        Some(PresetScope::User) => "yours",
+                        Some(PresetScope::Guild) => "this server's",
+                    };
+
+                    embed.field(
+                        format!("{} ({})", preset.name, scope),
+                        config_summary(&preset.config),
+                        false,
+                    )
+                })
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_preset_deleted(ctx: Context<'_>, name: &str) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.description(format!(
+                    "Preset `{}` deleted. It's not like you were using it anyway.",
+                    name.trim().to_lowercase()
+                ))
+            })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_preset_not_found(ctx: Context<'_>, name: &str) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("No Such Preset").description(format!(
+                "There's no preset called `{}` that you can use. Try `/presets` to see the ones \
+                 that actually exist.",
+                name.trim()
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_preset_not_allowed(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Not Allowed").description(
+                "Only server admins can change this server's presets, and only in the server. \
+                 Save one for yourself instead.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_template_saved(
+    ctx: Context<'_>,
+    name: &str,
+    config: &SessionConfig,
+    replaced: bool,
+) {
+    let replaced = if replaced {
+        " It replaced the old one with the same name."
+    } else {
+        ""
+    };
+
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                let embed = embed.title("Template Saved").description(format!(
+                    "Saved `{0}` for everyone in this server. They can start a session from it \
+                     with `/start template:{0}`, but only admins can change it.{1}",
+                    name.trim().to_lowercase(),
+                    replaced
+                ));
+
+                config_fields(embed, config)
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_templates(ctx: Context<'_>, templates: &[(String, SessionConfig)]) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                if templates.is_empty() {
+                    return embed.title("Templates").description(
+                        "This server doesn't have any templates. Ask an admin to make some with \
+                         `/template create`, or just use `/start` like everyone else.",
+                    );
+                }
+
+                let embed = embed.title("Templates").description(
+                    "Start a session from any of these with `/start template:<name>`.",
+                );
+
+                templates.iter().fold(embed, |embed, (name, config)| {
+                    embed.field(name, config_summary(config), false)
+                })
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_template_deleted(ctx: Context<'_>, name: &str) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.description(format!(
+                    "Template `{}` deleted. Hope nobody was attached to it.",
+                    name.trim().to_lowercase()
+                ))
+            })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_template_not_found(ctx: Context<'_>, name: &str) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("No Such Template").description(format!(
+                "This server doesn't have a template called `{}`. Try `/templates` to see the \
+                 ones it does have.",
+                name.trim()
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_cannot_start(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(red_embed(avatar_url, |embed| {
+            embed.title("Unable to Start Session").description(formatdoc! {"
+                Session is already running, now GET TO WORK.
+
+                Only one session can be running in each channel at a time. Try running `/stop` to stop the running session, or run this command again in a different channel.
+                 ",
+            })
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_cannot_start_in_guild(ctx: Context<'_>, existing: ChannelId) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Unable to Start Session")
+                .description(formatdoc! {"
+                There's already a session running in {}. Go and join that one.
+
+                Only one session can be running in this server at a time.
+                ",
+                    existing.mention(),
+                })
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_at_capacity(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Unable to Start Session").description(
+                "I'm already running as many sessions as I can handle. Believe it or not, other \
+                 people want to work too. Try again later.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_cooldown(ctx: Context<'_>, remaining: Duration) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Unable to Start Session").description(format!(
+                "A session only just ended here. Calm down and try again in {}.",
+                remaining.hhmmss()
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_cooldown_invalid(ctx: Context<'_>, max_seconds: usize) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Unable to Set Cooldown").description(format!(
+                "The cooldown can be at most {} seconds. Nobody needs to wait longer than that.",
+                max_seconds
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_cooldown_set(ctx: Context<'_>, cooldown: Duration) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Cooldown Set").description(format!(
+                    "New sessions can now be started {} after the last one in the same channel \
+                     ended.",
+                    cooldown.hhmmss()
+                ))
+            }))
+    })
+    .await;
+}
+
+#[cfg(feature = "daily-stats")]
+#[instrument(skip(ctx))]
+pub async fn reply_daily_stats_set(ctx: Context<'_>, enabled: bool) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.description(if enabled {
+                    "I'll post a summary of everyone's focus time here every day. Prepare to be \
+                     judged."
+                } else {
+                    "No more daily summaries. Your slacking will go unrecorded."
+                })
+            })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_stats_mode_set(ctx: Context<'_>, mode: StatsMode) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Stats Mode Set").description(match mode {
+                    StatsMode::Strict => {
+                        "Only work sessions that run to the end count towards focus time. No \
+                         participation trophies."
+                    }
+                    StatsMode::Partial => {
+                        "Skipped and stopped work sessions now count towards focus time for as \
+                         long as they ran. How generous of you."
+                    }
+                })
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_thread_sessions_set(ctx: Context<'_>, share: bool) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Thread Sessions Set").description(if share {
+                    "Threads now share their parent channel's session, so nobody can hide from it \
+                     in a thread."
+                } else {
+                    "Threads now get their own sessions, separate from their parent channel's."
+                })
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_one_session_set(ctx: Context<'_>, enabled: bool) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Session Limit Set").description(if enabled {
+                    "Only one session can run in this server at a time now. Everyone can suffer \
+                     together."
+                } else {
+                    "Each channel can have its own session again."
+                })
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_mentions_set(ctx: Context<'_>, limit: Option<usize>, role: Option<RoleId>) {
+    let description = match (limit, role) {
+        (None, _) => "I'll ping every member of a session one by one, however many there are. \
+                      Hope you like notifications."
+            .to_owned(),
+        (Some(limit), Some(role)) => format!(
+            "Once a session has more than {} members, I'll ping {} instead of each of them.",
+            limit,
+            role.mention()
+        ),
+        (Some(limit), None) => format!(
+            "Once a session has more than {} members, I won't ping anyone. You'll just have to \
+             keep an eye on the channel.",
+            limit
+        ),
+    };
+
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Pings Set").description(description)
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_first_skip_set(ctx: Context<'_>, first_skip: FirstSkip) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("First Skip Set").description(match first_skip {
+                    FirstSkip::Confirm => {
+                        "Skipping the first phase of a session now has to be confirmed, for \
+                         everyone who \"accidentally\" skips before they've started."
+                    }
+                    FirstSkip::Comment => {
+                        "Skipping the first phase of a session works, but I'll have something to \
+                         say about it."
+                    }
+                    FirstSkip::Normal => {
+                        "Skipping the first phase of a session works like skipping any other. Your \
+                         funeral."
+                    }
+                })
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_audit_channel_set(ctx: Context<'_>, channel: Option<ChannelId>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Audit Log Set").description(match channel {
+                    Some(channel) => format!(
+                        "Admin commands will be logged in {} from now on. Nobody can say they \
+                         weren't warned.",
+                        channel.mention()
+                    ),
+                    None => "Admin commands won't be logged any more. What happens in admin \
+                             commands stays in admin commands."
+                        .to_owned(),
+                })
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_transcript_channel_set(ctx: Context<'_>, channel: Option<ChannelId>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Transcript Set").description(match channel {
+                    Some(channel) => format!(
+                        "Every session in this server will be logged in {} from now on. Your \
+                         permanent record starts now.",
+                        channel.mention()
+                    ),
+                    None => "Sessions won't be logged any more. Your secrets are safe with me."
+                        .to_owned(),
+                })
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_guild_timezone_set(ctx: Context<'_>, tz: Tz) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Time Zone Set").description(format!(
+                    "Times will be shown in {} for anyone who doesn't ask for their own time zone.",
+                    tz
+                ))
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_invalid_timezone(ctx: Context<'_>, timezone: &str) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Invalid Time Zone").description(format!(
+                "`{}` isn't a time zone I know. Try something like `Europe/Berlin`.",
+                timezone
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_tips_set(ctx: Context<'_>, percent: u32) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                let embed = embed.title("Tips Set");
+
+                match percent {
+                    0 => embed.description("No more tips. They can figure it out themselves."),
+                    percent => embed.description(format!(
+                        "{}% of work sessions will now start with a tip. Not that anyone will \
+                         listen.",
+                        percent
+                    )),
+                }
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_tidy_set(ctx: Context<'_>, delay: Option<Duration>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                let embed = embed.title("Tidy Mode");
+
+                match delay {
+                    Some(delay) => embed.description(format!(
+                        "Confirmations will now be deleted {} after they're sent. Neat freak.",
+                        delay.hhmmss()
+                    )),
+                    None => embed.description("Tidy mode is off. Enjoy the mess."),
+                }
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_force_stopped(ctx: Context<'_>, channel_id: ChannelId) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Session Stopped").description(format!(
+                    "The session in {} has been shut down. Nothing to see here.",
+                    channel_id.mention()
+                ))
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_force_stop_not_found(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Session Not Found").description(
+                "I couldn't find a session with that ID. You need at least the first 8 characters \
+                 of it.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_force_stop_ambiguous(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Which One?").description(
+                "More than one session starts with that. Give me more of the ID and I'll think \
+                 about it.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_force_stop_failed(ctx: Context<'_>, id: Uuid) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Failed to Stop Session")
+                .description(formatdoc! { "
+                    It may be between phases. Please try again.
+
+                    A bug report would be appreciated. Please click on the link in the title of this embed, and quote the session ID below in your report. Thank you!
+                    ",
+                })
+                .field("Session ID", id, false)
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_event_linked(ctx: Context<'_>, channel_id: ChannelId) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Event Linked").description(format!(
+                    "A session will be started in {} when the event starts, and stopped when it \
+                     ends. Attendance is mandatory.",
+                    channel_id.mention()
+                ))
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_event_unlinked(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Event Unlinked").description(
+                    "Sessions will no longer be started for this event. Any session that's \
+                     already running will keep going until someone stops it.",
+                )
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_event_not_linked(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Event Not Linked")
+                .description("That event isn't linked to anything. Check your IDs.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_event_not_found(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Event Not Found")
+                .description("There's no event with that ID in this server. Check your IDs.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_event_channel_elsewhere(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Wrong Channel").description(
+                "Sessions for this server's events have to run in one of this server's channels.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_invalid_event_id(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Invalid Event ID").description(
+                "That's not an event ID. Right-click the event and select \"Copy ID\", it's not \
+                 that hard.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx, members))]
+pub async fn say_phase_finished<I, M>(
+    ctx: SessionContext<'_>,
+    finished: PhaseType,
+    next: PhaseType,
+    number: Option<usize>,
+    members: I,
+    start_link: Option<String>,
+    silent: bool,
+) where
+    I: Iterator<Item = M>,
+    M: AsRef<UserId>,
+{
+    let settings = ctx.data.guild_settings(ctx.guild_id).await;
+    let members = members.collect::<Vec<_>>();
+
+    // In a DM, the message notifies the only person who could be in the session
+    // anyway.
+    let pings = if ctx.guild_id.is_some() {
+        settings.pings_for(members.len())
+    } else {
+        Pings::Nobody
+    };
+
+    let mentions = match pings {
+        Pings::Members => ctx
+            .data
+            .pings_due(
+                members.iter().map(|member| *member.as_ref()).collect(),
+                Utc::now(),
+            )
+            .await
+            .into_iter()
+            .fold(&mut MessageBuilder::new(), |builder, member| {
+                builder.mention(&member).push(" ")
+            })
+            .build(),
+        Pings::Role(role) => role.mention().to_string(),
+        Pings::Nobody => String::new(),
+    };
+
+    let phrases = match next {
+        PhaseType::Work(_) => phrases::STARTING_WORK,
+        PhaseType::Short(_) => phrases::STARTING_SHORT_BREAK,
+        PhaseType::Long(_) => phrases::STARTING_LONG_BREAK,
+    };
+
+    let tip_percent = settings.work_tip_percent.min(100);
+
+    let phrase = ctx.data.pick(phrases).await;
+    let thumbnail = ctx
+        .data
+        .phase_thumbnails
+        .lock()
+        .await
+        .for_phase(next)
+        .map(ToOwned::to_owned);
+
+    let tip = if matches!(next, PhaseType::Work(_))
+        && ctx.data.rng.lock().await.gen_ratio(tip_percent, 100)
+    {
+        Some(ctx.data.pick(phrases::WORK_TIPS).await)
+    } else {
+        None
+    };
+
+    send_message(ctx, |avatar_url, message| {
+        if silent {
+            message.0.insert("flags", SUPPRESS_NOTIFICATIONS.into());
+        }
+
+        // This is posted after every phase, so the footer would just be repeated
+        // over and over.
+        message.content(mentions.trim()).embed(embed_with_defaults(
+            avatar_url,
+            phase_color(next),
+            no_footer(|embed| {
+                let embed = embed
+                    .title(":rotating_light: WEE WOO :rotating_light: WEE WOO :rotating_light:")
+                    .description(format!(
+                        "{} Starting a {}. {}",
+                        next.emoji(),
+                        next.description(),
+                        phrase
+                    ))
+                    .field(
+                        "Just Finished",
+                        format!("{} {}", finished.emoji(), finished.description()),
+                        false,
+                    );
+
+                if let Some(number) = number {
+                    embed.field("Progress", describe_phase_number(next, number), false);
+                }
+
+                if let Some(thumbnail) = thumbnail {
+                    embed.thumbnail(thumbnail);
+                }
+
+                if let Some(tip) = tip {
+                    embed.field("Tip", tip, false);
+                }
+
+                if let Some(link) = start_link {
+                    embed.field("Session", format!("[Jump to start]({})", link), false);
+                }
+
+                embed
+            }),
+        ))
+    })
+    .await;
+}
+
+/// Everything `/status` shows about a running session, which is what
+/// [`SessionStatus::Running`] holds.
+#[derive(Debug)]
+pub struct StatusView {
+    pub phase_type: PhaseType,
+    pub phase_number: Option<usize>,
+    pub phase_elapsed: Duration,
+    pub phase_remaining: Duration,
+    pub phase_planned: Duration,
+    pub phase_length: Duration,
+    pub session_elapsed: Duration,
+    pub next_type: PhaseType,
+    pub long_at: Option<DateTime<Utc>>,
+    pub ending: Option<SessionEnding>,
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_status(ctx: Context<'_>, status: StatusView, tz: Tz) {
+    let StatusView {
+        phase_type,
+        phase_number,
+        phase_elapsed,
+        phase_remaining,
+        phase_planned,
+        phase_length,
+        session_elapsed,
+        next_type,
+        long_at,
+        ending,
+    } = status;
+
+    let length = if phase_length == phase_planned {
+        format!("{} (as planned)", phase_length.hhmmss())
+    } else {
+        format!(
+            "{} (planned: {})",
+            phase_length.hhmmss(),
+            phase_planned.hhmmss()
+        )
+    };
+
+    let long_at = match long_at {
+        Some(long_at) => format!(
+            "{} ({}), {} from now",
+            long_at.with_timezone(&tz).format("%H:%M:%S"),
+            tz,
+            (long_at - Utc::now()).hhmmss()
+        ),
+        None => "Never, apparently you don't need one".to_owned(),
+    };
+
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                let phase = match phase_number {
+                    Some(number) => format!(
+                        "{} ({})",
+                        phase_type.description(),
+                        describe_phase_number(phase_type, number)
+                    ),
+                    None => phase_type.description(),
+                };
+
+                let embed = embed
+                    .title("Status")
+                    .field(format!("{} Phase", phase_type.emoji()), phase, false)
+                    .field("Elapsed", phase_elapsed.hhmmss(), true)
+                    .field("Remaining", phase_remaining.hhmmss(), true)
+                    .field("Length", length, true)
+                    .field(
+                        format!("{} Next", next_type.emoji()),
+                        next_type.description(),
+                        true,
+                    )
+                    .field("Session Elapsed", session_elapsed.hhmmss(), true)
+                    .field("Next Long Break", long_at, false);
+
+                match ending {
+                    Some(SessionEnding::Finishing) => embed.field(
+                        "Last Phase",
+                        "The session finishes when this phase is complete. Make it count.",
+                        false,
+                    ),
+                    Some(SessionEnding::Expiring(at)) => embed.field(
+                        "Last Phase",
+                        format!(
+                            "The session hits its maximum length and stops at <t:{}:t>, before \
+                             this phase is over.",
+                            at.timestamp()
+                        ),
+                        false,
+                    ),
+                    None => embed,
+                }
+            }))
+    })
+    .await;
+}
+
+/// Reply with the status of a session in one line, which is easier to read on
+/// a phone than the full embed. Times are Discord timestamps, so everyone sees
+/// them in their own time zone.
+#[instrument(skip(ctx))]
+pub async fn reply_status_compact(
+    ctx: Context<'_>,
+    phase_type: PhaseType,
+    phase_remaining: Duration,
+    next_type: PhaseType,
+) {
+    let end = Utc::now() + phase_remaining;
+
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.description(format!(
+                    "{0}, ends <t:{1}:t> (<t:{1}:R>). Next: {2}.",
+                    phase_type.description(),
+                    end.timestamp(),
+                    next_type.description()
+                ))
+            })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_long_break(ctx: Context<'_>, long_at: DateTime<Utc>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.description(format!(
+                    "The next long break starts at <t:{0}:t>, <t:{0}:R>. Until then, get back to \
+                     work.",
+                    long_at.timestamp()
+                ))
+            })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_long_break_never(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.description(
+                    "This session doesn't have long breaks. Apparently you don't need one.",
+                )
+            })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_long_break_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("No Session").description(
+                "There's no session running, so every break is a long break. Enjoy it.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_reloaded(ctx: Context<'_>, changed: &[&str]) {
+    let description = if changed.is_empty() {
+        "Nothing changed. Were you expecting something to?".to_owned()
+    } else {
+        format!(
+            "Reloaded. These changed: {}",
+            changed
+                .iter()
+                .map(|key| format!("`{}`", key))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed
+                    .title("Configuration Reloaded")
+                    .description(description)
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_reload_failed(ctx: Context<'_>, error: &Error) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Reload Failed").description(format!(
+                "Nothing was changed, because the new configuration doesn't work: {}",
+                error
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx, dump))]
+pub async fn reply_debug_dump(ctx: Context<'_>, dump: String, sessions: usize) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("State Dump").description(format!(
+                    "Here's everything I know about the {} session{} I'm running. Try not to lose \
+                     it.",
+                    sessions,
+                    if sessions == 1 { "" } else { "s" }
+                ))
+            }))
+            .attachment(serenity::AttachmentType::Bytes {
+                data: dump.into_bytes().into(),
+                filename: "pomocop-dump.json".to_owned(),
+            })
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_break_time(ctx: Context<'_>, time_spent: TimeSpent) {
+    let description = match time_spent.break_percentage() {
+        Some(percentage) => {
+            let judgement = if percentage < 15.0 {
+                "Do you even blink?"
+            } else if percentage <= 35.0 {
+                "That's about what a pomodoro is supposed to look like. Don't let it go to your \
+                 head."
+            } else if percentage <= 60.0 {
+                "That's a lot of resting for not a lot of working."
+            } else {
+                "Are you sure you know what a pomodoro is?"
+            };
+
+            format!(
+                "You've spent {:.0}% as long on breaks as you have working. {}",
+                percentage, judgement
+            )
+        }
+        None => "You haven't done any work yet, so I can't even work out a ratio. Impressive."
+            .to_owned(),
+    };
+
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed
+                    .title("Break Time")
+                    .description(description)
+                    .field("Working", time_spent.work.hhmmss(), true)
+                    .field("Breaks", time_spent.breaks.hhmmss(), true)
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_break_time_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("No Session").description(
+                "There's no session running, so it's all break time. That's not a good ratio.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_health_ok(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.description("This session is alive and well. Unlike your motivation.")
+            })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_health_stalled(ctx: Context<'_>, id: Uuid) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Session Stalled")
+                .description(formatdoc! { "
+                    This session had stopped running without telling anyone, so I've cleaned it up. You can run `/start` to start a new one.
+
+                    A bug report would be appreciated. Please click on the link in the title of this embed, and quote the session ID below in your report. Thank you!
+                    ",
+                })
+                .field("Session ID", id, false)
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_health_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("No Session")
+                .description("There's no session here, so it's about as healthy as it gets.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_status_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("No Session")
+                .description("I can't tell you the status of a session that doesn't exist, genius.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_last_session(ctx: Context<'_>, ended: DateTime<Utc>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Last Session").description(format!(
+                    "The last session in this channel ended {} ago. Not that anyone's counting.",
+                    (Utc::now() - ended).hhmmss()
+                ))
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_last_session_running(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Last Session").description(
+                    "There's a session running right now. Maybe try paying attention to it?",
+                )
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_last_session_never(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("No Sessions").description(
+                "I haven't seen a single session in this channel. Colour me unsurprised.",
+            )
+        }))
+    })
+    .await;
+}
+
+/// Reply to someone who joined a session, with where the session was up to
+/// when they joined so that they don't have to ask with `/status` as well.
+#[instrument(skip(ctx))]
+pub async fn reply_joined(ctx: Context<'_>, status: SessionStatus) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                let embed = embed.title("Session Joined").description(
+                    "You will now be pinged when the phase changes. Use `/leave` to leave again.",
+                );
+
+                match status {
+                    SessionStatus::Running {
+                        phase_type,
+                        phase_remaining,
+                        next_type,
+                        ..
+                    } => {
+                        let end = Utc::now() + phase_remaining;
+
+                        embed
+                            .field(
+                                format!("{} Now", phase_type.emoji()),
+                                phase_type.description(),
+                                true,
+                            )
+                            .field(
+                                "Ends",
+                                format!("<t:{0}:t> (<t:{0}:R>)", end.timestamp()),
+                                true,
+                            )
+                            .field(
+                                format!("{} Next", next_type.emoji()),
+                                next_type.description(),
+                                true,
+                            )
+                    }
+                    // The session is between phases, so there's nothing to
+                    // catch up on yet.
+                    SessionStatus::NoSession => embed.field(
+                        "Now",
+                        "The next phase is just about to start. You'll be pinged when it does.",
+                        false,
+                    ),
+                }
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_join_already_member(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Already a Member").description(
+                "You are already a member of this session, idiot. Use `/leave` to leave.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_join_full(ctx: Context<'_>, max_members: usize) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Session Full").description(format!(
+                "This session already has {} members, which is all it's allowed. Go find your own \
+                 channel.",
+                max_members
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_join_during_break(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Not Yet").description(
+                "This session only lets people join during breaks, so that nobody gets disturbed \
+                 mid-focus. You can join during the next break.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_join_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("No Session").description(
+                "You can't join a session if there is no session! I can see you're paying \
+                 attention...",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_enrolled(ctx: Context<'_>, enrollment: &Enrollment) {
+    let mut skipped = Vec::new();
+
+    if enrollment.already_members > 0 {
+        skipped.push(format!("{} already in it", enrollment.already_members));
+    }
+
+    if enrollment.opted_out > 0 {
+        skipped.push(format!(
+            "{} who opted out of being added",
+            enrollment.opted_out
+        ));
+    }
+
+    if enrollment.no_room > 0 {
+        skipped.push(format!("{} who didn't fit", enrollment.no_room));
+    }
+
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            let embed = embed.description(format!(
+                "Added {} to the session. No escape now.",
+                match enrollment.added {
+                    1 => "1 member".to_owned(),
+                    added => format!("{} members", added),
+                }
+            ));
+
+            if !skipped.is_empty() {
+                embed.field("Skipped", skipped.join(", "), false);
+            }
+
+            embed
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_not_host(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Not the Host").description(
+                "Only the host of this session can add people to it. Who are you again?",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_join_all_no_voice(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Not in a Voice Channel").description(
+                "I add everyone in your voice channel, so you need to actually be in one first.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_opt_out_set(ctx: Context<'_>, opted_out: bool) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.description(if opted_out {
+                    "Hosts can no longer add you to their sessions. You'll have to use `/join` \
+                     yourself, if you ever feel like working."
+                } else {
+                    "Hosts can add you to their sessions again. Brave."
+                })
+            })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_accountability_set(ctx: Context<'_>, enabled: bool) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.description(if enabled {
+                    "I'll DM you a summary of what you actually did at the end of each session. \
+                     You asked for this."
+                } else {
+                    "No more summaries. Ignorance is bliss, I suppose."
+                })
+            })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_stats_reset(ctx: Context<'_>, minutes: usize) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Stats Reset").description(format!(
+                    "Deleted all {} minutes of focus time I had recorded for you. As far as I'm \
+                     concerned, you've never done a day's work in your life.",
+                    minutes
+                ))
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_guild_stats_reset(ctx: Context<'_>, minutes: usize) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Server Stats Reset").description(format!(
+                    "Deleted all {} minutes of focus time recorded in this server. Fresh start. \
+                     Try not to waste it.",
+                    minutes
+                ))
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_stats_reset_nothing(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Nothing to Reset").description(
+                "There's no focus time recorded to delete. Can't lose what you never had.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_left(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Session Left").description(
+                    "You will no longer be pinged when the phase changes. Use `/join` to join \
+                     again.",
+                )
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_leave_not_member(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Not a Member").description(
+                "You are not a member of this session, bird-brain. Use `/join` to join.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_leave_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("No Session")
+                .description("Nice try, there has to be a session running for you to leave it.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_silenced(ctx: Context<'_>, phases: usize, until: Option<DateTime<Utc>>) {
+    let changes = format!(
+        "{} phase change{}",
+        phases,
+        if phases == 1 { "" } else { "s" }
+    );
+
+    let description = match until {
+        Some(until) => format!(
+            "You won't be pinged for the next {}. Enjoy the peace and quiet while it lasts, \
+             because you'll be pinged again <t:{}:R>.",
+            changes,
+            until.timestamp()
+        ),
+        None => format!("You won't be pinged for the next {}.", changes),
+    };
+
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(green_embed(avatar_url, |embed| {
+                embed.title("Silenced").description(description)
+            }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_silence_invalid(ctx: Context<'_>, max_phases: usize) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Unable to Silence").description(format!(
+                "You can only stay quiet for between 1 and {} phase changes at a time.",
+                max_phases
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_silence_not_member(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Not a Member").description(
+                "You aren't even a member of this session, so nothing is pinging you anyway.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_silence_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("No Session")
+                .description("There's no session running, so nothing is going to ping you.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_skipping_phase(
+    ctx: Context<'_>,
+    skipped: PhaseType,
+    next: PhaseType,
+    first_phase: bool,
+    undo_seconds: i64,
+) {
+    let phrases = match skipped {
+        _ if first_phase => phrases::SKIPPING_FIRST_PHASE,
+        PhaseType::Work(_) => phrases::SKIPPING_WORK,
+        PhaseType::Short(_) | PhaseType::Long(_) => phrases::SKIPPING_BREAK,
+    };
+
+    let phrase = ctx.data().pick(phrases).await;
+
+    send_tidy_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            embed
+                .description(format!("Skipping {}. {}", skipped.description(), phrase))
+                .field("Up Next", next.description(), false)
+                .field(
+                    "Skipped by Accident?",
+                    format!("Use `/undo` in the next {} seconds.", undo_seconds),
+                    false,
+                )
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_skipping_phases(
+    ctx: Context<'_>,
+    skipped: &[PhaseType],
+    next: PhaseType,
+    undo_seconds: i64,
+) {
+    let phrase = ctx.data().pick(phrases::SKIPPING_MANY).await;
+
+    send_tidy_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            embed
+                .description(format!("Skipping {} phases. {}", skipped.len(), phrase))
+                .field(
+                    "Skipped",
+                    skipped
+                        .iter()
+                        .map(|phase_type| format!("- {}", phase_type.description()))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    false,
+                )
+                .field("Up Next", next.description(), false)
+                .field(
+                    "Skipped by Accident?",
+                    format!(
+                        "Use `/undo` in the next {} seconds to go back to the first one.",
+                        undo_seconds
+                    ),
+                    false,
+                )
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_skip_break_working(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Not on a Break").description(
+                "`/skipbreak` only skips breaks, and you're meant to be working right now. If you \
+                 really want to skip this, you know where `/skip` is.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_skip_confirm_first(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply
+            .ephemeral(true)
+            .embed(no_footer(green_embed(avatar_url, |embed| {
+                embed.title("Are You Sure?").description(
+                    "This is the very first phase of the session. Did you even start yet? If you \
+                     really want to skip it, use `/skip confirm:True`.",
+                )
+            })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_skip_failed(ctx: Context<'_>, id: Uuid) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Failed to Skip Phase")
+                .description(formatdoc! { "
+                    It may have completed on its own. Please check if the phase already advanced, and if not, try again.
+
+                    A bug report would be appreciated. Please click on the link in the title of this embed, and quote the session ID below in your report. Thank you!
+                    ",
+                })
+                .field("Session ID", id, false)
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_skip_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(red_embed(avatar_url, |embed| {
+            embed.title("Failed to Skip Phase").description(
+                "I'm not even running a session and you're already trying to get out of work?",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_undone(ctx: Context<'_>, resumed: PhaseType, remaining: Duration) {
+    send_tidy_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            embed.description(format!(
+                "Going back to the {} you skipped, with the {} it had left. Make your mind up \
+                 next time.",
+                resumed.description(),
+                remaining.hhmmss()
+            ))
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_undo_unavailable(ctx: Context<'_>, undo_seconds: i64) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Nothing to Undo").description(format!(
+                "Skips can only be undone in the {} seconds after they happen, and only until the \
+                 next phase changes. You'll have to live with your choices.",
+                undo_seconds
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_undo_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("No Session")
+                .description("There's no session running, so there's nothing to undo.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_session_moved(ctx: Context<'_>, to: ChannelId) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            embed.description(format!(
+                "This session has moved to {}. Follow it, or don't. See if I care.",
+                to.mention()
+            ))
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn say_session_moved_here(ctx: SessionContext<'_>, from: ChannelId) {
+    send_message(ctx, |avatar_url, message| {
+        message.embed(green_embed(avatar_url, |embed| {
+            embed.title("Session Moved").description(format!(
+                "The session from {} has moved here, members and all. Carry on.",
+                from.mention()
+            ))
+        }))
+    })
+    .await;
+}
+
+/// Tell `author` what happened when they used a phrase instead of a command.
+#[instrument(skip(ctx))]
+pub async fn say_alias_outcome(ctx: SessionContext<'_>, author: UserId, outcome: &AliasOutcome) {
+    let author = author.mention();
+
+    let (succeeded, description) = match outcome {
+        AliasOutcome::Joined => (
+            true,
+            format!(
+                "{} joined the session, and will be pinged when the phase changes.",
+                author
+            ),
+        ),
+        AliasOutcome::AlreadyMember => (
+            false,
+            format!("{} is already a member of this session, idiot.", author),
+        ),
+        AliasOutcome::WaitForBreak => (
+            false,
+            format!(
+                "This session only lets people join during breaks, {}. You can join during the \
+                 next one.",
+                author
+            ),
+        ),
+        AliasOutcome::Full(members) => (
+            false,
+            format!(
+                "Sorry {}, this session is full. All {} spots are taken.",
+                author, members
+            ),
+        ),
+        AliasOutcome::Left => (true, format!("{} left the session.", author)),
+        AliasOutcome::NotMember => (
+            false,
+            format!(
+                "{} isn't a member of this session. Nothing to leave.",
+                author
+            ),
+        ),
+        AliasOutcome::Skipping { skipped, next } => (
+            true,
+            format!(
+                "{} skipped the {}. Starting a {} instead.",
+                author,
+                skipped.description(),
+                next.description()
+            ),
+        ),
+        AliasOutcome::ConfirmSkip => (
+            false,
+            format!(
+                "The session's barely started, {}. Use `/skip confirm:True` if you really mean it.",
+                author
+            ),
+        ),
+        AliasOutcome::Stopping => (true, "Stopping session...".to_owned()),
+        AliasOutcome::NoSession => (
+            false,
+            format!(
+                "There's no session running here, {}. Try starting one first.",
+                author
+            ),
+        ),
+        AliasOutcome::Failed(id) => (
+            false,
+            format!(
+                "That didn't work, the session (`{}`) is between phases. Try again in a moment.",
+                id
+            ),
+        ),
+        AliasOutcome::CannotStart(StartBlocked::Running(channel_id))
+            if *channel_id == ctx.channel_id =>
+        {
+            (
+                false,
+                "Session is already running, now GET TO WORK.".to_owned(),
+            )
+        }
+        AliasOutcome::CannotStart(StartBlocked::Running(channel_id)) => (
+            false,
+            format!(
+                "This server only allows one session at a time, and there's already one running \
+                 in {}.",
+                channel_id.mention()
+            ),
+        ),
+        AliasOutcome::CannotStart(StartBlocked::AtCapacity) => (
+            false,
+            "I'm already running as many sessions as I can handle. Try again later.".to_owned(),
+        ),
+        AliasOutcome::CannotStart(StartBlocked::Cooldown(remaining)) => (
+            false,
+            format!(
+                "A session only just ended here. Calm down and try again in {}.",
+                remaining.hhmmss()
+            ),
+        ),
+    };
+
+    let color = if succeeded { GREEN } else { RED };
+
+    send_message(ctx, |avatar_url, message| {
+        message.embed(embed_with_defaults(
+            avatar_url,
+            color,
+            no_footer(|embed| embed.description(description)),
+        ))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_move_not_host(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Not the Host")
+                .description("Only the host of this session can move it. Nice try.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_move_same_channel(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Already Here")
+                .description("The session is already in that channel. Congratulations.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_move_occupied(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Unable to Move Session").description(
+                "There's already a session running in that channel. One at a time, please.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_move_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("No Session")
+                .description("There's no session running here to move anywhere.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_break_now(ctx: Context<'_>, minutes: usize, resuming: Duration) {
+    send_tidy_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            embed.description(format!(
+                "Fine, take a {} minute break. The remaining {} of work will be waiting for you \
+                 when you get back.",
+                minutes,
+                resuming.hhmmss()
+            ))
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_break_now_invalid(ctx: Context<'_>, max_minutes: usize) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Unable to Take a Break").description(format!(
+                "The break has to be between 1 and {} minutes long. Nice try.",
+                max_minutes
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_break_now_not_working(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Unable to Take a Break").description(
+                "You're already on a break. A break from your break? I don't think so.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_break_now_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Unable to Take a Break")
+                .description("A break from what, exactly? There's no session running.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_time_set(ctx: Context<'_>, phase_type: PhaseType, end: DateTime<Utc>) {
+    send_tidy_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            // Discord shows timestamps in each reader's own time zone.
+            embed.description(format!(
+                "Done. This {} now ends at <t:{}:t>, <t:{}:R>.",
+                phase_type.description(),
+                end.timestamp(),
+                end.timestamp()
+            ))
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_set_time_invalid(ctx: Context<'_>, max_minutes: usize) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed.title("Unable to Set Time").description(format!(
+                "The time left has to be between 1 and {} minutes. Nice try.",
+                max_minutes
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_set_time_failed(ctx: Context<'_>, id: Uuid) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Failed to Set Time")
+                .description(formatdoc! { "
+                    The phase may have just finished. Please check if the phase already advanced, and if not, try again.
+
+                    A bug report would be appreciated. Please click on the link in the title of this embed, and quote the session ID below in your report. Thank you!
+                    ",
+                })
+                .field("Session ID", id, false)
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_set_time_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Unable to Set Time")
+                .description("There's no session running, so there's no time to set.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_reset_sequence(ctx: Context<'_>, skipped: PhaseType) {
+    send_tidy_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            embed.description(format!(
+                "Skipping {} and starting again from the top. Don't make me do this again.",
+                skipped.description()
+            ))
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_interval_adjusted(
+    ctx: Context<'_>,
+    config: &SessionConfig,
+    long_at: Option<DateTime<Utc>>,
+) {
+    send_tidy_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            let embed = embed.description(format!(
+                "Long breaks will now come every {} work sessions.",
+                config.interval
+            ));
+
+            if let Some(long_at) = long_at {
+                embed.field(
+                    "Next Long Break",
+                    format!("{} from now", (long_at - Utc::now()).hhmmss()),
+                    false,
+                );
+            }
+
+            embed
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_adjust_interval_invalid(ctx: Context<'_>, error: ConfigError) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Failed to Adjust Interval")
+                .description(format!("Nice try, but {}.", error))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_adjust_interval_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Failed to Adjust Interval")
+                .description("There's no session here to adjust. Maybe start one first?")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_reset_failed(ctx: Context<'_>, id: Uuid) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Failed to Reset Session")
+                .description(formatdoc! { "
+                    The current phase may have completed on its own. Please try again.
+
+                    A bug report would be appreciated. Please click on the link in the title of this embed, and quote the session ID below in your report. Thank you!
+                    ",
+                })
+                .field("Session ID", id, false)
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_reset_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Failed to Reset Session")
+                .description("You can't start over when you haven't even started.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_stopping_session(ctx: Context<'_>) {
+    send_tidy_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            embed.description("Stopping session...")
+        })))
     })
     .await;
 }
@@ -432,14 +2987,322 @@ pub async fn reply_stop_no_session(ctx: Context<'_>) {
     .await;
 }
 
+/// The ID of the button members press to say they're ready to stop.
+pub const READY_TO_STOP_BUTTON: &str = "ready_to_stop";
+/// The ID of the button members press to say they want to keep going.
+pub const NOT_READY_TO_STOP_BUTTON: &str = "not_ready_to_stop";
+
+fn stop_poll_embed(
+    embed: &mut CreateEmbed,
+    tally: Tally,
+    consensus: Consensus,
+    closes: DateTime<Utc>,
+) -> &mut CreateEmbed {
+    let needed = match consensus {
+        Consensus::Everyone => "everyone".to_owned(),
+        Consensus::Majority => format!("{} of you", tally.needed),
+    };
+
+    embed
+        .title("Ready to Stop?")
+        .description(format!(
+            "I'll stop the session if {} agree. Voting closes <t:{}:R>.",
+            needed,
+            closes.timestamp()
+        ))
+        .field("Ready", format!("{}/{}", tally.ayes, tally.voters), true)
+        .field(
+            "Not Ready",
+            format!("{}/{}", tally.noes, tally.voters),
+            true,
+        )
+}
+
+/// Reply with a poll asking the members of the session whether they're ready
+/// to stop, returning the message that was sent if possible.
+#[instrument(skip(ctx))]
+pub async fn reply_stop_poll(
+    ctx: Context<'_>,
+    tally: Tally,
+    consensus: Consensus,
+    closes: DateTime<Utc>,
+) -> Option<Message> {
+    send_reply_and_get_message(ctx, |avatar_url, reply| {
+        reply
+            .embed(green_embed(avatar_url, |embed| {
+                stop_poll_embed(embed, tally, consensus, closes)
+            }))
+            .components(|components| {
+                components.create_action_row(|row| {
+                    row.create_button(|button| {
+                        button
+                            .custom_id(READY_TO_STOP_BUTTON)
+                            .label("Ready")
+                            .style(serenity::ButtonStyle::Success)
+                    })
+                    .create_button(|button| {
+                        button
+                            .custom_id(NOT_READY_TO_STOP_BUTTON)
+                            .label("Not Ready")
+                            .style(serenity::ButtonStyle::Secondary)
+                    })
+                })
+            })
+    })
+    .await
+}
+
+/// Update the poll that `interaction` came from with the new tally.
+#[instrument(skip(ctx, interaction))]
+pub async fn respond_stop_poll_updated(
+    ctx: Context<'_>,
+    interaction: &serenity::MessageComponentInteraction,
+    tally: Tally,
+    consensus: Consensus,
+    closes: DateTime<Utc>,
+) {
+    let avatar_url = get_avatar_url(&ctx.discord().http).await;
+
+    interaction
+        .create_interaction_response(&ctx.discord().http, |response| {
+            response
+                .kind(serenity::InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|data| {
+                    data.embed(green_embed(avatar_url, |embed| {
+                        stop_poll_embed(embed, tally, consensus, closes)
+                    }))
+                })
+        })
+        .await
+        .tap_err(|error| warn!(?error, "unable to update stop poll"))
+        .ok();
+}
+
+/// Tell the user behind `interaction` that they can't vote, because they
+/// aren't a member of the session.
+#[instrument(skip(ctx, interaction))]
+pub async fn respond_stop_poll_not_member(
+    ctx: Context<'_>,
+    interaction: &serenity::MessageComponentInteraction,
+) {
+    let avatar_url = get_avatar_url(&ctx.discord().http).await;
+
+    interaction
+        .create_interaction_response(&ctx.discord().http, |response| {
+            response
+                .kind(serenity::InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|data| {
+                    data.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+                        embed.title("Not a Member").description(
+                            "You aren't in this session, so you don't get a say. Democracy has \
+                             limits.",
+                        )
+                    }))
+                })
+        })
+        .await
+        .tap_err(|error| warn!(?error, "unable to respond to stop poll vote"))
+        .ok();
+}
+
+/// Replace the poll in `message` with how it turned out, and remove its
+/// buttons so nobody can keep voting.
+#[instrument(skip(ctx, message))]
+pub async fn edit_stop_poll_closed(
+    ctx: Context<'_>,
+    message: &mut Message,
+    outcome: Option<Outcome>,
+    tally: Tally,
+) {
+    let avatar_url = get_avatar_url(&ctx.discord().http).await;
+
+    let description = match outcome {
+        Some(Outcome::Passed) => {
+            "Enough of you are done for the day, apparently. Stopping session..."
+        }
+        Some(Outcome::Failed) => {
+            "Not enough of you are ready, so the session continues. Back to it."
+        }
+        None => "Not enough of you could be bothered to vote, so the session continues.",
+    };
+
+    message
+        .edit(&ctx.discord().http, |message| {
+            message
+                .embed(green_embed(avatar_url, |embed| {
+                    embed
+                        .title("Ready to Stop?")
+                        .description(description)
+                        .field("Ready", format!("{}/{}", tally.ayes, tally.voters), true)
+                        .field(
+                            "Not Ready",
+                            format!("{}/{}", tally.noes, tally.voters),
+                            true,
+                        )
+                }))
+                .components(|components| components)
+        })
+        .await
+        .tap_err(|error| warn!(?error, "unable to close stop poll"))
+        .ok();
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_stop_poll_not_member(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Not a Member")
+                .description("You aren't even a member of this session. Just leave if you're done.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_finishing(ctx: Context<'_>, current: Option<PhaseType>) {
+    let description = match current {
+        Some(PhaseType::Work(_)) => {
+            "I'll end the session after this work session. Finish strong.".to_owned()
+        }
+        Some(phase_type) => format!(
+            "I'll end the session after this {}. Don't get too comfortable.",
+            phase_type.description()
+        ),
+        None => "I'll end the session after this phase.".to_owned(),
+    };
+
+    send_tidy_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            embed.description(description)
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_finish_no_session(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("No Session")
+                .description("You can't finish what you haven't started.")
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_unfinished(ctx: Context<'_>) {
+    send_tidy_reply(ctx, |avatar_url, reply| {
+        reply.embed(no_footer(green_embed(avatar_url, |embed| {
+            embed.description("Changed your mind? Fine, the session will carry on as normal.")
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn reply_unfinish_not_finishing(ctx: Context<'_>) {
+    send_reply(ctx, |avatar_url, reply| {
+        reply.ephemeral(true).embed(red_embed(avatar_url, |embed| {
+            embed
+                .title("Not Finishing")
+                .description("This session isn't ending any time soon. Nice try, though.")
+        }))
+    })
+    .await;
+}
+
+/// Count down the last few seconds before `end` in a single message, which is
+/// edited once a second.
+#[instrument(skip(ctx))]
+pub async fn say_countdown(ctx: SessionContext<'_>, end: DateTime<Utc>) {
+    const SECONDS: i64 = 5;
+
+    sleep_until(end - Duration::seconds(SECONDS)).await;
+
+    let mut content = format!("{}...", SECONDS);
+
+    let mut message = match ctx
+        .channel_id
+        .send_message(&ctx.discord.http, |message| message.content(&content))
+        .await
+    {
+        Ok(message) => message,
+        Err(error) => {
+            error!(?error, "unable to send countdown message");
+            return;
+        }
+    };
+
+    for remaining in (1..SECONDS).rev() {
+        sleep_until(end - Duration::seconds(remaining)).await;
+
+        content.push_str(&format!(" {}...", remaining));
+
+        if let Err(error) = message
+            .edit(ctx.discord, |message| message.content(&content))
+            .await
+        {
+            warn!(?error, "unable to edit countdown message");
+            return;
+        }
+    }
+}
+
+/// Sleep until `when`, or not at all if it's already passed.
+async fn sleep_until(when: DateTime<Utc>) {
+    if let Ok(duration) = (when - Utc::now()).to_std() {
+        sleep(duration).await;
+    }
+}
+
+/// Post `summary` in its channel.
+///
+/// This doesn't take a [`SessionContext`], because daily summaries are posted
+/// outside of any session.
+#[cfg(feature = "daily-stats")]
+#[instrument(skip(http))]
+pub async fn say_daily_stats(http: &serenity::Http, summary: &StatsSummary) {
+    let avatar_url = get_avatar_url(http).await;
+
+    summary
+        .channel_id
+        .send_message(http, |message| {
+            message.embed(green_embed(avatar_url, |embed| {
+                let embed = embed
+                    .title("Daily Stats")
+                    .description("Here's how much work got done in here yesterday.")
+                    .field(
+                        "Total Focus Time",
+                        Duration::minutes(summary.total_minutes as i64).hhmmss(),
+                        true,
+                    );
+
+                if let Some((user_id, minutes)) = summary.top_contributor {
+                    embed.field(
+                        "Top Contributor",
+                        format!(
+                            "{} ({})",
+                            user_id.mention(),
+                            Duration::minutes(minutes as i64).hhmmss()
+                        ),
+                        true,
+                    );
+                }
+
+                embed
+            }))
+        })
+        .await
+        .tap_err(|error| error!(?error, "unable to send daily stats"))
+        .ok();
+}
+
 #[instrument(skip(ctx))]
-pub async fn say_session_stopped(ctx: Context<'_>) {
-    let mut rng = &mut *ctx.data().rng.lock().await;
-    let phrase = phrases::STOPPING_SESSION
-        .choose(&mut rng)
-        .expect("the list of phrases is not empty")
-        .deref()
-        .to_owned();
+pub async fn say_session_stopped(ctx: SessionContext<'_>) {
+    let phrase = ctx.data.pick(phrases::STOPPING_SESSION).await;
 
     send_message(ctx, |avatar_url, message| {
         message.embed(green_embed(avatar_url, |embed| {
@@ -450,7 +3313,283 @@ pub async fn say_session_stopped(ctx: Context<'_>) {
 }
 
 #[instrument(skip(ctx))]
-pub async fn say_session_failed(ctx: Context<'_>, id: Uuid) {
+pub async fn say_session_expired(ctx: SessionContext<'_>) {
+    send_message(ctx, |avatar_url, message| {
+        message.embed(green_embed(avatar_url, |embed| {
+            embed.title("Session Expired").description(
+                "This session has been running for far too long, so I've put it out of its \
+                 misery. If any of you are actually still here, start a new one.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn say_session_finished(
+    ctx: SessionContext<'_>,
+    work_phases: usize,
+    focus_minutes: usize,
+) {
+    send_message(ctx, |avatar_url, message| {
+        message.embed(green_embed(avatar_url, |embed| {
+            embed
+                .title("Session Finished")
+                .description("That's a wrap. Go and do something that isn't work.")
+                .field("Work Sessions Completed", work_phases, true)
+                .field("Focus Time", format!("{} minutes", focus_minutes), true)
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn say_session_finished_briefly(ctx: SessionContext<'_>) {
+    send_message(ctx, |avatar_url, message| {
+        message.embed(no_footer(green_embed(avatar_url, |embed| {
+            embed.description("Session finished.")
+        })))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn say_session_force_stopped(ctx: SessionContext<'_>, admin: UserId) {
+    send_message(ctx, |avatar_url, message| {
+        message.embed(red_embed(avatar_url, |embed| {
+            embed.title("Session Shut Down").description(format!(
+                "This session has been stopped by {}. I'd think about what you did, if I were you.",
+                admin.mention()
+            ))
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn say_audit_entry(
+    ctx: SessionContext<'_>,
+    command: &str,
+    admin: UserId,
+    used_in: ChannelId,
+    target: &str,
+) {
+    send_message(ctx, |avatar_url, message| {
+        message.embed(green_embed(avatar_url, |embed| {
+            embed
+                .title("Audit Log")
+                .description(format!("{} used `{}`.", admin.mention(), command))
+                .field("Target", target, false)
+                .field("Used In", used_in.mention(), true)
+                .field("When", format!("<t:{}:F>", Utc::now().timestamp()), true)
+        }))
+    })
+    .await;
+}
+
+/// Add `line` to the guild's session transcript, if it keeps one.
+///
+/// If the line can't be posted because the transcript channel was deleted, or
+/// we aren't allowed to post in it, the transcript is turned off for the guild
+/// and the session's channel is told, so this only happens once. Any other
+/// error (e.g. Discord having a bad moment) just loses the line.
+#[instrument(skip(ctx))]
+pub async fn log_transcript(ctx: SessionContext<'_>, line: &str) {
+    let transcript_channel = match ctx
+        .data
+        .guild_settings(ctx.guild_id)
+        .await
+        .transcript_channel
+    {
+        Some(transcript_channel) => transcript_channel,
+        None => return,
+    };
+
+    let content = format!(
+        "<t:{}:T> {} {}",
+        Utc::now().timestamp(),
+        ctx.channel_id.mention(),
+        line
+    );
+
+    let result = transcript_channel
+        .send_message(&ctx.discord.http, |message| {
+            message
+                .content(content)
+                .allowed_mentions(|mentions| mentions.empty_parse())
+        })
+        .await;
+
+    if let Err(error) = result {
+        let unusable = matches!(
+            &error,
+            serenity::Error::Http(error) if matches!(
+                &**error,
+                serenity::HttpError::UnsuccessfulRequest(response)
+                    if CHANNEL_UNUSABLE_ERROR_CODES.contains(&response.error.code)
+            )
+        );
+
+        if !unusable {
+            warn!(?error, "unable to log to transcript channel");
+            return;
+        }
+
+        warn!(
+            ?error,
+            "unable to log to transcript channel, turning it off"
+        );
+
+        if let Some(guild_id) = ctx.guild_id {
+            let mut guild_settings = ctx.data.guild_settings.lock().await;
+            let settings = guild_settings.entry(guild_id).or_default();
+
+            // It might have been changed to another channel in the meantime, which
+            // could still work.
+            if settings.transcript_channel == Some(transcript_channel) {
+                settings.transcript_channel = None;
+            }
+        }
+
+        say_transcript_disabled(ctx, transcript_channel).await;
+    }
+}
+
+/// Describe `members` for the transcript, without pinging any of them.
+pub fn transcript_members(members: &[UserId]) -> String {
+    if members.is_empty() {
+        "nobody".to_owned()
+    } else {
+        members
+            .iter()
+            .map(|member| member.mention().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[instrument(skip(ctx))]
+pub async fn say_transcript_disabled(ctx: SessionContext<'_>, transcript_channel: ChannelId) {
+    send_message(ctx, |avatar_url, message| {
+        message.embed(red_embed(avatar_url, |embed| {
+            embed.title("Can't Write Transcript").description(format!(
+                "I can't post in {}, so I've stopped logging sessions there. An admin can use \
+                 `/settranscript` to pick a channel I'm actually allowed in.",
+                transcript_channel.mention()
+            ))
+        }))
+    })
+    .await;
+}
+
+/// Get the topic of the session's channel, if it has one.
+#[instrument(skip(ctx))]
+pub async fn get_topic(ctx: SessionContext<'_>) -> Option<String> {
+    match ctx.channel_id.to_channel(&ctx.discord.http).await {
+        Ok(serenity::Channel::Guild(channel)) => channel.topic,
+        Ok(_) => None,
+        Err(error) => {
+            warn!(?error, "unable to fetch channel topic");
+            None
+        }
+    }
+}
+
+/// Set the topic of the session's channel to `topic`, returning whether it
+/// was possible.
+#[instrument(skip(ctx))]
+pub async fn set_topic(ctx: SessionContext<'_>, topic: &str) -> bool {
+    ctx.channel_id
+        .edit(&ctx.discord.http, |channel| channel.topic(topic))
+        .await
+        .tap_err(|error| warn!(?error, "unable to set channel topic"))
+        .is_ok()
+}
+
+/// Pin the message with ID `message_id` in `channel_id`, returning whether it
+/// was possible. If it wasn't, the session's channel is told why.
+#[instrument(skip(ctx))]
+pub async fn pin_message(
+    ctx: SessionContext<'_>,
+    channel_id: ChannelId,
+    message_id: MessageId,
+) -> bool {
+    match channel_id.pin(&ctx.discord.http, message_id).await {
+        Ok(()) => true,
+        Err(error) => {
+            warn!(?error, "unable to pin message");
+
+            let full = matches!(
+                &error,
+                serenity::Error::Http(error) if matches!(
+                    &**error,
+                    serenity::HttpError::UnsuccessfulRequest(response)
+                        if response.error.code == MAX_PINS_ERROR_CODE
+                )
+            );
+
+            say_pin_failed(ctx, full).await;
+            false
+        }
+    }
+}
+
+/// Unpin the message with ID `message_id` in `channel_id`.
+#[instrument(skip(ctx))]
+pub async fn unpin_message(ctx: SessionContext<'_>, channel_id: ChannelId, message_id: MessageId) {
+    // It was pinned successfully, so if it can't be unpinned now someone has most
+    // likely unpinned or deleted it already.
+    channel_id
+        .unpin(&ctx.discord.http, message_id)
+        .await
+        .tap_err(|error| warn!(?error, "unable to unpin message"))
+        .ok();
+}
+
+#[instrument(skip(ctx))]
+async fn say_pin_failed(ctx: SessionContext<'_>, full: bool) {
+    send_message(ctx, |avatar_url, message| {
+        message.embed(red_embed(avatar_url, |embed| {
+            embed.title("Can't Pin").description(if full {
+                "This channel already has as many pins as Discord allows, so the session will just \
+                 have to run unpinned. Maybe have a clear-out."
+            } else {
+                "I'm not allowed to pin messages in this channel, so the session will just have to \
+                 run unpinned. Give me the Manage Messages permission if you want me to do it next \
+                 time."
+            })
+        }))
+    })
+    .await;
+}
+
+/// Describe `phase_type` for the channel topic, given that it ends at `end`.
+pub fn phase_topic(phase_type: PhaseType, end: DateTime<Utc>) -> String {
+    let minutes = (end - Utc::now()).num_seconds().max(0) / 60;
+
+    match phase_type {
+        PhaseType::Work(_) => format!("🍅 Working — {}m left", minutes),
+        PhaseType::Short(_) => format!("☕ Short break — {}m left", minutes),
+        PhaseType::Long(_) => format!("🛋️ Long break — {}m left", minutes),
+    }
+}
+
+#[instrument(skip(ctx))]
+pub async fn say_topic_disabled(ctx: SessionContext<'_>) {
+    send_message(ctx, |avatar_url, message| {
+        message.embed(red_embed(avatar_url, |embed| {
+            embed.title("Can't Change Topic").description(
+                "I'm not allowed to change the topic of this channel, so I'll stop trying for \
+                 this session. Give me the Manage Channel permission if you want me to do it next \
+                 time.",
+            )
+        }))
+    })
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub async fn say_session_failed(ctx: SessionContext<'_>, id: Uuid) {
     send_message(ctx, |avatar_url, message| {
         message.embed(red_embed(avatar_url, |embed| {
             embed
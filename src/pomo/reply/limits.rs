@@ -0,0 +1,213 @@
+//! Keeping embeds within the limits Discord puts on their size, so that an
+//! embed with too much in it is cut down instead of failing to send at all.
+//!
+//! See <https://discord.com/developers/docs/resources/channel#embed-object-embed-limits>.
+
+use poise::serenity_prelude::CreateEmbed;
+use serde_json::{json, Value};
+use tracing::warn;
+
+const MAX_TITLE: usize = 256;
+const MAX_DESCRIPTION: usize = 4096;
+const MAX_FIELDS: usize = 25;
+const MAX_FIELD_NAME: usize = 256;
+const MAX_FIELD_VALUE: usize = 1024;
+const MAX_FOOTER: usize = 2048;
+const MAX_AUTHOR: usize = 256;
+/// The limit on all of the above put together.
+const MAX_TOTAL: usize = 6000;
+
+/// What to end text with when some of it had to be cut off.
+const ELLIPSIS: char = '…';
+
+/// Cut down anything in `embed` that's over Discord's limits, so that it can
+/// still be sent. Returns whether anything had to be changed.
+///
+/// Fields past the limit on how many there can be are replaced with a final
+/// field saying how many were left out. If the embed is still too big overall,
+/// fields are left out from the end, and then the description is shortened.
+pub fn fit_embed(embed: &mut CreateEmbed) -> bool {
+    let map = &mut embed.0;
+    let mut changed = false;
+
+    changed |= truncate_at(map.get_mut("title"), MAX_TITLE);
+    changed |= truncate_at(map.get_mut("description"), MAX_DESCRIPTION);
+    changed |= truncate_at(
+        map.get_mut("footer")
+            .and_then(|footer| footer.get_mut("text")),
+        MAX_FOOTER,
+    );
+    changed |= truncate_at(
+        map.get_mut("author")
+            .and_then(|author| author.get_mut("name")),
+        MAX_AUTHOR,
+    );
+
+    if let Some(Value::Array(fields)) = map.get_mut("fields") {
+        if fields.len() > MAX_FIELDS {
+            let left_out = fields.len() - (MAX_FIELDS - 1);
+            fields.truncate(MAX_FIELDS - 1);
+            fields.push(left_out_field(left_out));
+            changed = true;
+        }
+
+        for field in fields.iter_mut() {
+            changed |= truncate_at(field.get_mut("name"), MAX_FIELD_NAME);
+            changed |= truncate_at(field.get_mut("value"), MAX_FIELD_VALUE);
+        }
+    }
+
+    let mut excess = total_length(embed).saturating_sub(MAX_TOTAL);
+    let map = &mut embed.0;
+
+    if excess > 0 {
+        changed = true;
+
+        if let Some(Value::Array(fields)) = map.get_mut("fields") {
+            while excess > 0 {
+                match fields.pop() {
+                    Some(field) => excess = excess.saturating_sub(field_length(&field)),
+                    None => break,
+                }
+            }
+        }
+    }
+
+    if excess > 0 {
+        if let Some(length) = map.get("description").map(text_length) {
+            truncate_at(
+                map.get_mut("description"),
+                length.saturating_sub(excess).max(1),
+            );
+        }
+    }
+
+    if changed {
+        warn!("embed was over Discord's limits, so it was cut down");
+    }
+
+    changed
+}
+
+/// A field saying that `count` fields had to be left out.
+fn left_out_field(count: usize) -> Value {
+    json!({
+        "name": ELLIPSIS.to_string(),
+        "value": format!("…and {} more, which didn't fit", count),
+        "inline": false,
+    })
+}
+
+/// Shorten the string in `value` to at most `max` characters, ending it with an
+/// ellipsis if anything had to be cut off. Returns whether it was shortened.
+fn truncate_at(value: Option<&mut Value>, max: usize) -> bool {
+    match value {
+        Some(Value::String(text)) if text.chars().count() > max => {
+            *text = text
+                .chars()
+                .take(max - 1)
+                .chain(std::iter::once(ELLIPSIS))
+                .collect();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// The length of everything in `embed` that counts towards [`MAX_TOTAL`].
+fn total_length(embed: &CreateEmbed) -> usize {
+    let map = &embed.0;
+
+    let text = |value: Option<&Value>| value.map_or(0, text_length);
+
+    text(map.get("title"))
+        + text(map.get("description"))
+        + text(map.get("footer").and_then(|footer| footer.get("text")))
+        + text(map.get("author").and_then(|author| author.get("name")))
+        + match map.get("fields") {
+            Some(Value::Array(fields)) => fields.iter().map(field_length).sum(),
+            _ => 0,
+        }
+}
+
+fn field_length(field: &Value) -> usize {
+    field.get("name").map_or(0, text_length) + field.get("value").map_or(0, text_length)
+}
+
+fn text_length(value: &Value) -> usize {
+    value.as_str().map_or(0, |text| text.chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(embed: &CreateEmbed) -> &Vec<Value> {
+        match embed.0.get("fields") {
+            Some(Value::Array(fields)) => fields,
+            _ => panic!("embed has no fields"),
+        }
+    }
+
+    #[test]
+    fn small_embed_is_unchanged() {
+        let mut embed = CreateEmbed::default();
+        embed
+            .title("Status")
+            .description("All good")
+            .field("Work", "25 minutes", true);
+        let before = embed.0.clone();
+
+        assert!(!fit_embed(&mut embed));
+        assert_eq!(embed.0, before);
+    }
+
+    #[test]
+    fn long_text_is_truncated() {
+        let mut embed = CreateEmbed::default();
+        embed
+            .title("t".repeat(300))
+            .description("d".repeat(5000))
+            .field("n", "v".repeat(2000), false);
+
+        assert!(fit_embed(&mut embed));
+
+        assert_eq!(text_length(&embed.0["title"]), MAX_TITLE);
+        assert!(embed.0["title"].as_str().unwrap().ends_with(ELLIPSIS));
+        assert!(total_length(&embed) <= MAX_TOTAL);
+        assert_eq!(text_length(&fields(&embed)[0]["value"]), MAX_FIELD_VALUE);
+    }
+
+    #[test]
+    fn extra_fields_are_left_out() {
+        let mut embed = CreateEmbed::default();
+        for i in 0..30 {
+            embed.field(i, "value", true);
+        }
+
+        assert!(fit_embed(&mut embed));
+
+        let fields = fields(&embed);
+        assert_eq!(fields.len(), MAX_FIELDS);
+        assert_eq!(fields[MAX_FIELDS - 2]["name"], "23");
+        assert_eq!(
+            fields[MAX_FIELDS - 1]["value"],
+            "…and 6 more, which didn't fit"
+        );
+    }
+
+    #[test]
+    fn fields_are_dropped_to_fit_total() {
+        let mut embed = CreateEmbed::default();
+        embed.description("d".repeat(MAX_DESCRIPTION));
+        for i in 0..5 {
+            embed.field(i, "v".repeat(MAX_FIELD_VALUE), false);
+        }
+
+        assert!(fit_embed(&mut embed));
+
+        assert!(total_length(&embed) <= MAX_TOTAL);
+        assert_eq!(text_length(&embed.0["description"]), MAX_DESCRIPTION);
+        assert_eq!(fields(&embed).len(), 1);
+    }
+}
@@ -1,50 +1,91 @@
-pub const STARTING_SESSION: &[&str] = &[
-    "OK you miserable lot, get to it!",
-    "I don't like you, but I'll still start the session...",
-    "Wow, what fucking time do you call this?",
-    "Doing a 4 hour work day today, are we?",
-    "You got this! Is that enough praise for you?",
+/// What to say when there aren't any phrases to pick from.
+pub const FALLBACK: &str = "I've got nothing to say to you.";
+
+/// Weighted so that the last one only turns up once in a while.
+pub static STARTING_SESSION: &[(&str, u32)] = &[
+    ("OK you miserable lot, get to it!", 10),
+    ("I don't like you, but I'll still start the session...", 10),
+    ("Wow, what fucking time do you call this?", 10),
+    ("Doing a 4 hour work day today, are we?", 10),
+    ("You got this! Is that enough praise for you?", 10),
+    (
+        "I was going to give you a pep talk, but honestly? I believe in you. Don't tell anyone I \
+         said that.",
+        1,
+    ),
+];
+
+pub static ACCOUNTABILITY_CLOSING: &[&str] = &[
+    "Same time tomorrow? Don't answer that, I already know.",
+    "I've seen better. I've also seen worse, but not by much.",
+    "Your mother would be proud. Probably. I've never met her.",
+    "Not bad, for a human.",
 ];
 
-pub const STARTING_SHORT_BREAK: &[&str] = &[
+pub static STARTING_SHORT_BREAK: &[&str] = &[
     "I'm legally required to give you this break.",
     "Unfortunately, you still have rights.",
     "I bet you weren't even working...",
     "Congratulations on doing the bare minimum!",
 ];
 
-pub const STARTING_LONG_BREAK: &[&str] = &[
+pub static STARTING_LONG_BREAK: &[&str] = &[
     "God I cannot believe you're still dragging this out.",
     "I'm amazed you've lasted this long, kid.",
     "You really won't give up, huh?",
     "Don't get too carried away, there's another work session after this...",
 ];
 
-pub const STARTING_WORK: &[&str] = &[
+pub static STARTING_WORK: &[&str] = &[
     "Back into the pit!",
     "Back to work, maggots!",
     "Put some elbow grease into it!",
     "Slackers will be sent to bed without their dinner!",
 ];
 
-pub const SKIPPING_WORK: &[&str] = &[
+pub static SKIPPING_WORK: &[&str] = &[
     "Fucking unbelievable...",
     "You won't get far in life with an attitude like that!",
     "I knew I was right about you...",
     "About as hard-working as you look!",
 ];
 
-pub const SKIPPING_BREAK: &[&str] = &[
+pub static SKIPPING_FIRST_PHASE: &[&str] = &[
+    "Skipping the very first phase. Off to a flying start, then.",
+    "You haven't even started yet, and you're already skipping things.",
+    "Bold move, skipping before you've done anything at all.",
+];
+
+pub static SKIPPING_MANY: &[&str] = &[
+    "Why do one thing badly when you can do several?",
+    "At this rate you'll be done before you've started.",
+    "Time flies when you're not doing anything.",
+];
+
+pub static SKIPPING_BREAK: &[&str] = &[
     "Haven't seen you this motivated since lasagne night!",
     "Maybe there's hope for you yet!",
     "Maybe you do have potential...",
     "Smart move!",
 ];
 
-pub const STOPPING_SESSION: &[&str] = &[
+pub static STOPPING_SESSION: &[&str] = &[
     "*czzt* Perps have been handled, Pomocop out *czzt*",
     "Done enough for today, have we?",
     "Another successful day browsing Reddit?",
     "Putting that degree to good use, then?",
     "All I'm saying is, *I* could have worked for at least 2 more hours...",
 ];
+
+/// Genuinely useful advice, occasionally shown when a work phase starts. Keep
+/// these sincere; the snark goes in the other lists.
+pub static WORK_TIPS: &[&str] = &[
+    "Pick one task for this session and write it down before you start.",
+    "Put your phone in another room, or at least face down and out of reach.",
+    "If a task feels too big to start, find the smallest next step and do that.",
+    "Close the tabs you don't need for what you're working on right now.",
+    "If something distracting comes to mind, note it down and deal with it on your break.",
+    "Have some water nearby, so you don't need to get up in the middle of the session.",
+    "Don't worry about getting it perfect the first time; you can improve it later.",
+    "Take your breaks away from the screen if you can.",
+];
@@ -1,7 +1,8 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet, VecDeque},
     fmt,
     future::Future,
+    ops::RangeInclusive,
     pin::Pin,
     sync::{Arc, Mutex},
     task::{Context, Poll, Waker},
@@ -10,32 +11,123 @@ use std::{
 
 use chrono::{DateTime, Duration, Utc};
 use poise::serenity_prelude as serenity;
-use serenity::UserId;
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+use serenity::{ChannelId, MessageId, UserId};
 use tap::TapFallible;
 use thiserror::Error;
 use tokio::sync::oneshot::{channel as oneshot_channel, error::TryRecvError, Receiver, Sender};
 use tracing::{debug, instrument, trace, warn};
 use uuid::Uuid;
 
+use crate::pomo::clock::{Clock, SystemClock};
+
+/// The range of lengths, in minutes, that work phases are picked from in
+/// roulette mode.
+pub const ROULETTE_MINUTES: RangeInclusive<usize> = 10..=50;
+
 /// An active pomocop session.
 #[derive(Debug)]
 pub struct Session {
     id: Uuid,
+    host: UserId,
     members: HashSet<UserId>,
+    max_members: Option<usize>,
+    /// Whether members can only join during breaks, so that nobody is
+    /// disturbed by people arriving in the middle of work.
+    join_on_breaks: bool,
     config: SessionConfig,
     current_phase: Option<PhaseHandle>,
     next_index: usize,
+    /// How far through a cycle of work phases and breaks (ending with a long
+    /// break) the sequence was at index 0, so that adjusting the interval can
+    /// keep progress through the current cycle without moving `next_index`.
+    /// Always even, so that work phases stay at even indices.
+    cycle_offset: usize,
+    /// Phases to run before continuing with the normal sequence, along with
+    /// how long each should last.
+    interjections: VecDeque<(PhaseType, Duration)>,
+    /// The message that was sent when the session started, if it's known.
+    start_message: Option<(ChannelId, MessageId)>,
+    /// Whether to count down the last few seconds of each work phase.
+    countdown: bool,
+    /// Whether to announce phase changes without making members' devices
+    /// notify them.
+    silent_pings: bool,
+    /// Whether to end the session when the current phase finishes, instead of
+    /// starting the next one.
+    finishing: bool,
+    /// Whether to show the current phase in the channel topic.
+    topic: bool,
+    /// Whether the start message is pinned for as long as the session runs.
+    pin: bool,
+    /// Where the session gets the current time from.
+    clock: Arc<dyn Clock>,
+    /// When the current phase was last polled, which happens regularly for as
+    /// long as the task running the session is alive.
+    heartbeat: Arc<Mutex<DateTime<Utc>>>,
+    /// How many phases have been started so far, including interjected ones.
+    phases_started: usize,
+    /// Members who shouldn't be mentioned when phases start, along with the
+    /// number of the last phase they shouldn't be mentioned for.
+    silenced: HashMap<UserId, usize>,
+    /// The longest this session can run for, if it should be different to the
+    /// bot's default.
+    max_length: Option<Duration>,
+    /// When this session should be stopped, regardless of its phases.
+    deadline: Option<DateTime<Utc>>,
+    /// The most recently skipped phase, so that the skip can be undone.
+    last_skip: Option<SkippedPhase>,
+    /// How to announce the session finishing, if it isn't stopped.
+    finish_announcement: FinishAnnouncement,
+    /// Whether skipped work phases count towards the next long break.
+    skipped_work: SkippedWork,
+    /// When the first phase of this session started, or when the session was
+    /// created if it hasn't started yet.
+    started: DateTime<Utc>,
+    /// How long was spent in phases that have already ended.
+    time_spent: TimeSpent,
+    /// Where to get the lengths of work phases from in roulette mode, or
+    /// `None` if they're the length in the config as usual.
+    roulette: Option<StdRng>,
+    /// The length the roulette picked for the current phase, if it picked one.
+    rolled: Option<usize>,
 }
 
 impl Session {
-    /// Create a session from the given [`SessionConfig`], without starting it.
-    fn from_config(config: SessionConfig) -> Self {
+    /// Create a session from the given [`SessionConfig`], hosted by `host`,
+    /// without starting it. The host is added as the session's first member.
+    fn from_config(config: SessionConfig, host: UserId) -> Self {
         Self {
             id: Uuid::new_v4(),
-            members: HashSet::new(),
+            host,
+            members: HashSet::from([host]),
+            max_members: None,
+            join_on_breaks: false,
             config,
             current_phase: None,
             next_index: 0,
+            cycle_offset: 0,
+            interjections: VecDeque::new(),
+            start_message: None,
+            countdown: false,
+            silent_pings: false,
+            finishing: false,
+            topic: false,
+            pin: false,
+            clock: Arc::new(SystemClock),
+            heartbeat: Arc::new(Mutex::new(Utc::now())),
+            phases_started: 0,
+            silenced: HashMap::new(),
+            max_length: None,
+            deadline: None,
+            last_skip: None,
+            finish_announcement: FinishAnnouncement::default(),
+            skipped_work: SkippedWork::default(),
+            started: Utc::now(),
+            time_spent: TimeSpent::default(),
+            roulette: None,
+            rolled: None,
         }
     }
 
@@ -49,6 +141,234 @@ impl Session {
         &self.config
     }
 
+    /// Get when the first phase of this session started, or when the session
+    /// was created if it hasn't started yet.
+    pub fn started(&self) -> DateTime<Utc> {
+        self.started
+    }
+
+    /// Get the user who started this session.
+    pub fn host(&self) -> UserId {
+        self.host
+    }
+
+    /// Get the maximum number of members this session can have, if there is
+    /// one.
+    pub fn max_members(&self) -> Option<usize> {
+        self.max_members
+    }
+
+    /// Set the maximum number of members this session can have, or `None` for
+    /// no limit.
+    pub fn set_max_members(&mut self, max_members: Option<usize>) {
+        self.max_members = max_members;
+    }
+
+    /// Returns whether there is room in this session for `user` to join.
+    ///
+    /// The host can always join, regardless of how many members there are.
+    pub fn has_room_for(&self, user: UserId) -> bool {
+        match self.max_members {
+            Some(max) if user != self.host => self.members.len() < max,
+            _ => true,
+        }
+    }
+
+    /// Set whether members can only join during breaks.
+    pub fn set_join_on_breaks(&mut self, join_on_breaks: bool) {
+        self.join_on_breaks = join_on_breaks;
+    }
+
+    /// Returns whether `user` can join this session right now, which is any
+    /// time unless it only lets members join during breaks.
+    ///
+    /// The host can always join, even in the middle of work.
+    pub fn can_join_now(&self, user: UserId) -> bool {
+        let working = matches!(
+            self.status(),
+            SessionStatus::Running {
+                phase_type: PhaseType::Work(_),
+                ..
+            }
+        );
+
+        !(self.join_on_breaks && working) || user == self.host
+    }
+
+    /// Get the channel and ID of the message that was sent when this session
+    /// started, if it's known.
+    pub fn start_message(&self) -> Option<(ChannelId, MessageId)> {
+        self.start_message
+    }
+
+    /// Record the message that was sent when this session started.
+    pub fn set_start_message(&mut self, message: Option<(ChannelId, MessageId)>) {
+        self.start_message = message;
+    }
+
+    /// Returns whether the last few seconds of each work phase should be
+    /// counted down.
+    pub fn countdown(&self) -> bool {
+        self.countdown
+    }
+
+    /// Set whether the last few seconds of each work phase should be counted
+    /// down.
+    pub fn set_countdown(&mut self, countdown: bool) {
+        self.countdown = countdown;
+    }
+
+    /// Returns whether phase changes should be announced without making
+    /// members' devices notify them.
+    pub fn silent_pings(&self) -> bool {
+        self.silent_pings
+    }
+
+    /// Set whether phase changes should be announced without making members'
+    /// devices notify them.
+    pub fn set_silent_pings(&mut self, silent_pings: bool) {
+        self.silent_pings = silent_pings;
+    }
+
+    /// Set the clock that this session gets the current time from.
+    ///
+    /// This should be done before the first phase is started, because phases
+    /// keep using the clock they were started with.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Get the clock that this session gets the current time from.
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Get the longest this session can run for, if it should be different to
+    /// the bot's default.
+    pub fn max_length(&self) -> Option<Duration> {
+        self.max_length
+    }
+
+    /// Set the longest this session can run for, or `None` to use the bot's
+    /// default.
+    pub fn set_max_length(&mut self, max_length: Option<Duration>) {
+        self.max_length = max_length;
+    }
+
+    /// Set the deadline of this session to its maximum length from now, or
+    /// `default_max_length` from now if it doesn't have one, and return it.
+    ///
+    /// A maximum length too long to represent as a date means the session
+    /// effectively has no deadline, so the latest possible date is used.
+    pub fn start_deadline(&mut self, default_max_length: Duration) -> DateTime<Utc> {
+        let deadline = self
+            .clock
+            .now()
+            .checked_add_signed(self.max_length.unwrap_or(default_max_length))
+            .unwrap_or(chrono::MAX_DATETIME);
+        self.deadline = Some(deadline);
+        deadline
+    }
+
+    /// Get when this session should be stopped regardless of its phases, if
+    /// its deadline has been started.
+    pub fn deadline(&self) -> Option<DateTime<Utc>> {
+        self.deadline
+    }
+
+    /// Returns whether this session has run past its deadline.
+    pub fn is_expired(&self) -> bool {
+        self.deadline
+            .map_or(false, |deadline| self.clock.now() >= deadline)
+    }
+
+    /// Returns whether the task running this session seems to have stopped,
+    /// because its phases haven't been polled for longer than `threshold`.
+    ///
+    /// Phases are polled several times a second while they're running, and
+    /// only stop being polled briefly while the next phase is being announced,
+    /// so `threshold` should comfortably cover how long an announcement could
+    /// take. This uses the system clock rather than the session's clock,
+    /// because it's about the task rather than the session.
+    pub fn is_stalled(&self, threshold: Duration) -> bool {
+        Utc::now() - *self.heartbeat.lock().unwrap() > threshold
+    }
+
+    /// Returns whether the current phase should be shown in the channel topic.
+    pub fn topic(&self) -> bool {
+        self.topic
+    }
+
+    /// Set whether the current phase should be shown in the channel topic.
+    pub fn set_topic(&mut self, topic: bool) {
+        self.topic = topic;
+    }
+
+    /// Returns whether this session is in roulette mode, with a random length
+    /// for each work phase.
+    pub fn roulette(&self) -> bool {
+        self.roulette.is_some()
+    }
+
+    /// Put this session in roulette mode, picking the length of each work
+    /// phase from [`ROULETTE_MINUTES`] with `rng`, or take it out of roulette
+    /// mode with `None`.
+    pub fn set_roulette(&mut self, rng: Option<StdRng>) {
+        self.roulette = rng;
+    }
+
+    /// Get the length the roulette picked for the current phase, or `None` if
+    /// it didn't pick one (because the session isn't in roulette mode, the
+    /// phase isn't a work phase, or the phase is resuming an earlier one).
+    pub fn rolled(&self) -> Option<usize> {
+        self.rolled
+    }
+
+    /// Returns whether the start message is pinned while the session runs, and
+    /// so needs to be unpinned when it ends.
+    pub fn pin(&self) -> bool {
+        self.pin
+    }
+
+    /// Set whether the start message is pinned while the session runs.
+    pub fn set_pin(&mut self, pin: bool) {
+        self.pin = pin;
+    }
+
+    /// Returns whether this session will end when the current phase finishes.
+    pub fn finishing(&self) -> bool {
+        self.finishing
+    }
+
+    /// Set whether this session should end when the current phase finishes.
+    pub fn set_finishing(&mut self, finishing: bool) {
+        self.finishing = finishing;
+    }
+
+    /// Get how this session should announce that it has finished, if it isn't
+    /// stopped.
+    pub fn finish_announcement(&self) -> FinishAnnouncement {
+        self.finish_announcement
+    }
+
+    /// Set how this session should announce that it has finished, if it isn't
+    /// stopped.
+    pub fn set_finish_announcement(&mut self, finish_announcement: FinishAnnouncement) {
+        self.finish_announcement = finish_announcement;
+    }
+
+    /// Get whether work phases skipped in this session count towards the next
+    /// long break.
+    pub fn skipped_work(&self) -> SkippedWork {
+        self.skipped_work
+    }
+
+    /// Set whether work phases skipped in this session count towards the next
+    /// long break.
+    pub fn set_skipped_work(&mut self, skipped_work: SkippedWork) {
+        self.skipped_work = skipped_work;
+    }
+
     /// Add a user to the set of members of this session.
     ///
     /// Returns whether the user was added (i.e. `true` if the user was not
@@ -57,21 +377,79 @@ impl Session {
         self.members.insert(user)
     }
 
+    /// Add each of `users` that has room and hasn't opted out (according to
+    /// `opted_out`) to the members of this session, on behalf of the host.
+    pub fn enroll<I, F>(&mut self, users: I, opted_out: F) -> Enrollment
+    where
+        I: IntoIterator<Item = UserId>,
+        F: Fn(UserId) -> bool,
+    {
+        let mut enrollment = Enrollment::default();
+
+        for user in users {
+            if self.is_member(user) {
+                enrollment.already_members += 1;
+            } else if opted_out(user) {
+                enrollment.opted_out += 1;
+            } else if !self.has_room_for(user) {
+                enrollment.no_room += 1;
+            } else {
+                self.add_member(user);
+                enrollment.added += 1;
+            }
+        }
+
+        enrollment
+    }
+
     /// Remove a user from the set of members of this session.
     ///
     /// Returns whether the user was a member.
     pub fn remove_member(&mut self, user: UserId) -> bool {
+        self.silenced.remove(&user);
         self.members.remove(&user)
     }
 
+    /// Returns whether `user` is a member of this session.
+    pub fn is_member(&self, user: UserId) -> bool {
+        self.members.contains(&user)
+    }
+
     /// Get the set of members of this session
     pub fn members(&self) -> &HashSet<UserId> {
         &self.members
     }
 
+    /// Stop mentioning `user` when each of the next `phases` phases starts,
+    /// after which they'll be mentioned again as normal.
+    pub fn silence(&mut self, user: UserId, phases: usize) {
+        self.silenced.insert(user, self.phases_started + phases);
+    }
+
+    /// Get the members of this session who should be mentioned when the
+    /// current phase starts, i.e. those who haven't silenced it.
+    pub fn mentioned_members(&self) -> Vec<UserId> {
+        self.members
+            .iter()
+            .copied()
+            .filter(|user| {
+                self.silenced
+                    .get(user)
+                    .map_or(true, |&until| self.phases_started > until)
+            })
+            .collect()
+    }
+
     /// Unconditionally advance to the next phase and return it, regardless of
     /// whether there is a running phase already.
     ///
+    /// If any phases have been interjected (by [`Session::break_now()`]), the
+    /// first of those is started instead, without moving on in the normal
+    /// sequence.
+    ///
+    /// In roulette mode, work phases from the normal sequence get a random
+    /// length instead of the one in the config.
+    ///
     /// In the process, this will drop the stored [`PhaseHandle`], making it
     /// impossible to skip or stop a running phase. If there is a possibility
     /// that a phase is still running, [`Session::skip()`] or
@@ -79,17 +457,45 @@ impl Session {
     #[instrument]
     pub fn advance(&mut self) -> Phase {
         let (send, recv) = oneshot_channel();
+        self.end_phase();
+
+        self.rolled = None;
+
+        let (phase_type, length) = match self.interjections.pop_front() {
+            Some(interjection) => interjection,
+            None => {
+                let phase_type = match (self.phase_at(self.next_index), &mut self.roulette) {
+                    (PhaseType::Work(_), Some(rng)) => {
+                        let minutes = rng.gen_range(ROULETTE_MINUTES);
+                        self.rolled = Some(minutes);
+                        PhaseType::Work(minutes)
+                    }
+                    (phase_type, _) => phase_type,
+                };
+                self.next_index += 1;
+
+                (phase_type, Duration::minutes(phase_type.length() as i64))
+            }
+        };
 
-        let phase_type = self.config.phase_at(self.next_index);
-        self.next_index += 1;
+        self.phases_started += 1;
+        let phases_started = self.phases_started;
+        self.silenced.retain(|_, until| *until >= phases_started);
 
-        let start = Utc::now();
-        let end = start + Duration::minutes(phase_type.length() as i64);
+        let start = self.clock.now();
+        let end = Arc::new(Mutex::new(start + length));
+
+        if phases_started == 1 {
+            self.started = start;
+        }
 
         self.current_phase = Some(PhaseHandle {
             started: start,
+            end: end.clone(),
+            planned: Duration::minutes(phase_type.length() as i64),
             phase_type,
             send,
+            clock: self.clock.clone(),
         });
 
         Phase {
@@ -98,9 +504,19 @@ impl Session {
             phase_type,
             recv,
             waker: None,
+            clock: self.clock.clone(),
+            heartbeat: self.heartbeat.clone(),
         }
     }
 
+    /// Take the currently running phase, if there is one, adding the time spent
+    /// in it to the session's total.
+    fn end_phase(&mut self) -> Option<PhaseHandle> {
+        let phase = self.current_phase.take()?;
+        self.time_spent.add(phase.phase_type, phase.time_spent());
+        Some(phase)
+    }
+
     /// Skip the currently running phase.
     ///
     /// Returns [`SessionError::NotActive`] if there is no currently running
@@ -109,10 +525,12 @@ impl Session {
     /// running phase, returns its type.
     #[instrument]
     pub fn skip(&mut self) -> Result<PhaseType, SessionError> {
-        if let Some(phase) = self.current_phase.take() {
+        if let Some(phase) = self.end_phase() {
             phase
                 .send
-                .send(PhaseMessage::Skip)
+                .send(PhaseMessage::Skip {
+                    elapsed: phase.elapsed(),
+                })
                 .tap_err(|_| warn!("unable to skip phase; did it complete on its own?"))
                 .ok();
 
@@ -122,6 +540,122 @@ impl Session {
         }
     }
 
+    /// Skip the currently running phase, returning both its type and the type
+    /// of the phase that will run after it.
+    ///
+    /// Both are read under the same borrow of the session, so they're always
+    /// consistent with each other. Returns [`SessionError::NotActive`] under
+    /// the same conditions as [`Session::skip`].
+    ///
+    /// If skipped work phases don't count in this session (see
+    /// [`SkippedWork`]), a skipped work phase is followed by a short break, and
+    /// then its place in the sequence is taken again by the next work phase.
+    ///
+    /// The skip can be undone with [`Session::undo_skip`].
+    #[instrument]
+    pub fn skip_and_report_next(&mut self) -> Result<(PhaseType, PhaseType), SessionError> {
+        let remaining = self
+            .current_phase
+            .as_ref()
+            .map_or_else(Duration::zero, PhaseHandle::remaining);
+        let skipped = self.skip()?;
+
+        self.last_skip = Some(SkippedPhase {
+            phase_type: skipped,
+            remaining,
+            next_index: self.next_index,
+            interjections: self.interjections.clone(),
+            phases_started: self.phases_started,
+            at: self.clock.now(),
+        });
+
+        if let (PhaseType::Work(_), SkippedWork::NotCounted) = (skipped, self.skipped_work) {
+            self.uncount_skipped_work();
+        }
+
+        Ok((skipped, self.next_phase_type()))
+    }
+
+    /// Skip the currently running phase along with the `count - 1` phases after
+    /// it, returning the types of every skipped phase and the type of the
+    /// phase that will run after them.
+    ///
+    /// The phases after the running one are passed over without ever starting,
+    /// so nothing is announced for them. The [`SkippedWork`] policy only
+    /// applies to the running phase, since skipping work phases that don't
+    /// count would otherwise never get any further. Undoing the skip with
+    /// [`Session::undo_skip`] resumes the running phase, and the sequence
+    /// carries on from just after it.
+    #[instrument]
+    pub fn skip_many(&mut self, count: usize) -> Result<(Vec<PhaseType>, PhaseType), SessionError> {
+        let (first, _) = self.skip_and_report_next()?;
+        let mut skipped = vec![first];
+
+        for _ in 1..count {
+            let phase_type = match self.interjections.pop_front() {
+                Some((phase_type, _)) => phase_type,
+                None => {
+                    let phase_type = self.phase_at(self.next_index);
+                    self.next_index += 1;
+                    phase_type
+                }
+            };
+
+            skipped.push(phase_type);
+        }
+
+        Ok((skipped, self.next_phase_type()))
+    }
+
+    /// Take back the place in the sequence of the work phase that was just
+    /// skipped, replacing the break that would have followed it with a short
+    /// break.
+    fn uncount_skipped_work(&mut self) {
+        // Interjected phases (e.g. after `/breaknow`) come before the sequence
+        // carries on, so they need to run before the place is taken again.
+        let short = PhaseType::Short(self.config.short);
+
+        match self.next_index.checked_sub(1) {
+            Some(index) if matches!(self.phase_at(index), PhaseType::Work(_)) => {
+                self.next_index = index;
+                self.interjections
+                    .push_back((short, Duration::minutes(short.length() as i64)));
+            }
+            _ => {}
+        }
+    }
+
+    /// Undo the most recent skip, by skipping the phase that replaced the
+    /// skipped phase and resuming the skipped phase with the time it had left.
+    /// The sequence then carries on from where it was before the skip.
+    ///
+    /// A skip can only be undone within `window` of it happening, and only
+    /// while the phase that replaced the skipped phase is running. Returns the
+    /// type of the resumed phase and how much of it is left, or `None` if
+    /// there's no skip that can be undone.
+    #[instrument]
+    pub fn undo_skip(&mut self, window: Duration) -> Option<(PhaseType, Duration)> {
+        let now = self.clock.now();
+        let undoable = self.current_phase.is_some()
+            && self.last_skip.as_ref().map_or(false, |skipped| {
+                self.phases_started == skipped.phases_started + 1 && now - skipped.at <= window
+            });
+
+        if !undoable {
+            return None;
+        }
+
+        let skipped = self.last_skip.take()?;
+        self.skip().ok()?;
+
+        self.next_index = skipped.next_index;
+        self.interjections = skipped.interjections;
+        self.interjections
+            .push_front((skipped.phase_type, skipped.remaining));
+
+        Some((skipped.phase_type, skipped.remaining))
+    }
+
     /// Stop the session by stopping the currently running phase.
     ///
     /// Returns [`SessionError::NotActive`] if there is no currently running
@@ -129,10 +663,12 @@ impl Session {
     /// means that the phase finished on its own).
     #[instrument]
     pub fn stop(&mut self) -> Result<(), SessionError> {
-        if let Some(phase) = self.current_phase.take() {
+        if let Some(phase) = self.end_phase() {
             phase
                 .send
-                .send(PhaseMessage::Stop)
+                .send(PhaseMessage::Stop {
+                    elapsed: phase.elapsed(),
+                })
                 .tap_err(|_| warn!("unable to stop phase; did it complete on its own?"))
                 .map_err(|_| SessionError::NotActive)
         } else {
@@ -140,97 +676,670 @@ impl Session {
         }
     }
 
+    /// Reset the phase sequence back to the start, by skipping the currently
+    /// running phase and arranging for the next phase to be the first one (a
+    /// work phase). Members and config are left untouched.
+    ///
+    /// Returns [`SessionError::NotActive`] if there is no currently running
+    /// phase. If there was a currently running phase, returns its type.
+    #[instrument]
+    pub fn reset_sequence(&mut self) -> Result<PhaseType, SessionError> {
+        let skipped = self.skip()?;
+        self.next_index = 0;
+        self.cycle_offset = 0;
+        self.interjections.clear();
+
+        Ok(skipped)
+    }
+
+    /// Change how many work phases there are between each long break, from now
+    /// on.
+    ///
+    /// Progress through the current cycle is kept: the next long break comes
+    /// once `interval` work phases have started since the last one (or since
+    /// the session started). If at least that many already have, the next
+    /// break is a long break. The place in the sequence itself doesn't change,
+    /// so phase numbers and alternating work lengths carry on as before.
+    /// Returns [`ConfigError::ZeroInterval`] if `interval` is 0.
+    #[instrument]
+    pub fn adjust_interval(&mut self, interval: usize) -> Result<(), ConfigError> {
+        if interval == 0 {
+            return Err(ConfigError::ZeroInterval);
+        }
+
+        // The structure of a cycle (alternating work and short breaks, ending with
+        // a long break) doesn't depend on its length. So the position within the
+        // current cycle carries over as-is, unless it's past the end of the new
+        // cycle, in which case it's pulled back to the latest position with the
+        // same kind of phase next.
+        let position = (self.next_index + self.cycle_offset) % (self.config.interval * 2);
+        let latest = if position % 2 == 0 {
+            interval * 2 - 2
+        } else {
+            interval * 2 - 1
+        };
+
+        // Both have the same parity as `next_index`, and the cycle length is even,
+        // so the offset comes out even too.
+        let cycle = interval * 2;
+        self.cycle_offset = (position.min(latest) + cycle - self.next_index % cycle) % cycle;
+        self.config.interval = interval;
+
+        Ok(())
+    }
+
+    /// Interrupt the currently running phase with a break lasting `minutes`,
+    /// after which the rest of the interrupted phase will resume.
+    ///
+    /// The break doesn't count as part of the normal sequence, so it doesn't
+    /// affect when the next long break happens. Returns
+    /// [`SessionError::NotActive`] if there is no currently running phase.
+    /// Otherwise, returns how much of the interrupted phase is left to resume.
+    #[instrument]
+    pub fn break_now(&mut self, minutes: usize) -> Result<Duration, SessionError> {
+        let (phase_type, remaining) = self
+            .current_phase
+            .as_ref()
+            .map(|phase| (phase.phase_type, phase.remaining()))
+            .ok_or(SessionError::NotActive)?;
+
+        self.skip()?;
+
+        self.interjections.push_front((phase_type, remaining));
+        self.interjections
+            .push_front((PhaseType::Short(minutes), Duration::minutes(minutes as i64)));
+
+        Ok(remaining)
+    }
+
+    /// Make the currently running phase end `remaining` from now, instead of
+    /// whenever it was going to end.
+    ///
+    /// Returns [`SessionError::NotActive`] if there is no currently running
+    /// phase. Otherwise, returns the phase's new end.
+    #[instrument]
+    pub fn set_remaining(&mut self, remaining: Duration) -> Result<DateTime<Utc>, SessionError> {
+        let phase = self.current_phase.as_ref().ok_or(SessionError::NotActive)?;
+
+        let end = self.clock.now() + remaining;
+        *phase.end.lock().unwrap() = end;
+
+        Ok(end)
+    }
+
+    /// Get how long has been spent working and on breaks in this session so
+    /// far, including the currently running phase.
+    pub fn time_spent(&self) -> TimeSpent {
+        let mut time_spent = self.time_spent;
+
+        if let Some(ref phase) = self.current_phase {
+            time_spent.add(phase.phase_type, phase.time_spent());
+        }
+
+        time_spent
+    }
+
+    /// Returns whether this session has a currently running phase.
+    ///
+    /// A session that is not running is either yet to start its first phase,
+    /// or between phases: a phase has been skipped or stopped but the next one
+    /// (if any) hasn't been started yet.
+    pub fn is_running(&self) -> bool {
+        self.current_phase.is_some()
+    }
+
+    /// Get the types of the next `n` phases after the currently running one,
+    /// without changing anything.
+    ///
+    /// Any interjected phases come first, followed by the normal sequence from
+    /// where the session is up to. In roulette mode, work phases are given the
+    /// length in the config, since their real length isn't picked until they
+    /// start.
+    pub fn peek_next_phases(&self, n: usize) -> Vec<PhaseType> {
+        self.next_phases()
+            .take(n)
+            .map(|(phase_type, _)| phase_type)
+            .collect()
+    }
+
+    /// Get the currently running phase (if there is one) followed by the
+    /// phases after it, up to and including the next long break, but no more
+    /// than `max` phases altogether.
+    pub fn timeline(&self, max: usize) -> Vec<PhaseType> {
+        let mut timeline = self.current_phase_type().into_iter().collect::<Vec<_>>();
+
+        for phase_type in self.peek_next_phases(max) {
+            if timeline.len() >= max {
+                break;
+            }
+
+            timeline.push(phase_type);
+
+            if let PhaseType::Long(_) = phase_type {
+                break;
+            }
+        }
+
+        timeline
+    }
+
+    /// Iterate over the phases after the currently running one, along with
+    /// how long each will last.
+    fn next_phases(&self) -> impl Iterator<Item = (PhaseType, Duration)> + '_ {
+        self.interjections.iter().copied().chain(
+            self.config
+                .phases_from(self.next_index, self.cycle_offset)
+                .map(|phase_type| (phase_type, Duration::minutes(phase_type.length() as i64))),
+        )
+    }
+
+    /// Get the next `count` phases after the currently running one, along with
+    /// when each is expected to start (assuming nothing gets skipped).
+    pub fn upcoming_phases(&self, count: usize) -> Vec<(PhaseType, DateTime<Utc>)> {
+        let mut start = self.clock.now()
+            + self
+                .current_phase
+                .as_ref()
+                .map_or_else(Duration::zero, |phase| phase.remaining());
+
+        self.next_phases()
+            .take(count)
+            .map(|(phase_type, length)| {
+                let phase_start = start;
+                start = start + length;
+                (phase_type, phase_start)
+            })
+            .collect()
+    }
+
+    /// Get when the phase `n` phases after the next one is expected to start
+    /// (assuming nothing gets skipped), so that `n = 0` is the next phase.
+    pub fn upcoming_phase_start(&self, n: usize) -> Option<DateTime<Utc>> {
+        let mut start = self.clock.now()
+            + self
+                .current_phase
+                .as_ref()
+                .map_or_else(Duration::zero, |phase| phase.remaining());
+
+        self.next_phases()
+            .map(|(_, length)| {
+                let phase_start = start;
+                start = start + length;
+                phase_start
+            })
+            .nth(n)
+    }
+
+    /// Get the number of the currently running phase among the work phases or
+    /// the breaks in the sequence, counting from 1, so that the third work
+    /// phase is number 3 and so is the break after it.
+    ///
+    /// A work phase resumed after `/breaknow` keeps its number. Phases that
+    /// aren't part of the sequence (like the break from `/breaknow`) don't have
+    /// one.
+    pub fn phase_number(&self) -> Option<usize> {
+        let current = self.current_phase.as_ref()?.phase_type;
+        let index = self.next_index.checked_sub(1)?;
+
+        match (current, self.phase_at(index)) {
+            (PhaseType::Work(_), PhaseType::Work(_))
+            | (
+                PhaseType::Short(_) | PhaseType::Long(_),
+                PhaseType::Short(_) | PhaseType::Long(_),
+            ) => Some(index / 2 + 1),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the currently running phase is the first phase of this
+    /// session.
+    pub fn is_first_phase(&self) -> bool {
+        self.phases_started == 1 && self.current_phase.is_some()
+    }
+
+    /// Get the type of the currently running phase, if there is one.
+    pub fn current_phase_type(&self) -> Option<PhaseType> {
+        self.current_phase.as_ref().map(|phase| phase.phase_type)
+    }
+
+    /// Get the type of the phase at index `index` in this session's sequence.
+    fn phase_at(&self, index: usize) -> PhaseType {
+        self.config.phase_at(index, self.cycle_offset)
+    }
+
+    /// Get the type of the phase that will run when the current one ends.
+    fn next_phase_type(&self) -> PhaseType {
+        self.next_phases()
+            .map(|(phase_type, _)| phase_type)
+            .next()
+            .expect("the sequence of phases never ends")
+    }
+
+    /// Get how the session ends with its current phase, which finishes at
+    /// `phase_end`, or `None` if it carries on afterwards.
+    fn ending(&self, phase_end: DateTime<Utc>) -> Option<SessionEnding> {
+        match self.deadline {
+            Some(deadline) if deadline <= phase_end => Some(SessionEnding::Expiring(deadline)),
+            _ if self.finishing => Some(SessionEnding::Finishing),
+            _ => None,
+        }
+    }
+
     pub fn status(&self) -> SessionStatus {
         match self.current_phase {
             Some(ref phase) => SessionStatus::Running {
                 phase_type: phase.phase_type,
                 phase_elapsed: phase.elapsed(),
                 phase_remaining: phase.remaining(),
-                next_type: self.config.phase_at(self.next_index),
-                long_at: Utc::now()
-                    + phase.remaining()
-                    + Duration::minutes(self.config.until_long(self.next_index) as i64),
+                phase_planned: phase.planned,
+                phase_length: phase.length(),
+                phase_number: self.phase_number(),
+                session_elapsed: self.clock.now() - self.started,
+                next_type: self.next_phase_type(),
+                long_at: self
+                    .config
+                    .until_long(self.next_index, self.cycle_offset)
+                    .map(|minutes| {
+                        self.clock.now()
+                            + phase.remaining()
+                            + self
+                                .interjections
+                                .iter()
+                                .fold(Duration::zero(), |total, (_, length)| total + *length)
+                            + Duration::minutes(minutes as i64)
+                    }),
+                ending: self.ending(self.clock.now() + phase.remaining()),
             },
             None => SessionStatus::NoSession,
         }
     }
+
+    /// Get a snapshot of this session's state, for `/debug dump`.
+    pub fn dump(&self) -> SessionDump {
+        SessionDump {
+            id: self.id.to_string(),
+            host: self.host,
+            members: self.members.len(),
+            max_members: self.max_members,
+            config: self.config.clone(),
+            next_index: self.next_index,
+            cycle_offset: self.cycle_offset,
+            phases_started: self.phases_started,
+            current_phase: self.current_phase.as_ref().map(|phase| phase.phase_type),
+            phase_end: self.current_phase.as_ref().map(PhaseHandle::end),
+            interjections: self
+                .interjections
+                .iter()
+                .map(|(phase_type, _)| *phase_type)
+                .collect(),
+            started: self.started,
+            deadline: self.deadline,
+            finishing: self.finishing,
+            join_on_breaks: self.join_on_breaks,
+            silent_pings: self.silent_pings,
+            countdown: self.countdown,
+            roulette: self.roulette.is_some(),
+        }
+    }
 }
 
-#[derive(Debug)]
+/// The state of a [`Session`] as it's included in `/debug dump`, which is
+/// everything that's useful to know when something's gone wrong with it.
+#[derive(Debug, Serialize)]
+pub struct SessionDump {
+    pub id: String,
+    pub host: UserId,
+    /// How many members the session has, rather than who they are.
+    pub members: usize,
+    pub max_members: Option<usize>,
+    pub config: SessionConfig,
+    pub next_index: usize,
+    pub cycle_offset: usize,
+    pub phases_started: usize,
+    /// The type of the phase that's running, or `None` if the session is
+    /// between phases.
+    pub current_phase: Option<PhaseType>,
+    pub phase_end: Option<DateTime<Utc>>,
+    pub interjections: Vec<PhaseType>,
+    pub started: DateTime<Utc>,
+    pub deadline: Option<DateTime<Utc>>,
+    pub finishing: bool,
+    pub join_on_breaks: bool,
+    pub silent_pings: bool,
+    pub countdown: bool,
+    pub roulette: bool,
+}
+
+/// How long a session has spent working and on breaks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeSpent {
+    pub work: Duration,
+    pub breaks: Duration,
+}
+
+impl Default for TimeSpent {
+    fn default() -> Self {
+        Self {
+            work: Duration::zero(),
+            breaks: Duration::zero(),
+        }
+    }
+}
+
+impl TimeSpent {
+    fn add(&mut self, phase_type: PhaseType, length: Duration) {
+        match phase_type {
+            PhaseType::Work(_) => self.work = self.work + length,
+            PhaseType::Short(_) | PhaseType::Long(_) => self.breaks = self.breaks + length,
+        }
+    }
+
+    /// Get the time spent on breaks as a percentage of the time spent working,
+    /// or `None` if no time has been spent working.
+    pub fn break_percentage(&self) -> Option<f64> {
+        if self.work > Duration::zero() {
+            Some(self.breaks.num_seconds() as f64 / self.work.num_seconds() as f64 * 100.0)
+        } else {
+            None
+        }
+    }
+}
+
+/// A snapshot of the status of a session.
+///
+/// This is serializable for the benefit of anything outside the bot that wants
+/// to keep track of sessions, so its wire format should be kept stable:
+/// durations are serialized as whole seconds, and timestamps in RFC 3339
+/// format.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
 pub enum SessionStatus {
     NoSession,
     Running {
         phase_type: PhaseType,
+        #[serde(with = "seconds")]
         phase_elapsed: Duration,
+        #[serde(with = "seconds")]
         phase_remaining: Duration,
+        /// How long the phase was planned to be.
+        #[serde(with = "seconds")]
+        phase_planned: Duration,
+        /// How long the phase actually runs for, which is the same as
+        /// `phase_planned` unless something changed it.
+        #[serde(with = "seconds")]
+        phase_length: Duration,
+        /// The number of the phase among the work phases or breaks in the
+        /// sequence, or `None` if it isn't part of the sequence (in which case
+        /// it's left out of the wire format).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        phase_number: Option<usize>,
+        /// How long it's been since the session's first phase started.
+        #[serde(with = "seconds")]
+        session_elapsed: Duration,
         next_type: PhaseType,
-        long_at: DateTime<Utc>,
+        /// When the next long break starts, or `None` if there are no long
+        /// breaks (in which case it's left out of the wire format).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        long_at: Option<DateTime<Utc>>,
+        /// How the session ends with this phase, or `None` if it carries on
+        /// afterwards (in which case it's left out of the wire format).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ending: Option<SessionEnding>,
     },
 }
 
-#[derive(Debug, Error)]
-pub enum SessionError {
-    #[error("there is no currently active phase")]
-    NotActive,
-}
-
-/// Messages that can be sent to running [`Phase`]s to instruct them to do
-/// things.
-enum PhaseMessage {
-    /// Stop the phase and resolve to a [`PhaseResult::Skipped`].
-    Skip,
-    /// Stop the phase and resolve to a [`PhaseResult::Stopped`].
-    Stop,
+/// How a session ends with its current phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEnding {
+    /// Someone used `/finish`, so the session finishes when the phase does.
+    Finishing,
+    /// The session reaches its maximum length at this time, before the phase
+    /// is over, and is stopped then.
+    Expiring(DateTime<Utc>),
 }
 
-/// A handle allowing communication with, and holding details about, a running
-/// [`Phase`].
-pub struct PhaseHandle {
-    started: DateTime<Utc>,
-    phase_type: PhaseType,
-    send: Sender<PhaseMessage>,
-}
+/// (De)serialization of [`Duration`]s as a whole number of seconds.
+mod seconds {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
 
-impl PhaseHandle {
-    fn elapsed(&self) -> Duration {
-        Utc::now() - self.started
+    pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i64(duration.num_seconds())
     }
 
-    fn remaining(&self) -> Duration {
-        Duration::minutes(self.phase_type.length() as i64) - self.elapsed()
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        i64::deserialize(deserializer).map(Duration::seconds)
     }
 }
 
-impl fmt::Debug for PhaseHandle {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Phase")
-            .field("started", &self.started)
-            .field("send", &"Sender<PhaseMessage>")
-            .finish()
+/// How a session announces that it has finished, rather than being stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, poise::SlashChoiceParameter)]
+pub enum FinishAnnouncement {
+    /// An embed with some stats about the session.
+    #[name = "Full"]
+    Full,
+    /// A one-line message.
+    #[name = "Brief"]
+    Brief,
+    /// No message at all.
+    #[name = "None"]
+    Silent,
+}
+
+impl Default for FinishAnnouncement {
+    fn default() -> Self {
+        Self::Full
     }
 }
 
-#[derive(Debug)]
-pub enum PhaseResult {
-    Completed(PhaseType),
-    Skipped(PhaseType),
-    Stopped(PhaseType),
-    Failed(PhaseType),
+/// Whether skipped work phases count towards the next long break.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, poise::SlashChoiceParameter)]
+pub enum SkippedWork {
+    /// Skipped work phases count just like completed ones.
+    #[name = "Counted"]
+    Counted,
+    /// A skipped work phase is followed by a short break, and then has to be
+    /// done again before the next long break.
+    #[name = "Not counted"]
+    NotCounted,
+}
+
+impl Default for SkippedWork {
+    fn default() -> Self {
+        Self::Counted
+    }
+}
+
+/// How to show a session's config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, poise::SlashChoiceParameter)]
+pub enum ConfigFormat {
+    /// An embed with a field for each setting.
+    #[name = "Embed"]
+    Embed,
+    /// A TOML block that can be copied into a file.
+    #[name = "TOML"]
+    Toml,
+}
+
+impl Default for ConfigFormat {
+    fn default() -> Self {
+        Self::Embed
+    }
+}
+
+/// Everything needed to go back to a phase after it was skipped.
+#[derive(Debug)]
+struct SkippedPhase {
+    phase_type: PhaseType,
+    /// How much of the phase was left when it was skipped.
+    remaining: Duration,
+    /// Where the session was in the sequence when the phase was skipped.
+    next_index: usize,
+    interjections: VecDeque<(PhaseType, Duration)>,
+    /// How many phases had been started when the phase was skipped.
+    phases_started: usize,
+    at: DateTime<Utc>,
+}
+
+/// How many users were added to a session by [`Session::enroll`], and how many
+/// weren't for each possible reason.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Enrollment {
+    pub added: usize,
+    pub already_members: usize,
+    pub opted_out: usize,
+    pub no_room: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum SessionError {
+    #[error("there is no currently active phase")]
+    NotActive,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("{0} phases must be at least 1 minute long")]
+    ZeroLength(&'static str),
+    #[error("the long break interval must be at least 1")]
+    ZeroInterval,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConfigCodeError {
+    #[error("that isn't a config code")]
+    Malformed,
+    #[error("config codes from version {0} aren't supported")]
+    UnsupportedVersion(u32),
+    #[error(transparent)]
+    Invalid(#[from] ConfigError),
+}
+
+/// The version of the format of codes produced by [`SessionConfig::to_code`].
+/// This must be bumped whenever a change is made that means old codes would no
+/// longer decode to the same config.
+const CONFIG_CODE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct ConfigCode {
+    version: u32,
+    config: SessionConfig,
+}
+
+#[derive(Deserialize)]
+struct ConfigCodeVersion {
+    version: u32,
+}
+
+/// Messages that can be sent to running [`Phase`]s to instruct them to do
+/// things.
+enum PhaseMessage {
+    /// Stop the phase and resolve to a [`PhaseResult::Skipped`], having run
+    /// for `elapsed` before it was skipped.
+    Skip { elapsed: Duration },
+    /// Stop the phase and resolve to a [`PhaseResult::Stopped`], having run
+    /// for `elapsed` before it was stopped.
+    Stop { elapsed: Duration },
+}
+
+/// A handle allowing communication with, and holding details about, a running
+/// [`Phase`].
+pub struct PhaseHandle {
+    started: DateTime<Utc>,
+    /// When the phase ends, which is shared with the [`Phase`] itself so that
+    /// it can be changed while the phase is running.
+    end: Arc<Mutex<DateTime<Utc>>>,
+    /// How long the phase was planned to be, before anything changed how long
+    /// it actually runs for (e.g. being resumed with only part of it left).
+    planned: Duration,
+    phase_type: PhaseType,
+    send: Sender<PhaseMessage>,
+    clock: Arc<dyn Clock>,
+}
+
+impl PhaseHandle {
+    fn elapsed(&self) -> Duration {
+        self.clock.now() - self.started
+    }
+
+    fn end(&self) -> DateTime<Utc> {
+        *self.end.lock().unwrap()
+    }
+
+    /// Get how long this phase actually runs for, from when it started to when
+    /// it ends.
+    fn length(&self) -> Duration {
+        self.end() - self.started
+    }
+
+    /// Get how much time has been spent in this phase, which doesn't count any
+    /// time after its end before the next phase started.
+    fn time_spent(&self) -> Duration {
+        self.elapsed().min(self.length())
+    }
+
+    fn remaining(&self) -> Duration {
+        self.remaining_at(self.clock.now())
+    }
+
+    /// Get how much of this phase is left at `now`.
+    ///
+    /// This is never negative: a phase that has gone past its end but hasn't
+    /// been advanced from yet has nothing left.
+    fn remaining_at(&self, now: DateTime<Utc>) -> Duration {
+        (self.end() - now).max(Duration::zero())
+    }
+}
+
+impl fmt::Debug for PhaseHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Phase")
+            .field("started", &self.started)
+            .field("end", &self.end())
+            .field("planned", &self.planned)
+            .field("send", &"Sender<PhaseMessage>")
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub enum PhaseResult {
+    Completed(PhaseType),
+    /// The phase was skipped after running for the given length of time.
+    Skipped(PhaseType, Duration),
+    /// The phase was stopped after running for the given length of time.
+    Stopped(PhaseType, Duration),
+    Failed(PhaseType),
 }
 
 #[must_use]
 pub struct Phase {
     session: Uuid,
-    end: DateTime<Utc>,
+    end: Arc<Mutex<DateTime<Utc>>>,
     phase_type: PhaseType,
     recv: Receiver<PhaseMessage>,
     waker: Option<(Arc<Mutex<Waker>>, Receiver<()>)>,
+    clock: Arc<dyn Clock>,
+    heartbeat: Arc<Mutex<DateTime<Utc>>>,
 }
 
 impl Phase {
     pub fn phase_type(&self) -> &PhaseType {
         &self.phase_type
     }
+
+    /// Get the time at which this phase will complete, if it isn't skipped or
+    /// stopped first.
+    pub fn end(&self) -> DateTime<Utc> {
+        *self.end.lock().unwrap()
+    }
 }
 
 impl Future for Phase {
@@ -238,6 +1347,8 @@ impl Future for Phase {
 
     #[instrument(skip(self, ctx))]
     fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        *self.heartbeat.lock().unwrap() = Utc::now();
+
         // For more info on this waker logic: https://tokio.rs/tokio/tutorial/async
 
         if let Some((waker, waker_recv)) = self.waker.as_mut() {
@@ -261,6 +1372,8 @@ impl Future for Phase {
         // This will be None either if we haven't spawned a waker thread yet, or if
         // we've just found out that the previous one is finished.
         if self.waker.is_none() {
+            // This is how often we check whether the phase has finished, so it uses the
+            // system clock even if the phase has a different one.
             let when = Utc::now() + Duration::milliseconds(100);
 
             let (send, recv) = oneshot_channel();
@@ -268,54 +1381,66 @@ impl Future for Phase {
             self.waker = Some((waker.clone(), recv));
 
             let session = self.session;
+            let phase_type = self.phase_type;
+            let end = self.end();
 
-            thread::spawn(move || {
-                let span = tracing::debug_span!("waker", id = ?session);
-                let _enter = span.enter();
-
-                let now = Utc::now();
+            let spawned = thread::Builder::new()
+                .name(format!("waker-{}", session))
+                .spawn(move || {
+                    let span = tracing::debug_span!("waker", id = ?session, ?phase_type, %end);
+                    let _enter = span.enter();
 
-                if now < when {
-                    let duration = (when - now)
-                        .to_std()
-                        .expect("duration is not negative, we just checked");
+                    let now = Utc::now();
 
-                    thread::sleep(duration);
-                }
+                    if now < when {
+                        let duration = (when - now)
+                            .to_std()
+                            .expect("duration is not negative, we just checked");
 
-                match send.send(()) {
-                    Ok(()) => {
-                        trace!("signalled phase that waker thread has completed");
+                        thread::sleep(duration);
                     }
-                    Err(()) => {
-                        debug!(
-                            "unable to signal phase that waker thread has completed; phase was \
-                             probably dropped"
-                        );
+
+                    match send.send(()) {
+                        Ok(()) => {
+                            trace!("signalled phase that waker thread has completed");
+                        }
+                        Err(()) => {
+                            debug!(
+                                "unable to signal phase that waker thread has completed; phase \
+                                 was probably dropped"
+                            );
+                        }
                     }
-                }
 
-                let waker = waker.lock().unwrap();
-                waker.wake_by_ref();
-            });
+                    let waker = waker.lock().unwrap();
+                    waker.wake_by_ref();
+                });
+
+            if let Err(error) = spawned {
+                // Nothing else is going to wake us up, so ask to be polled again straight away
+                // and try to spawn another one then.
+                warn!(?error, "unable to spawn waker thread");
+                self.waker = None;
+                ctx.waker().wake_by_ref();
+            }
         }
 
         match self.recv.try_recv() {
-            Ok(PhaseMessage::Skip) => {
-                debug!("phase skipped");
-                Poll::Ready(PhaseResult::Skipped(self.phase_type))
+            Ok(PhaseMessage::Skip { elapsed }) => {
+                debug!(%elapsed, "phase skipped");
+                Poll::Ready(PhaseResult::Skipped(self.phase_type, elapsed))
             }
-            Ok(PhaseMessage::Stop) => {
-                debug!("phase stopped");
-                Poll::Ready(PhaseResult::Stopped(self.phase_type))
+            Ok(PhaseMessage::Stop { elapsed }) => {
+                debug!(%elapsed, "phase stopped");
+                Poll::Ready(PhaseResult::Stopped(self.phase_type, elapsed))
             }
             Err(TryRecvError::Closed) => {
                 debug!("phase failed");
                 Poll::Ready(PhaseResult::Failed(self.phase_type))
             }
             Err(TryRecvError::Empty) => {
-                let now = Utc::now();
-                let is_finished = now >= self.end;
+                let now = self.clock.now();
+                let is_finished = now >= self.end();
 
                 if is_finished {
                     debug!("phase completed");
@@ -331,21 +1456,114 @@ impl Future for Phase {
 
 /// A pomocop session configuration, defining the lengths (in minutes) of each
 /// of the three types of phase, and the interval between long breaks.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SessionConfig {
     /// The number of minutes each work phase should last for.
     pub work: usize,
+    /// The number of minutes every other work phase should last for, starting
+    /// with the second, if they should alternate between two lengths.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternate_work: Option<usize>,
     /// The number of minutes each short break should last for.
     pub short: usize,
     /// The number of minutes each long break should last for.
     pub long: usize,
     /// The number of work sessions in between each long break.
     pub interval: usize,
+    /// Whether to never have long breaks, in which case every break is a
+    /// short break and `interval` is ignored.
+    #[serde(default)]
+    pub no_long: bool,
 }
 
 impl SessionConfig {
-    pub fn build(self) -> Session {
-        Session::from_config(self)
+    /// Create a config from a map of setting names to values, such as one
+    /// loaded from a config file.
+    ///
+    /// The `work`, `alternate_work`, `short`, `long` and `interval` keys are
+    /// all optional, and the default value is used for any that are missing.
+    /// Any other keys are ignored (with a warning). Returns a [`ConfigError`]
+    /// if the resulting config is invalid.
+    pub fn from_map(map: &HashMap<String, usize>) -> Result<Self, ConfigError> {
+        for key in map.keys() {
+            if !matches!(
+                key.as_str(),
+                "work" | "alternate_work" | "short" | "long" | "interval"
+            ) {
+                warn!(%key, "ignoring unknown session config key");
+            }
+        }
+
+        Self::default()
+            .work_or_default(map.get("work").copied())
+            .alternate_work(map.get("alternate_work").copied())
+            .short_or_default(map.get("short").copied())
+            .long_or_default(map.get("long").copied())
+            .interval_or_default(map.get("interval").copied())
+            .validate()
+    }
+
+    /// Check that this config describes a sequence of phases that makes sense,
+    /// returning it if so.
+    pub fn validate(self) -> Result<Self, ConfigError> {
+        if self.work == 0 {
+            Err(ConfigError::ZeroLength("work"))
+        } else if self.alternate_work == Some(0) {
+            Err(ConfigError::ZeroLength("alternate work"))
+        } else if self.short == 0 {
+            Err(ConfigError::ZeroLength("short"))
+        } else if self.long == 0 {
+            Err(ConfigError::ZeroLength("long"))
+        } else if self.interval == 0 {
+            Err(ConfigError::ZeroInterval)
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Encode this config as a short code that can be shared, and turned back
+    /// into the same config with [`SessionConfig::from_code`].
+    pub fn to_code(&self) -> String {
+        let code = ConfigCode {
+            version: CONFIG_CODE_VERSION,
+            config: self.clone(),
+        };
+
+        let json = serde_json::to_vec(&code).expect("a session config can always be serialized");
+        base64::encode_config(json, base64::URL_SAFE_NO_PAD)
+    }
+
+    /// Write this config out as TOML, which can be saved to a file and read
+    /// back into the same config.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("a session config can always be serialized")
+    }
+
+    /// Decode a config from a code produced by [`SessionConfig::to_code`].
+    ///
+    /// Returns a [`ConfigCodeError`] if the code isn't one we produced, was
+    /// produced by an incompatible version, or describes an invalid config.
+    pub fn from_code(code: &str) -> Result<Self, ConfigCodeError> {
+        let json = base64::decode_config(code.trim(), base64::URL_SAFE_NO_PAD)
+            .map_err(|_| ConfigCodeError::Malformed)?;
+
+        // Check the version on its own first, so that a code from another version
+        // is reported as such even if the rest of it has a different shape.
+        let ConfigCodeVersion { version } =
+            serde_json::from_slice(&json).map_err(|_| ConfigCodeError::Malformed)?;
+
+        if version != CONFIG_CODE_VERSION {
+            return Err(ConfigCodeError::UnsupportedVersion(version));
+        }
+
+        let ConfigCode { config, .. } =
+            serde_json::from_slice(&json).map_err(|_| ConfigCodeError::Malformed)?;
+
+        Ok(config.validate()?)
+    }
+
+    pub fn build(self, host: UserId) -> Session {
+        Session::from_config(self, host)
     }
 
     pub fn work(mut self, work: usize) -> Self {
@@ -361,6 +1579,14 @@ impl SessionConfig {
         }
     }
 
+    /// Alternate between work phases of `work` minutes and `alternate_work`
+    /// minutes, or have them all be the same length if `alternate_work` is
+    /// `None`.
+    pub fn alternate_work(mut self, alternate_work: Option<usize>) -> Self {
+        self.alternate_work = alternate_work;
+        self
+    }
+
     pub fn short(mut self, short: usize) -> Self {
         self.short = short;
         self
@@ -400,12 +1626,65 @@ impl SessionConfig {
         }
     }
 
-    /// Return the phase type and length for the phase at index `phase_index`.
-    fn phase_at(&self, phase_index: usize) -> PhaseType {
+    pub fn no_long(mut self, no_long: bool) -> Self {
+        self.no_long = no_long;
+        self
+    }
+
+    /// Describe anything about the sequence of phases produced by this config
+    /// that might surprise whoever asked for it, if there is anything.
+    pub fn sequence_note(&self) -> Option<String> {
+        if self.no_long {
+            return None;
+        }
+
+        let mut breaks = self
+            .phases_from(0, 0)
+            .take(self.interval * 2)
+            .filter(|phase| !matches!(phase, PhaseType::Work(_)));
+
+        if breaks.all(|phase| matches!(phase, PhaseType::Long(_))) {
+            Some(format!(
+                "With an interval of {}, every break will be a {} minute long break, so there \
+                 won't be any short breaks at all.",
+                self.interval, self.long
+            ))
+        } else if self.long <= self.short {
+            Some(format!(
+                "Long breaks ({} minutes) won't actually be any longer than short breaks ({} \
+                 minutes).",
+                self.long, self.short
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Get the types of the `n` phases in the sequence starting from the one
+    /// at index `start`.
+    pub fn peek_phases(&self, start: usize, n: usize) -> Vec<PhaseType> {
+        self.phases_from(start, 0).take(n).collect()
+    }
+
+    /// Iterate over the (endless) sequence of phases, starting from the one at
+    /// index `start`, where index 0 is `cycle_offset` phases into a cycle.
+    fn phases_from(
+        &self,
+        start: usize,
+        cycle_offset: usize,
+    ) -> impl Iterator<Item = PhaseType> + '_ {
+        (start..).map(move |index| self.phase_at(index, cycle_offset))
+    }
+
+    /// Return the phase type and length for the phase at index `phase_index`,
+    /// where index 0 is `cycle_offset` phases into a cycle.
+    fn phase_at(&self, phase_index: usize, cycle_offset: usize) -> PhaseType {
         if phase_index % 2 == 0 {
             // The phase index is even, so it's a work phase
-            PhaseType::Work(self.work)
-        } else if phase_index % (self.interval * 2) == (self.interval * 2 - 1) {
+            PhaseType::Work(self.work_length(phase_index / 2))
+        } else if self.no_long {
+            PhaseType::Short(self.short)
+        } else if (phase_index + cycle_offset) % (self.interval * 2) == (self.interval * 2 - 1) {
             // The interval refers to how many *work* sessions pass between each long break,
             // so we need to multiply it by 2 to get how many *actual* sessions
             // pass between each long break.
@@ -415,17 +1694,29 @@ impl SessionConfig {
         }
     }
 
-    /// Return the number of minutes between the beginning of the phase with
-    /// index `current` and the beginning of the next long break.
-    fn until_long(&self, mut current: usize) -> usize {
-        let mut minutes = 0;
+    /// Return the length of the work phase with index `work_index` among only
+    /// the work phases.
+    fn work_length(&self, work_index: usize) -> usize {
+        match self.alternate_work {
+            Some(alternate_work) if work_index % 2 == 1 => alternate_work,
+            _ => self.work,
+        }
+    }
 
-        while let PhaseType::Work(length) | PhaseType::Short(length) = self.phase_at(current) {
-            minutes += length;
-            current += 1;
+    /// Return the number of minutes between the beginning of the phase with
+    /// index `current` and the beginning of the next long break, or `None` if
+    /// there are no long breaks.
+    fn until_long(&self, current: usize, cycle_offset: usize) -> Option<usize> {
+        if self.no_long {
+            return None;
         }
 
-        minutes
+        Some(
+            self.phases_from(current, cycle_offset)
+                .take_while(|phase| !matches!(phase, PhaseType::Long(_)))
+                .map(|phase| phase.length())
+                .sum(),
+        )
     }
 }
 
@@ -433,14 +1724,17 @@ impl Default for SessionConfig {
     fn default() -> Self {
         Self {
             work: 25,
+            alternate_work: None,
             short: 5,
             long: 15,
             interval: 4,
+            no_long: false,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "minutes", rename_all = "snake_case")]
 pub enum PhaseType {
     Work(usize),
     Short(usize),
@@ -462,11 +1756,21 @@ impl PhaseType {
             PhaseType::Long(length) => format!("{} minute long break", length),
         }
     }
+
+    /// Get the emoji that stands for this type of phase wherever it's shown.
+    pub fn emoji(&self) -> &'static str {
+        match *self {
+            PhaseType::Work(_) => "\u{1f345}",
+            PhaseType::Short(_) => "\u{2615}",
+            PhaseType::Long(_) => "\u{1f6cb}\u{fe0f}",
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pomo::clock::FakeClock;
 
     #[test]
     fn length_calc() {
@@ -474,7 +1778,7 @@ mod tests {
 
         let actual = (0..8)
             .into_iter()
-            .map(|i| config.phase_at(i))
+            .map(|i| config.phase_at(i, 0))
             .collect::<Vec<_>>();
 
         let expected = vec![
@@ -493,4 +1797,1143 @@ mod tests {
             "lengths of each session were not calculated correctly"
         );
     }
+
+    #[test]
+    fn alternating_work_lengths() {
+        // An odd interval means that the first work phase after each long break
+        // isn't always the same length.
+        let config = SessionConfig::default()
+            .work(50)
+            .alternate_work(Some(25))
+            .interval(3);
+
+        let actual = (0..14).map(|i| config.phase_at(i, 0)).collect::<Vec<_>>();
+
+        let expected = vec![
+            PhaseType::Work(50),
+            PhaseType::Short(5),
+            PhaseType::Work(25),
+            PhaseType::Short(5),
+            PhaseType::Work(50),
+            PhaseType::Long(15),
+            PhaseType::Work(25),
+            PhaseType::Short(5),
+            PhaseType::Work(50),
+            PhaseType::Short(5),
+            PhaseType::Work(25),
+            PhaseType::Long(15),
+            PhaseType::Work(50),
+            PhaseType::Short(5),
+        ];
+
+        assert_eq!(actual, expected);
+        assert_eq!(config.until_long(0, 0), Some(50 + 5 + 25 + 5 + 50));
+        assert_eq!(config.until_long(6, 0), Some(25 + 5 + 50 + 5 + 25));
+
+        assert_eq!(
+            SessionConfig::from_code(&config.to_code()),
+            Ok(config.clone())
+        );
+        assert_eq!(
+            config.alternate_work(Some(0)).validate(),
+            Err(ConfigError::ZeroLength("alternate work"))
+        );
+    }
+
+    #[test]
+    fn reset_sequence() {
+        let mut session = SessionConfig::default().build(UserId(1));
+
+        let _first = session.advance();
+        let _second = session.advance();
+
+        let skipped = session.reset_sequence().expect("a phase is running");
+        assert_eq!(skipped, PhaseType::Short(session.config().short));
+
+        let next = session.advance();
+        assert_eq!(
+            *next.phase_type(),
+            PhaseType::Work(session.config().work),
+            "sequence did not restart with a work phase"
+        );
+        assert!(
+            session.members().contains(&UserId(1)),
+            "members were not kept"
+        );
+    }
+
+    #[test]
+    fn skip_and_report_next() {
+        let mut session = SessionConfig::default().build(UserId(1));
+
+        assert!(matches!(
+            session.skip_and_report_next(),
+            Err(SessionError::NotActive)
+        ));
+
+        let _first = session.advance();
+        let (skipped, next) = session.skip_and_report_next().expect("a phase is running");
+        assert_eq!(skipped, PhaseType::Work(session.config().work));
+        assert_eq!(next, PhaseType::Short(session.config().short));
+
+        let _second = session.advance();
+        let _third = session.advance();
+        session.break_now(3).expect("a phase is running");
+
+        let _break = session.advance();
+        let (skipped, next) = session.skip_and_report_next().expect("a phase is running");
+        assert_eq!(skipped, PhaseType::Short(3));
+        assert_eq!(
+            next,
+            PhaseType::Work(session.config().work),
+            "next phase did not account for interjections"
+        );
+    }
+
+    #[test]
+    fn skip_many() {
+        let config = SessionConfig::default();
+        let mut session = config.clone().build(UserId(1));
+
+        let _first = session.advance();
+        let (skipped, next) = session.skip_many(3).expect("a phase is running");
+        assert_eq!(
+            skipped,
+            vec![
+                PhaseType::Work(config.work),
+                PhaseType::Short(config.short),
+                PhaseType::Work(config.work),
+            ]
+        );
+        assert_eq!(next, PhaseType::Short(config.short));
+
+        let second = session.advance();
+        assert_eq!(*second.phase_type(), PhaseType::Short(config.short));
+
+        let (resumed, _) = session
+            .undo_skip(Duration::seconds(30))
+            .expect("the skip can be undone");
+        assert_eq!(resumed, PhaseType::Work(config.work));
+
+        let _resumed = session.advance();
+        assert_eq!(
+            session.next_phase_type(),
+            PhaseType::Short(config.short),
+            "undoing should go back to just after the first skipped phase"
+        );
+
+        session.break_now(3).expect("a phase is running");
+        let _break = session.advance();
+        let (skipped, next) = session.skip_many(2).expect("a phase is running");
+        assert_eq!(
+            skipped,
+            vec![PhaseType::Short(3), PhaseType::Work(config.work)],
+            "the interrupted phase should be skipped before the sequence carries on"
+        );
+        assert_eq!(next, PhaseType::Short(config.short));
+    }
+
+    #[test]
+    fn skipped_work_policies() {
+        let config = SessionConfig::default()
+            .work(25)
+            .short(5)
+            .long(15)
+            .interval(2);
+
+        // Runs the session through 8 phases, skipping the second work phase
+        // and letting the rest complete.
+        let sequence = |skipped_work| {
+            let mut session = config.clone().build(UserId(1));
+            session.set_skipped_work(skipped_work);
+
+            (0..8)
+                .map(|i| {
+                    let phase = session.advance();
+                    let phase_type = *phase.phase_type();
+
+                    if i == 2 {
+                        session.skip_and_report_next().expect("a phase is running");
+                    }
+
+                    phase_type
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            sequence(SkippedWork::Counted),
+            vec![
+                PhaseType::Work(25),
+                PhaseType::Short(5),
+                PhaseType::Work(25),
+                PhaseType::Long(15),
+                PhaseType::Work(25),
+                PhaseType::Short(5),
+                PhaseType::Work(25),
+                PhaseType::Long(15),
+            ]
+        );
+        assert_eq!(
+            sequence(SkippedWork::NotCounted),
+            vec![
+                PhaseType::Work(25),
+                PhaseType::Short(5),
+                // Skipped, so it's done again before the long break
+                PhaseType::Work(25),
+                PhaseType::Short(5),
+                PhaseType::Work(25),
+                PhaseType::Long(15),
+                PhaseType::Work(25),
+                PhaseType::Short(5),
+            ]
+        );
+    }
+
+    #[test]
+    fn join_on_breaks() {
+        let clock = Arc::new(FakeClock::new());
+        let host = UserId(1);
+        let mut session = SessionConfig::default().build(host);
+        session.set_clock(clock.clone());
+
+        let _work = session.advance();
+        assert!(
+            session.can_join_now(UserId(2)),
+            "anyone should be able to join at any time by default"
+        );
+
+        session.set_join_on_breaks(true);
+        assert!(!session.can_join_now(UserId(2)));
+        assert!(
+            session.can_join_now(host),
+            "the host should always be able to join"
+        );
+
+        clock.advance(Duration::minutes(25));
+        let _short = session.advance();
+        assert!(session.can_join_now(UserId(2)));
+    }
+
+    #[test]
+    fn max_members() {
+        let host = UserId(1);
+        let mut session = SessionConfig::default().build(host);
+        session.set_max_members(Some(2));
+
+        assert!(session.has_room_for(UserId(2)));
+        session.add_member(UserId(2));
+        assert!(
+            !session.has_room_for(UserId(3)),
+            "session should be full at its maximum"
+        );
+
+        session.remove_member(host);
+        session.add_member(UserId(3));
+        assert!(
+            session.has_room_for(host),
+            "the host should always be able to rejoin"
+        );
+    }
+
+    #[test]
+    fn config_from_empty_map() {
+        let config = SessionConfig::from_map(&HashMap::new()).expect("defaults are valid");
+
+        assert_eq!(config, SessionConfig::default());
+    }
+
+    #[test]
+    fn config_from_partial_map() {
+        let map = HashMap::from([
+            ("work".to_owned(), 50),
+            ("interval".to_owned(), 2),
+            ("unknown".to_owned(), 7),
+        ]);
+
+        let config = SessionConfig::from_map(&map).expect("config is valid");
+
+        assert_eq!(
+            config,
+            SessionConfig::default().work(50).interval(2),
+            "only the provided keys should override the defaults"
+        );
+    }
+
+    #[test]
+    fn config_from_invalid_map() {
+        let map = HashMap::from([("interval".to_owned(), 0)]);
+
+        assert_eq!(
+            SessionConfig::from_map(&map),
+            Err(ConfigError::ZeroInterval)
+        );
+    }
+
+    #[test]
+    fn break_now_resumes_interrupted_phase() {
+        let config = SessionConfig::default();
+        let mut session = config.clone().build(UserId(1));
+
+        let _work = session.advance();
+        let remaining = session.break_now(3).expect("a phase is running");
+        assert!(remaining <= Duration::minutes(config.work as i64));
+
+        let actual = (0..4)
+            .map(|_| *session.advance().phase_type())
+            .collect::<Vec<_>>();
+
+        let expected = vec![
+            PhaseType::Short(3),
+            PhaseType::Work(config.work),
+            PhaseType::Short(config.short),
+            PhaseType::Work(config.work),
+        ];
+
+        assert_eq!(
+            actual, expected,
+            "the interjected break should not affect the rest of the sequence"
+        );
+    }
+
+    #[test]
+    fn phase_emoji() {
+        assert_eq!(PhaseType::Work(25).emoji(), "🍅");
+        assert_eq!(PhaseType::Short(5).emoji(), "☕");
+        assert_eq!(PhaseType::Long(15).emoji(), "🛋️");
+    }
+
+    #[test]
+    fn status_wire_format() {
+        let status = SessionStatus::Running {
+            phase_type: PhaseType::Work(25),
+            phase_elapsed: Duration::seconds(90),
+            phase_remaining: Duration::seconds(1410),
+            phase_planned: Duration::seconds(1500),
+            phase_length: Duration::seconds(1500),
+            phase_number: Some(3),
+            session_elapsed: Duration::seconds(3090),
+            next_type: PhaseType::Short(5),
+            long_at: Some(
+                DateTime::parse_from_rfc3339("2021-09-01T12:00:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc),
+            ),
+            ending: None,
+        };
+
+        let json = serde_json::to_value(&status).unwrap();
+        let expected = serde_json::json!({
+            "status": "running",
+            "phase_type": { "type": "work", "minutes": 25 },
+            "phase_elapsed": 90,
+            "phase_remaining": 1410,
+            "phase_planned": 1500,
+            "phase_length": 1500,
+            "phase_number": 3,
+            "session_elapsed": 3090,
+            "next_type": { "type": "short", "minutes": 5 },
+            "long_at": "2021-09-01T12:00:00Z",
+        });
+
+        assert_eq!(json, expected, "wire format of SessionStatus changed");
+
+        let round_trip: SessionStatus = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, status);
+    }
+
+    #[test]
+    fn no_session_wire_format() {
+        let json = serde_json::to_value(&SessionStatus::NoSession).unwrap();
+        assert_eq!(json, serde_json::json!({ "status": "no_session" }));
+
+        let round_trip: SessionStatus = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, SessionStatus::NoSession);
+    }
+
+    #[test]
+    fn sequence_notes() {
+        assert_eq!(SessionConfig::default().sequence_note(), None);
+
+        assert!(
+            SessionConfig::default()
+                .interval(1)
+                .sequence_note()
+                .is_some(),
+            "an interval of 1 should be noted"
+        );
+
+        assert!(
+            SessionConfig::default()
+                .short(10)
+                .long(10)
+                .sequence_note()
+                .is_some(),
+            "long breaks no longer than short breaks should be noted"
+        );
+    }
+
+    #[test]
+    fn is_running() {
+        let mut session = SessionConfig::default().build(UserId(1));
+        assert!(!session.is_running(), "session has not started yet");
+
+        let _phase = session.advance();
+        assert!(session.is_running());
+
+        session.skip().expect("a phase is running");
+        assert!(
+            !session.is_running(),
+            "session is between phases after skipping"
+        );
+
+        let _phase = session.advance();
+        session.stop().expect("a phase is running");
+        assert!(!session.is_running(), "session is stopped");
+    }
+
+    #[test]
+    fn timeline() {
+        let config = SessionConfig::default().interval(2);
+        let mut session = config.clone().build(UserId(1));
+        let work = PhaseType::Work(config.work);
+        let short = PhaseType::Short(config.short);
+        let long = PhaseType::Long(config.long);
+
+        assert_eq!(session.timeline(10), vec![work, short, work, long]);
+
+        let _phase = session.advance();
+        let _phase = session.advance();
+        assert_eq!(
+            session.timeline(10),
+            vec![short, work, long],
+            "the current phase should come first"
+        );
+        assert_eq!(session.timeline(2), vec![short, work]);
+
+        let session = config.no_long(true).build(UserId(1));
+        assert_eq!(
+            session.timeline(5),
+            vec![work, short, work, short, work],
+            "without long breaks, the timeline should stop at the limit"
+        );
+    }
+
+    #[test]
+    fn upcoming_phases() {
+        let config = SessionConfig::default();
+        let mut session = config.clone().build(UserId(1));
+        let _phase = session.advance();
+
+        let upcoming = session.upcoming_phases(4);
+
+        let types = upcoming
+            .iter()
+            .map(|(phase_type, _)| *phase_type)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                PhaseType::Short(config.short),
+                PhaseType::Work(config.work),
+                PhaseType::Short(config.short),
+                PhaseType::Work(config.work),
+            ]
+        );
+
+        let gaps = upcoming
+            .windows(2)
+            .map(|pair| pair[1].1 - pair[0].1)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            gaps,
+            vec![
+                Duration::minutes(config.short as i64),
+                Duration::minutes(config.work as i64),
+                Duration::minutes(config.short as i64),
+            ],
+            "each phase should start when the previous one ends"
+        );
+    }
+
+    #[test]
+    fn upcoming_phase_start() {
+        let mut session = SessionConfig::default().build(UserId(1));
+        let _phase = session.advance();
+
+        let upcoming = session.upcoming_phases(4);
+
+        for (n, (_, start)) in upcoming.iter().enumerate() {
+            assert_eq!(session.upcoming_phase_start(n), Some(*start));
+        }
+    }
+
+    #[test]
+    fn config_toml() {
+        let config = SessionConfig::default().work(50).interval(2);
+        let written = config.to_toml();
+
+        assert_eq!(
+            written,
+            "work = 50\nshort = 5\nlong = 15\ninterval = 2\nno_long = false\n"
+        );
+        assert_eq!(toml::from_str::<SessionConfig>(&written), Ok(config));
+
+        let alternating = SessionConfig {
+            alternate_work: Some(15),
+            ..SessionConfig::default()
+        };
+        assert_eq!(
+            toml::from_str::<SessionConfig>(&alternating.to_toml()),
+            Ok(alternating)
+        );
+    }
+
+    #[test]
+    fn config_codes() {
+        let config = SessionConfig::default().work(50).interval(2);
+        assert_eq!(SessionConfig::from_code(&config.to_code()), Ok(config));
+
+        assert_eq!(
+            SessionConfig::from_code("not a code"),
+            Err(ConfigCodeError::Malformed)
+        );
+
+        let encode = |json: &str| base64::encode_config(json, base64::URL_SAFE_NO_PAD);
+
+        assert_eq!(
+            SessionConfig::from_code(&encode(r#"{"version":2}"#)),
+            Err(ConfigCodeError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            SessionConfig::from_code(&encode(
+                r#"{"version":1,"config":{"work":0,"short":5,"long":15,"interval":4}}"#
+            )),
+            Err(ConfigCodeError::Invalid(ConfigError::ZeroLength("work")))
+        );
+    }
+
+    #[test]
+    fn no_long_breaks() {
+        let config = SessionConfig::default().interval(1).no_long(true);
+
+        assert!(
+            (0..100).all(|i| !matches!(config.phase_at(i, 0), PhaseType::Long(_))),
+            "a long break was produced"
+        );
+        assert_eq!(config.until_long(0, 0), None);
+        assert_eq!(config.sequence_note(), None);
+
+        let mut session = config.build(UserId(1));
+        let _first = session.advance();
+        assert!(matches!(
+            session.status(),
+            SessionStatus::Running { long_at: None, .. }
+        ));
+    }
+
+    #[test]
+    fn remaining_at() {
+        let mut session = SessionConfig::default().build(UserId(1));
+        let _first = session.advance();
+
+        let phase = session.current_phase.as_ref().expect("a phase is running");
+
+        assert_eq!(
+            phase.remaining_at(phase.started),
+            Duration::minutes(session.config().work as i64)
+        );
+        assert_eq!(
+            phase.remaining_at(phase.end() - Duration::seconds(1)),
+            Duration::seconds(1)
+        );
+        assert_eq!(phase.remaining_at(phase.end()), Duration::zero());
+        assert_eq!(
+            phase.remaining_at(phase.end() + Duration::seconds(1)),
+            Duration::zero(),
+            "remaining time went negative past the end of the phase"
+        );
+    }
+
+    #[test]
+    fn adjust_interval() {
+        let mut session = SessionConfig::default().interval(4).build(UserId(1));
+        let config = session.config().clone();
+
+        assert_eq!(session.adjust_interval(0), Err(ConfigError::ZeroInterval));
+
+        // Work, short, work, short, work, so 3 work phases have started.
+        for _ in 0..5 {
+            let _phase = session.advance();
+        }
+
+        session.adjust_interval(8).unwrap();
+        assert_eq!(
+            session.next_phase_type(),
+            PhaseType::Short(config.short),
+            "a longer interval should carry on with the current cycle"
+        );
+
+        session.adjust_interval(2).unwrap();
+        assert_eq!(
+            session.next_phase_type(),
+            PhaseType::Long(config.long),
+            "a long break is overdue with the shorter interval"
+        );
+
+        let _long = session.advance();
+        let upcoming = session
+            .upcoming_phases(4)
+            .into_iter()
+            .map(|(phase_type, _)| phase_type)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            upcoming,
+            vec![
+                PhaseType::Work(config.work),
+                PhaseType::Short(config.short),
+                PhaseType::Work(config.work),
+                PhaseType::Long(config.long),
+            ],
+            "the new interval should apply after the long break"
+        );
+    }
+
+    #[test]
+    fn adjust_interval_keeps_place() {
+        let mut session = SessionConfig::default()
+            .work(50)
+            .alternate_work(Some(25))
+            .interval(3)
+            .build(UserId(1));
+
+        // A whole cycle and then the first work phase of the next one.
+        for _ in 0..7 {
+            let _phase = session.advance();
+        }
+        assert_eq!(session.phase_number(), Some(4));
+
+        session.adjust_interval(2).unwrap();
+        assert_eq!(
+            session.phase_number(),
+            Some(4),
+            "adjusting the interval shouldn't renumber the running phase"
+        );
+
+        let numbered = (0..4)
+            .map(|_| {
+                let phase = session.advance();
+                (*phase.phase_type(), session.phase_number())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            numbered,
+            vec![
+                (PhaseType::Short(5), Some(4)),
+                (PhaseType::Work(50), Some(5)),
+                (PhaseType::Long(15), Some(5)),
+                (PhaseType::Work(25), Some(6)),
+            ],
+            "numbers and alternating work lengths should carry on across the adjustment"
+        );
+
+        // Work, short, work, so the next phase is a break after 2 work phases.
+        let mut session = config.clone().build(UserId(1));
+        for _ in 0..3 {
+            let _phase = session.advance();
+        }
+        session.adjust_interval(1).unwrap();
+        assert_eq!(session.next_phase_type(), PhaseType::Long(config.long));
+
+        // Work, short, so the next phase is a work phase, then a long break.
+        let mut session = config.clone().build(UserId(1));
+        for _ in 0..2 {
+            let _phase = session.advance();
+        }
+        session.adjust_interval(1).unwrap();
+        assert_eq!(
+            session
+                .upcoming_phases(2)
+                .into_iter()
+                .map(|(phase_type, _)| phase_type)
+                .collect::<Vec<_>>(),
+            vec![PhaseType::Work(config.work), PhaseType::Long(config.long)]
+        );
+    }
+
+    #[test]
+    fn is_stalled() {
+        let session = SessionConfig::default().build(UserId(1));
+        assert!(!session.is_stalled(Duration::minutes(1)));
+
+        *session.heartbeat.lock().unwrap() = Utc::now() - Duration::minutes(2);
+        assert!(session.is_stalled(Duration::minutes(1)));
+        assert!(!session.is_stalled(Duration::minutes(5)));
+    }
+
+    #[test]
+    fn enroll() {
+        let mut session = SessionConfig::default().build(UserId(1));
+        session.set_max_members(Some(3));
+
+        let enrollment = session.enroll(
+            [UserId(1), UserId(2), UserId(3), UserId(4), UserId(5)],
+            |user| user == UserId(3),
+        );
+
+        assert_eq!(
+            enrollment,
+            Enrollment {
+                added: 2,
+                already_members: 1,
+                opted_out: 1,
+                no_room: 1,
+            }
+        );
+        assert!(session.is_member(UserId(2)));
+        assert!(!session.is_member(UserId(3)), "opted out user was added");
+        assert!(session.is_member(UserId(4)));
+        assert!(
+            !session.is_member(UserId(5)),
+            "user was added past the limit"
+        );
+    }
+
+    #[tokio::test]
+    async fn skip_and_stop_report_elapsed() {
+        let clock = Arc::new(FakeClock::new());
+        let mut session = SessionConfig::default().build(UserId(1));
+        session.set_clock(clock.clone());
+
+        let phase = session.advance();
+        clock.advance(Duration::minutes(3));
+        session.skip().expect("a phase is running");
+
+        assert!(matches!(
+            phase.await,
+            PhaseResult::Skipped(PhaseType::Work(_), elapsed) if elapsed == Duration::minutes(3)
+        ));
+
+        let phase = session.advance();
+        clock.advance(Duration::seconds(90));
+        session.stop().expect("a phase is running");
+
+        assert!(matches!(
+            phase.await,
+            PhaseResult::Stopped(PhaseType::Short(_), elapsed) if elapsed == Duration::seconds(90)
+        ));
+    }
+
+    #[tokio::test]
+    async fn set_remaining() {
+        let clock = Arc::new(FakeClock::new());
+        let mut session = SessionConfig::default().build(UserId(1));
+        session.set_clock(clock.clone());
+
+        assert!(matches!(
+            session.set_remaining(Duration::minutes(1)),
+            Err(SessionError::NotActive)
+        ));
+
+        let phase = session.advance();
+        clock.advance(Duration::minutes(3));
+
+        let end = session
+            .set_remaining(Duration::minutes(1))
+            .expect("a phase is running");
+        assert_eq!(end, clock.now() + Duration::minutes(1));
+        assert_eq!(phase.end(), end);
+
+        match session.status() {
+            SessionStatus::Running {
+                phase_remaining,
+                phase_length,
+                ..
+            } => {
+                assert_eq!(phase_remaining, Duration::minutes(1));
+                assert_eq!(phase_length, Duration::minutes(4));
+            }
+            status => panic!("expected a running session, got {:?}", status),
+        }
+
+        clock.advance(Duration::minutes(1));
+
+        assert!(matches!(
+            phase.await,
+            PhaseResult::Completed(PhaseType::Work(_))
+        ));
+    }
+
+    #[test]
+    fn silence() {
+        let mut session = SessionConfig::default().build(UserId(1));
+        session.add_member(UserId(2));
+        let _phase = session.advance();
+
+        session.silence(UserId(2), 2);
+
+        for _ in 0..2 {
+            let _phase = session.advance();
+            assert_eq!(session.mentioned_members(), vec![UserId(1)]);
+        }
+
+        let _phase = session.advance();
+        let mut mentioned = session.mentioned_members();
+        mentioned.sort();
+        assert_eq!(mentioned, vec![UserId(1), UserId(2)]);
+    }
+
+    #[test]
+    fn deadline() {
+        let clock = Arc::new(FakeClock::new());
+        let mut session = SessionConfig::default().build(UserId(1));
+        session.set_clock(clock.clone());
+        assert!(!session.is_expired(), "session without a deadline expired");
+
+        let deadline = session.start_deadline(Duration::hours(8));
+        assert_eq!(deadline, clock.now() + Duration::hours(8));
+
+        session.set_max_length(Some(Duration::hours(2)));
+        let deadline = session.start_deadline(Duration::hours(8));
+        assert_eq!(deadline, clock.now() + Duration::hours(2));
+        assert_eq!(session.deadline(), Some(deadline));
+
+        clock.advance(Duration::hours(2) - Duration::seconds(1));
+        assert!(!session.is_expired(), "session expired early");
+
+        clock.advance(Duration::seconds(1));
+        assert!(session.is_expired());
+
+        session.set_max_length(Some(Duration::hours(u32::MAX.into())));
+        let deadline = session.start_deadline(Duration::hours(8));
+        assert_eq!(deadline, chrono::MAX_DATETIME);
+        assert!(!session.is_expired());
+    }
+
+    #[test]
+    fn ending() {
+        let clock = Arc::new(FakeClock::new());
+        let mut session = SessionConfig::default().build(UserId(1));
+        session.set_clock(clock.clone());
+        session.set_max_length(Some(Duration::minutes(40)));
+        session.start_deadline(Duration::hours(8));
+
+        let ending = |session: &Session| match session.status() {
+            SessionStatus::Running { ending, .. } => ending,
+            status => panic!("expected a running session, got {:?}", status),
+        };
+
+        let _work = session.advance();
+        assert_eq!(ending(&session), None);
+
+        session.set_finishing(true);
+        assert_eq!(ending(&session), Some(SessionEnding::Finishing));
+        session.set_finishing(false);
+
+        clock.advance(Duration::minutes(25));
+        let _short = session.advance();
+        clock.advance(Duration::minutes(5));
+        let _work = session.advance();
+        assert_eq!(
+            ending(&session),
+            Some(SessionEnding::Expiring(clock.now() + Duration::minutes(10))),
+            "the deadline comes before the end of the second work phase"
+        );
+    }
+
+    #[test]
+    fn undo_skip() {
+        let clock = Arc::new(FakeClock::new());
+        let mut session = SessionConfig::default().build(UserId(1));
+        session.set_clock(clock.clone());
+        let window = Duration::seconds(30);
+
+        let _first = session.advance();
+        assert_eq!(
+            session.undo_skip(window),
+            None,
+            "undid a skip that never happened"
+        );
+
+        clock.advance(Duration::minutes(5));
+        session.skip_and_report_next().expect("a phase is running");
+        assert_eq!(
+            session.undo_skip(window),
+            None,
+            "undid a skip before the next phase started"
+        );
+
+        let _second = session.advance();
+        clock.advance(Duration::seconds(10));
+        let work = session.config().work;
+        assert_eq!(
+            session.undo_skip(window),
+            Some((PhaseType::Work(work), Duration::minutes(work as i64 - 5)))
+        );
+
+        let _resumed = session.advance();
+        assert_eq!(session.current_phase_type(), Some(PhaseType::Work(work)));
+        assert_eq!(
+            session.upcoming_phases(1)[0].0,
+            PhaseType::Short(session.config().short),
+            "sequence did not carry on from before the skip"
+        );
+
+        session.skip_and_report_next().expect("a phase is running");
+        let _third = session.advance();
+        clock.advance(Duration::seconds(31));
+        assert_eq!(session.undo_skip(window), None, "undid an expired skip");
+    }
+
+    #[test]
+    fn session_elapsed() {
+        let clock = Arc::new(FakeClock::new());
+        let mut session = SessionConfig::default().build(UserId(1));
+        session.set_clock(clock.clone());
+
+        clock.advance(Duration::minutes(1));
+        let _first = session.advance();
+        assert_eq!(session.started(), clock.now());
+
+        clock.advance(Duration::minutes(10));
+        session.skip_and_report_next().expect("a phase is running");
+        let _second = session.advance();
+        clock.advance(Duration::minutes(2));
+
+        match session.status() {
+            SessionStatus::Running {
+                phase_elapsed,
+                session_elapsed,
+                ..
+            } => {
+                assert_eq!(phase_elapsed, Duration::minutes(2));
+                assert_eq!(session_elapsed, Duration::minutes(12));
+            }
+            SessionStatus::NoSession => panic!("session isn't running"),
+        }
+    }
+
+    #[test]
+    fn time_spent() {
+        let clock = Arc::new(FakeClock::new());
+        let mut session = SessionConfig::default().work(25).short(5).build(UserId(1));
+        session.set_clock(clock.clone());
+
+        assert_eq!(session.time_spent().break_percentage(), None);
+
+        let _first = session.advance();
+        clock.advance(Duration::minutes(26));
+        let _second = session.advance();
+        clock.advance(Duration::minutes(2));
+        session.skip().expect("a phase is running");
+        let _third = session.advance();
+        clock.advance(Duration::minutes(15));
+
+        assert_eq!(
+            session.time_spent(),
+            TimeSpent {
+                work: Duration::minutes(40),
+                breaks: Duration::minutes(2),
+            },
+            "time after the end of a phase shouldn't count"
+        );
+        assert_eq!(session.time_spent().break_percentage(), Some(5.0));
+    }
+
+    #[test]
+    fn roulette() {
+        use rand::SeedableRng;
+
+        let config = SessionConfig::default().work(25).short(5).long(15);
+        let mut session = config.build(UserId(1));
+        session.set_roulette(Some(StdRng::seed_from_u64(1)));
+
+        for _ in 0..(config.interval * 2 * 3) {
+            let _phase = session.advance();
+
+            match session.current_phase_type() {
+                Some(PhaseType::Work(minutes)) => {
+                    assert!(
+                        ROULETTE_MINUTES.contains(&minutes),
+                        "{} is out of range",
+                        minutes
+                    );
+                    assert_eq!(session.rolled(), Some(minutes));
+                }
+                Some(phase_type) => {
+                    assert!(
+                        matches!(phase_type, PhaseType::Short(5) | PhaseType::Long(15)),
+                        "break lengths should be fixed, got {:?}",
+                        phase_type
+                    );
+                    assert_eq!(session.rolled(), None);
+                }
+                None => panic!("no phase is running"),
+            }
+        }
+
+        let _work = session.advance();
+        assert!(session.rolled().is_some());
+
+        session.break_now(5).expect("a phase is running");
+        let _break = session.advance();
+        let _resumed = session.advance();
+        assert_eq!(
+            session.rolled(),
+            None,
+            "a resumed work phase shouldn't be rolled again"
+        );
+    }
+
+    #[test]
+    fn peek_phases_across_long_break() {
+        let config = SessionConfig::default()
+            .work(25)
+            .short(5)
+            .long(15)
+            .interval(2);
+
+        assert_eq!(
+            config.peek_phases(2, 4),
+            vec![
+                PhaseType::Work(25),
+                PhaseType::Long(15),
+                PhaseType::Work(25),
+                PhaseType::Short(5),
+            ]
+        );
+        assert!(config.peek_phases(0, 0).is_empty());
+
+        let mut session = config.build(UserId(1));
+        assert_eq!(
+            session.peek_next_phases(4),
+            config.peek_phases(0, 4),
+            "a session that hasn't started should start at the beginning"
+        );
+
+        let _first = session.advance();
+        let _second = session.advance();
+        let _third = session.advance();
+        assert_eq!(
+            session.peek_next_phases(3),
+            vec![
+                PhaseType::Long(15),
+                PhaseType::Work(25),
+                PhaseType::Short(5),
+            ]
+        );
+        assert_eq!(
+            session.peek_next_phases(3),
+            session.peek_next_phases(3),
+            "peeking shouldn't move the session on"
+        );
+
+        session.break_now(10).expect("a phase is running");
+        assert_eq!(
+            session.peek_next_phases(3),
+            vec![
+                PhaseType::Short(10),
+                PhaseType::Work(25),
+                PhaseType::Long(15),
+            ],
+            "interjected phases should come first"
+        );
+    }
+
+    #[test]
+    fn first_phase() {
+        let mut session = SessionConfig::default().build(UserId(1));
+        assert!(!session.is_first_phase(), "no phase has started yet");
+
+        let _first = session.advance();
+        assert!(session.is_first_phase());
+
+        session.skip().expect("a phase is running");
+        assert!(!session.is_first_phase(), "the first phase was skipped");
+
+        let _second = session.advance();
+        assert!(!session.is_first_phase());
+
+        session.reset_sequence().expect("a phase is running");
+        let _restarted = session.advance();
+        assert!(
+            !session.is_first_phase(),
+            "restarting the sequence doesn't make it the first phase again"
+        );
+    }
+
+    #[test]
+    fn planned_and_actual_length() {
+        let clock = Arc::new(FakeClock::new());
+        let mut session = SessionConfig::default().work(25).build(UserId(1));
+        session.set_clock(clock.clone());
+
+        let lengths = |session: &Session| match session.status() {
+            SessionStatus::Running {
+                phase_planned,
+                phase_length,
+                ..
+            } => (phase_planned, phase_length),
+            SessionStatus::NoSession => panic!("session isn't running"),
+        };
+
+        let _work = session.advance();
+        assert_eq!(
+            lengths(&session),
+            (Duration::minutes(25), Duration::minutes(25)),
+            "a phase that hasn't been changed should run for as long as planned"
+        );
+
+        clock.advance(Duration::minutes(10));
+        session.break_now(5).expect("a phase is running");
+        let _break = session.advance();
+        clock.advance(Duration::minutes(5));
+        let _resumed = session.advance();
+
+        assert_eq!(
+            lengths(&session),
+            (Duration::minutes(25), Duration::minutes(15)),
+            "a resumed phase should only run for what was left of it"
+        );
+    }
+
+    #[test]
+    fn phase_numbers() {
+        let mut session = SessionConfig::default().build(UserId(1));
+        assert_eq!(session.phase_number(), None, "nothing has started yet");
+
+        let numbers = (0..4)
+            .map(|_| {
+                let phase = session.advance();
+                (*phase.phase_type(), session.phase_number())
+            })
+            .collect::<Vec<_>>();
+        let config = session.config().clone();
+
+        assert_eq!(
+            numbers,
+            vec![
+                (PhaseType::Work(config.work), Some(1)),
+                (PhaseType::Short(config.short), Some(1)),
+                (PhaseType::Work(config.work), Some(2)),
+                (PhaseType::Short(config.short), Some(2)),
+            ]
+        );
+
+        let _third = session.advance();
+        session.break_now(3).expect("a phase is running");
+        let _break = session.advance();
+        assert_eq!(
+            session.phase_number(),
+            None,
+            "an interjected break isn't part of the sequence"
+        );
+
+        let _resumed = session.advance();
+        assert_eq!(
+            session.phase_number(),
+            Some(3),
+            "a resumed work phase should keep its number"
+        );
+    }
 }
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use poise::serenity_prelude::ChannelId;
+
+use crate::pomo::session::Session;
+
+/// Storage for the session running in each channel.
+///
+/// Code that uses sessions should go through this trait rather than any
+/// particular kind of storage, so that the backend can be swapped out (e.g.
+/// for one that persists sessions) without having to change it. The default
+/// backend is a [`HashMap`], which keeps sessions in memory.
+pub trait SessionStore: Send + Sync {
+    /// Get the session running in `channel`, if there is one.
+    fn get(&self, channel: &ChannelId) -> Option<&Session>;
+
+    /// Get the session running in `channel` mutably, if there is one.
+    fn get_mut(&mut self, channel: &ChannelId) -> Option<&mut Session>;
+
+    /// Store `session` as the session running in `channel`, returning the
+    /// session that was previously stored there, if there was one.
+    fn insert(&mut self, channel: ChannelId, session: Session) -> Option<Session>;
+
+    /// Remove and return the session running in `channel`, if there is one.
+    fn remove(&mut self, channel: &ChannelId) -> Option<Session>;
+
+    /// Iterate over every stored session, along with the channel it's running
+    /// in.
+    fn iter(&self) -> Box<dyn Iterator<Item = (&ChannelId, &Session)> + '_>;
+
+    /// Returns whether there is a session running in `channel`.
+    fn contains_key(&self, channel: &ChannelId) -> bool {
+        self.get(channel).is_some()
+    }
+
+    /// Get the number of stored sessions.
+    fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns whether there are no stored sessions.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl SessionStore for HashMap<ChannelId, Session> {
+    fn get(&self, channel: &ChannelId) -> Option<&Session> {
+        HashMap::get(self, channel)
+    }
+
+    fn get_mut(&mut self, channel: &ChannelId) -> Option<&mut Session> {
+        HashMap::get_mut(self, channel)
+    }
+
+    fn insert(&mut self, channel: ChannelId, session: Session) -> Option<Session> {
+        HashMap::insert(self, channel, session)
+    }
+
+    fn remove(&mut self, channel: &ChannelId) -> Option<Session> {
+        HashMap::remove(self, channel)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (&ChannelId, &Session)> + '_> {
+        Box::new(HashMap::iter(self))
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::UserId;
+
+    use super::*;
+    use crate::pomo::session::SessionConfig;
+
+    #[test]
+    fn in_memory_store() {
+        let mut store: Box<dyn SessionStore> = Box::new(HashMap::new());
+        let channel = ChannelId(1);
+
+        let session = SessionConfig::default().build(UserId(1));
+        let id = session.id();
+
+        assert!(store.insert(channel, session).is_none());
+        assert!(store.contains_key(&channel));
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get(&channel).map(Session::id), Some(id));
+
+        assert_eq!(store.remove(&channel).map(|session| session.id()), Some(id));
+        assert!(store.is_empty());
+    }
+}
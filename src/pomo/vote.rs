@@ -0,0 +1,164 @@
+//! Polling a session's members, so that they can decide things together
+//! instead of leaving everything to whoever happens to run the command.
+
+use std::collections::{HashMap, HashSet};
+
+use poise::serenity_prelude::UserId;
+
+/// How many of the voters need to agree for a vote to pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, poise::SlashChoiceParameter)]
+pub enum Consensus {
+    #[name = "Everyone"]
+    Everyone,
+    #[name = "A majority"]
+    Majority,
+}
+
+impl Default for Consensus {
+    fn default() -> Self {
+        Self::Everyone
+    }
+}
+
+impl Consensus {
+    /// The number of votes in favour needed out of `voters` votes.
+    pub fn needed(self, voters: usize) -> usize {
+        match self {
+            Consensus::Everyone => voters,
+            Consensus::Majority => voters / 2 + 1,
+        }
+    }
+}
+
+/// How a vote turned out, once enough votes are in that nothing else could
+/// change it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed,
+}
+
+/// A yes or no vote between a fixed set of voters, who can change their minds
+/// until it's decided.
+#[derive(Clone, Debug)]
+pub struct Vote {
+    voters: HashSet<UserId>,
+    consensus: Consensus,
+    votes: HashMap<UserId, bool>,
+}
+
+impl Vote {
+    pub fn new(voters: HashSet<UserId>, consensus: Consensus) -> Self {
+        Self {
+            voters,
+            consensus,
+            votes: HashMap::new(),
+        }
+    }
+
+    pub fn consensus(&self) -> Consensus {
+        self.consensus
+    }
+
+    /// Record `user`'s vote, returning whether they're allowed to vote.
+    pub fn cast(&mut self, user: UserId, agree: bool) -> bool {
+        if self.voters.contains(&user) {
+            self.votes.insert(user, agree);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The number of votes for and against so far, and the number of votes
+    /// in favour needed to pass.
+    pub fn tally(&self) -> Tally {
+        let ayes = self.votes.values().filter(|agree| **agree).count();
+
+        Tally {
+            ayes,
+            noes: self.votes.len() - ayes,
+            needed: self.consensus.needed(self.voters.len()),
+            voters: self.voters.len(),
+        }
+    }
+
+    /// Get the outcome of the vote, if it's been decided.
+    pub fn outcome(&self) -> Option<Outcome> {
+        let tally = self.tally();
+
+        if tally.ayes >= tally.needed {
+            Some(Outcome::Passed)
+        } else if tally.voters - tally.noes < tally.needed {
+            Some(Outcome::Failed)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tally {
+    pub ayes: usize,
+    pub noes: usize,
+    pub needed: usize,
+    pub voters: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voters(n: u64) -> HashSet<UserId> {
+        (1..=n).map(UserId).collect()
+    }
+
+    #[test]
+    fn everyone() {
+        let mut vote = Vote::new(voters(3), Consensus::Everyone);
+
+        assert!(vote.cast(UserId(1), true));
+        assert!(vote.cast(UserId(2), true));
+        assert_eq!(vote.outcome(), None);
+
+        assert!(!vote.cast(UserId(4), true), "non-members can't vote");
+        assert_eq!(vote.outcome(), None);
+
+        assert!(vote.cast(UserId(3), false));
+        assert_eq!(vote.outcome(), Some(Outcome::Failed));
+
+        assert!(vote.cast(UserId(3), true), "voters can change their minds");
+        assert_eq!(vote.outcome(), Some(Outcome::Passed));
+        assert_eq!(
+            vote.tally(),
+            Tally {
+                ayes: 3,
+                noes: 0,
+                needed: 3,
+                voters: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn majority() {
+        let mut vote = Vote::new(voters(4), Consensus::Majority);
+
+        vote.cast(UserId(1), true);
+        vote.cast(UserId(2), false);
+        assert_eq!(vote.outcome(), None);
+
+        vote.cast(UserId(3), true);
+        assert_eq!(vote.outcome(), Some(Outcome::Passed));
+
+        let mut vote = Vote::new(voters(4), Consensus::Majority);
+
+        vote.cast(UserId(1), false);
+        vote.cast(UserId(2), false);
+        assert_eq!(
+            vote.outcome(),
+            Some(Outcome::Failed),
+            "a tie isn't a majority"
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Named session configs that users and guilds can save, and then start
+//! sessions from by name.
+
+use std::collections::HashMap;
+
+use poise::serenity_prelude::{GuildId, UserId};
+use thiserror::Error;
+
+use crate::pomo::session::SessionConfig;
+
+/// The longest a preset's name can be.
+pub(crate) const MAX_NAME_LENGTH: usize = 32;
+
+/// The names of presets that are always available, and can't be replaced.
+const BUILT_IN: &[&str] = &["default"];
+
+/// Who can use a preset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, poise::SlashChoiceParameter)]
+pub enum PresetScope {
+    /// Only the user who saved it.
+    #[name = "Just me"]
+    User,
+    /// Everyone in the guild it was saved in.
+    #[name = "This server"]
+    Guild,
+}
+
+impl Default for PresetScope {
+    fn default() -> Self {
+        Self::User
+    }
+}
+
+/// Who a saved preset belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PresetOwner {
+    User(UserId),
+    Guild(GuildId),
+}
+
+impl PresetOwner {
+    pub fn scope(&self) -> PresetScope {
+        match self {
+            PresetOwner::User(_) => PresetScope::User,
+            PresetOwner::Guild(_) => PresetScope::Guild,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PresetError {
+    #[error(
+        "preset names must be 1 to {} letters, numbers, dashes or underscores",
+        MAX_NAME_LENGTH
+    )]
+    InvalidName,
+    #[error("{0} is a built-in preset, so it can't be changed")]
+    BuiltIn(String),
+}
+
+/// A preset that can be used by someone, as listed by [`Presets::available`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AvailablePreset {
+    pub name: String,
+    /// Who can use the preset, or `None` if it's built in.
+    pub scope: Option<PresetScope>,
+    pub config: SessionConfig,
+}
+
+/// Every saved preset, by who it belongs to and its name.
+#[derive(Debug, Default)]
+pub struct Presets {
+    saved: HashMap<PresetOwner, HashMap<String, SessionConfig>>,
+}
+
+impl Presets {
+    /// Save `config` as the preset called `name` belonging to `owner`,
+    /// returning the config it replaced if there was already one with that
+    /// name.
+    pub fn save(
+        &mut self,
+        owner: PresetOwner,
+        name: &str,
+        config: SessionConfig,
+    ) -> Result<Option<SessionConfig>, PresetError> {
+        let name = normalize_name(name)?;
+
+        if BUILT_IN.contains(&name.as_str()) {
+            return Err(PresetError::BuiltIn(name));
+        }
+
+        Ok(self.saved.entry(owner).or_default().insert(name, config))
+    }
+
+    /// Delete the preset called `name` belonging to `owner`, returning its
+    /// config if it existed.
+    pub fn delete(
+        &mut self,
+        owner: PresetOwner,
+        name: &str,
+    ) -> Result<Option<SessionConfig>, PresetError> {
+        let name = normalize_name(name)?;
+
+        if BUILT_IN.contains(&name.as_str()) {
+            return Err(PresetError::BuiltIn(name));
+        }
+
+        Ok(self
+            .saved
+            .get_mut(&owner)
+            .and_then(|presets| presets.remove(&name)))
+    }
+
+    /// Find the preset called `name` that `user` can use in `guild_id`.
+    ///
+    /// The user's own presets are checked first, then the guild's, and then
+    /// the built-in ones.
+    pub fn get(
+        &self,
+        user: UserId,
+        guild_id: Option<GuildId>,
+        name: &str,
+    ) -> Option<SessionConfig> {
+        let name = normalize_name(name).ok()?;
+
+        owners(user, guild_id)
+            .find_map(|owner| self.saved.get(&owner)?.get(&name).cloned())
+            .or_else(|| built_in(&name))
+    }
+
+    /// List every preset that `user` can use in `guild_id`, in order of name.
+    pub fn available(&self, user: UserId, guild_id: Option<GuildId>) -> Vec<AvailablePreset> {
+        let mut available = BUILT_IN
+            .iter()
+            .filter_map(|name| {
+                Some(AvailablePreset {
+                    name: (*name).to_owned(),
+                    scope: None,
+                    config: built_in(name)?,
+                })
+            })
+            .chain(owners(user, guild_id).flat_map(|owner| {
+                self.saved
+                    .get(&owner)
+                    .into_iter()
+                    .flatten()
+                    .map(move |(name, config)| AvailablePreset {
+                        name: name.clone(),
+                        scope: Some(owner.scope()),
+                        config: config.clone(),
+                    })
+            }))
+            .collect::<Vec<_>>();
+
+        available.sort_by(|a, b| a.name.cmp(&b.name));
+        available
+    }
+}
+
+/// The owners of the presets that `user` can use in `guild_id`, in the order
+/// they should be checked.
+fn owners(user: UserId, guild_id: Option<GuildId>) -> impl Iterator<Item = PresetOwner> {
+    std::iter::once(PresetOwner::User(user)).chain(guild_id.map(PresetOwner::Guild))
+}
+
+fn built_in(name: &str) -> Option<SessionConfig> {
+    match name {
+        "default" => Some(SessionConfig::default()),
+        _ => None,
+    }
+}
+
+/// Check that `name` is a valid preset name, and return it in lowercase, so
+/// that names aren't case sensitive.
+pub(crate) fn normalize_name(name: &str) -> Result<String, PresetError> {
+    let name = name.trim().to_lowercase();
+
+    let valid = !name.is_empty()
+        && name.chars().count() <= MAX_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+
+    if valid {
+        Ok(name)
+    } else {
+        Err(PresetError::InvalidName)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_get_delete() {
+        let mut presets = Presets::default();
+        let user = UserId(1);
+        let guild = GuildId(1);
+        let mine = SessionConfig::default().work(50).short(10);
+        let ours = SessionConfig::default().work(45);
+
+        assert_eq!(
+            presets.save(PresetOwner::User(user), "Mine", mine.clone()),
+            Ok(None)
+        );
+        assert_eq!(
+            presets.save(PresetOwner::Guild(guild), "ours", ours.clone()),
+            Ok(None)
+        );
+
+        assert_eq!(presets.get(user, Some(guild), "mine"), Some(mine.clone()));
+        assert_eq!(presets.get(user, Some(guild), "ours"), Some(ours));
+        assert_eq!(
+            presets.get(UserId(2), Some(guild), "mine"),
+            None,
+            "other users shouldn't see personal presets"
+        );
+        assert_eq!(presets.get(user, None, "ours"), None);
+        assert_eq!(
+            presets.get(user, None, "default"),
+            Some(SessionConfig::default())
+        );
+
+        assert_eq!(
+            presets.save(PresetOwner::User(user), "ours", mine.clone()),
+            Ok(None)
+        );
+        assert_eq!(
+            presets.get(user, Some(guild), "ours"),
+            Some(mine.clone()),
+            "personal presets should come before guild presets"
+        );
+
+        assert_eq!(
+            presets.delete(PresetOwner::User(user), "MINE"),
+            Ok(Some(mine))
+        );
+        assert_eq!(presets.get(user, Some(guild), "mine"), None);
+        assert_eq!(presets.delete(PresetOwner::User(user), "mine"), Ok(None));
+    }
+
+    #[test]
+    fn invalid_names() {
+        let mut presets = Presets::default();
+        let owner = PresetOwner::User(UserId(1));
+
+        for name in [
+            "",
+            "   ",
+            "has space",
+            "semi;colon",
+            "x".repeat(33).as_str(),
+        ] {
+            assert_eq!(
+                presets.save(owner, name, SessionConfig::default()),
+                Err(PresetError::InvalidName),
+                "{:?} should be invalid",
+                name
+            );
+        }
+
+        assert_eq!(
+            presets.save(owner, "Default", SessionConfig::default()),
+            Err(PresetError::BuiltIn("default".to_owned()))
+        );
+        assert_eq!(
+            presets.delete(owner, "default"),
+            Err(PresetError::BuiltIn("default".to_owned()))
+        );
+    }
+
+    #[test]
+    fn available() {
+        let mut presets = Presets::default();
+        let user = UserId(1);
+        let guild = GuildId(1);
+
+        presets
+            .save(PresetOwner::Guild(guild), "b", SessionConfig::default())
+            .unwrap();
+        presets
+            .save(PresetOwner::User(user), "a", SessionConfig::default())
+            .unwrap();
+        presets
+            .save(PresetOwner::User(UserId(2)), "c", SessionConfig::default())
+            .unwrap();
+
+        let available = presets
+            .available(user, Some(guild))
+            .into_iter()
+            .map(|preset| (preset.name, preset.scope))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            available,
+            vec![
+                ("a".to_owned(), Some(PresetScope::User)),
+                ("b".to_owned(), Some(PresetScope::Guild)),
+                ("default".to_owned(), None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashSet;
+
+use chrono::Duration;
+use chrono_tz::{Tz, UTC};
+use poise::serenity_prelude::{ChannelId, RoleId};
+
+use crate::stats::StatsMode;
+
+/// Who to ping when a session's phase changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pings {
+    /// Each member of the session, one by one.
+    Members,
+    /// A role, because the session has too many members to ping one by one.
+    Role(RoleId),
+    /// Nobody, because the session has too many members to ping one by one
+    /// and there's no role to ping instead.
+    Nobody,
+}
+
+/// Settings that the admins of a guild can change to control how pomocop
+/// behaves in that guild.
+#[derive(Clone, Debug)]
+pub struct GuildSettings {
+    /// How long after a session in a channel ends before another one can be
+    /// started in the same channel.
+    pub start_cooldown: Duration,
+    /// How long to wait before deleting confirmations that everyone can see
+    /// (like the reply to `/skip`), or `None` to never delete them.
+    pub tidy_delay: Option<Duration>,
+    /// How work phases that don't run to completion count towards focus time.
+    pub stats_mode: StatsMode,
+    /// Whether commands used in a thread act on the session in its parent
+    /// channel, instead of the thread having its own session.
+    pub share_thread_sessions: bool,
+    /// Whether only one session can run in the whole guild at a time, instead
+    /// of one in each channel.
+    pub one_session_per_guild: bool,
+    /// The percentage of work phases that start with a productivity tip.
+    pub work_tip_percent: u32,
+    /// What `/skip` does when it's used on the first phase of a session.
+    pub first_skip: FirstSkip,
+    /// The channel to log uses of admin commands to, or `None` to not log
+    /// them.
+    pub audit_channel: Option<ChannelId>,
+    /// The channel to keep a running log of every session in the guild in, or
+    /// `None` to not keep one.
+    pub transcript_channel: Option<ChannelId>,
+    /// The most members a session can ping one by one when its phase changes,
+    /// or `None` for no limit.
+    pub mention_limit: Option<usize>,
+    /// The role to ping instead once a session has more members than
+    /// `mention_limit`, or `None` to not ping anyone then.
+    pub mention_role: Option<RoleId>,
+    /// The time zone to show times in when a user doesn't give their own.
+    pub timezone: Tz,
+    /// Whether to post a summary of each day's focus time in channels that
+    /// had sessions that day.
+    #[cfg(feature = "daily-stats")]
+    pub daily_stats: bool,
+}
+
+impl Default for GuildSettings {
+    fn default() -> Self {
+        Self {
+            start_cooldown: Duration::seconds(30),
+            tidy_delay: None,
+            stats_mode: StatsMode::default(),
+            share_thread_sessions: false,
+            one_session_per_guild: false,
+            work_tip_percent: 20,
+            first_skip: FirstSkip::default(),
+            audit_channel: None,
+            transcript_channel: None,
+            mention_limit: None,
+            mention_role: None,
+            timezone: UTC,
+            #[cfg(feature = "daily-stats")]
+            daily_stats: false,
+        }
+    }
+}
+
+impl GuildSettings {
+    /// Get who to ping when the phase of a session with `members` members to
+    /// ping changes.
+    pub fn pings_for(&self, members: usize) -> Pings {
+        match self.mention_limit {
+            Some(limit) if members > limit => self.mention_role.map_or(Pings::Nobody, Pings::Role),
+            _ => Pings::Members,
+        }
+    }
+
+    /// Get the time zone to show times in for a user who asked for
+    /// `requested`, falling back to the guild's time zone if they didn't ask
+    /// for one or it isn't a real time zone.
+    pub fn timezone_for(&self, requested: Option<&str>) -> Tz {
+        requested
+            .and_then(|requested| requested.trim().parse().ok())
+            .unwrap_or(self.timezone)
+    }
+
+    /// Get the channel whose session commands used in `channel_id` should act
+    /// on, given the parent channel of `channel_id` if it's a thread.
+    pub fn session_channel(
+        &self,
+        channel_id: ChannelId,
+        thread_parent: Option<ChannelId>,
+    ) -> ChannelId {
+        match thread_parent {
+            Some(parent) if self.share_thread_sessions => parent,
+            _ => channel_id,
+        }
+    }
+
+    /// Find the channel of a running session that stops a new one being
+    /// started in `channel_id`, if there is one.
+    ///
+    /// `running` is every channel with a session running in it, and
+    /// `guild_channels` is every channel in the guild, which only matters if
+    /// there can only be one session in the guild.
+    pub fn conflicting_session<I>(
+        &self,
+        channel_id: ChannelId,
+        running: I,
+        guild_channels: &HashSet<ChannelId>,
+    ) -> Option<ChannelId>
+    where
+        I: IntoIterator<Item = ChannelId>,
+    {
+        let running = running.into_iter().collect::<Vec<_>>();
+
+        if running.contains(&channel_id) {
+            Some(channel_id)
+        } else if self.one_session_per_guild {
+            running
+                .into_iter()
+                .find(|running| guild_channels.contains(running))
+        } else {
+            None
+        }
+    }
+}
+
+/// What `/skip` does when it's used on the very first phase of a session, which
+/// usually means whoever used it hadn't actually started working yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, poise::SlashChoiceParameter)]
+pub enum FirstSkip {
+    /// Don't skip it unless the skip is confirmed.
+    #[name = "Ask for confirmation"]
+    Confirm,
+    /// Skip it, but comment on it.
+    #[name = "Skip, with a comment"]
+    Comment,
+    /// Skip it like any other phase.
+    #[name = "Skip like any other phase"]
+    Normal,
+}
+
+impl Default for FirstSkip {
+    fn default() -> Self {
+        Self::Confirm
+    }
+}
+
+/// How to respond to `/skip`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipResponse {
+    /// Skip the phase.
+    Skip,
+    /// Skip the phase, and point out that it was the first one.
+    SkipFirst,
+    /// Don't skip the phase, and ask for the skip to be confirmed.
+    AskToConfirm,
+}
+
+impl FirstSkip {
+    /// Get how to respond to `/skip`, given whether it was used on the first
+    /// phase of a session and whether the skip was confirmed.
+    pub fn response(self, first_phase: bool, confirmed: bool) -> SkipResponse {
+        match (self, first_phase, confirmed) {
+            (_, false, _) | (FirstSkip::Normal, _, _) => SkipResponse::Skip,
+            (FirstSkip::Confirm, true, false) => SkipResponse::AskToConfirm,
+            (FirstSkip::Confirm, true, true) | (FirstSkip::Comment, true, _) => {
+                SkipResponse::SkipFirst
+            }
+        }
+    }
+}
+
+/// Settings that each user can change for themselves.
+#[derive(Clone, Debug)]
+pub struct UserSettings {
+    /// Whether the host of a session can add this user to it, rather than the
+    /// user having to join it themselves.
+    pub auto_enroll: bool,
+    /// Whether to send this user a summary of what they took part in when a
+    /// session they're in ends.
+    pub accountability: bool,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            auto_enroll: true,
+            accountability: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pings_for() {
+        let mut settings = GuildSettings::default();
+        assert_eq!(settings.pings_for(100), Pings::Members);
+
+        settings.mention_limit = Some(5);
+        assert_eq!(settings.pings_for(5), Pings::Members);
+        assert_eq!(settings.pings_for(6), Pings::Nobody);
+
+        settings.mention_role = Some(RoleId(1));
+        assert_eq!(settings.pings_for(5), Pings::Members);
+        assert_eq!(settings.pings_for(6), Pings::Role(RoleId(1)));
+    }
+
+    #[test]
+    fn session_channel() {
+        let thread = ChannelId(2);
+        let parent = ChannelId(1);
+
+        let mut settings = GuildSettings::default();
+        assert_eq!(settings.session_channel(thread, Some(parent)), thread);
+        assert_eq!(settings.session_channel(parent, None), parent);
+
+        settings.share_thread_sessions = true;
+        assert_eq!(settings.session_channel(thread, Some(parent)), parent);
+        assert_eq!(settings.session_channel(parent, None), parent);
+    }
+
+    #[test]
+    fn conflicting_session() {
+        let channel = ChannelId(1);
+        let other = ChannelId(2);
+        let elsewhere = ChannelId(3);
+        let guild_channels = vec![channel, other].into_iter().collect::<HashSet<_>>();
+
+        let mut settings = GuildSettings::default();
+        assert_eq!(
+            settings.conflicting_session(channel, [channel], &guild_channels),
+            Some(channel)
+        );
+        assert_eq!(
+            settings.conflicting_session(channel, [other, elsewhere], &guild_channels),
+            None
+        );
+
+        settings.one_session_per_guild = true;
+        assert_eq!(
+            settings.conflicting_session(channel, [other, channel], &guild_channels),
+            Some(channel),
+            "a session in the same channel should be reported first"
+        );
+        assert_eq!(
+            settings.conflicting_session(channel, [other, elsewhere], &guild_channels),
+            Some(other)
+        );
+        assert_eq!(
+            settings.conflicting_session(channel, [elsewhere], &guild_channels),
+            None
+        );
+    }
+
+    #[test]
+    fn first_skip_response() {
+        assert_eq!(
+            FirstSkip::Confirm.response(true, false),
+            SkipResponse::AskToConfirm
+        );
+        assert_eq!(
+            FirstSkip::Confirm.response(true, true),
+            SkipResponse::SkipFirst
+        );
+        assert_eq!(
+            FirstSkip::Comment.response(true, false),
+            SkipResponse::SkipFirst
+        );
+        assert_eq!(FirstSkip::Normal.response(true, false), SkipResponse::Skip);
+
+        for first_skip in [FirstSkip::Confirm, FirstSkip::Comment, FirstSkip::Normal] {
+            assert_eq!(
+                first_skip.response(false, false),
+                SkipResponse::Skip,
+                "{:?} shouldn't affect later phases",
+                first_skip
+            );
+        }
+    }
+
+    #[test]
+    fn timezone_fallback() {
+        let mut settings = GuildSettings::default();
+        assert_eq!(settings.timezone_for(None), UTC);
+
+        settings.timezone = chrono_tz::Europe::Berlin;
+        assert_eq!(settings.timezone_for(None), chrono_tz::Europe::Berlin);
+        assert_eq!(
+            settings.timezone_for(Some("Not/AZone")),
+            chrono_tz::Europe::Berlin
+        );
+        assert_eq!(
+            settings.timezone_for(Some("America/New_York")),
+            chrono_tz::America::New_York,
+            "a user's own time zone should come first"
+        );
+    }
+}
@@ -0,0 +1,432 @@
+//! Tracking how much focus time members put in.
+
+use std::{cmp::Reverse, collections::HashMap};
+
+use chrono::{DateTime, Duration, Utc};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+
+use crate::pomo::session::{PhaseResult, PhaseType};
+
+/// How work phases that don't run to completion count towards focus time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatsMode {
+    /// Only work phases that complete count, for their full length.
+    Strict,
+    /// Work phases that are skipped or stopped also count, for as many whole
+    /// minutes as they ran for.
+    Partial,
+}
+
+impl StatsMode {
+    /// Get how many minutes of focus time a phase that finished with `result`
+    /// is worth, or `None` if it isn't worth any.
+    pub fn credit(&self, result: &PhaseResult) -> Option<usize> {
+        match (self, result) {
+            (_, PhaseResult::Completed(PhaseType::Work(minutes))) => Some(*minutes),
+            (
+                StatsMode::Partial,
+                PhaseResult::Skipped(PhaseType::Work(_), elapsed)
+                | PhaseResult::Stopped(PhaseType::Work(_), elapsed),
+            ) => Some(elapsed.num_minutes())
+                .filter(|minutes| *minutes > 0)
+                .map(|minutes| minutes as usize),
+            _ => None,
+        }
+    }
+}
+
+impl Default for StatsMode {
+    fn default() -> Self {
+        Self::Strict
+    }
+}
+
+/// A member being credited with the time they spent in a work phase.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FocusRecord {
+    /// When the work phase finished.
+    pub at: DateTime<Utc>,
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    pub user_id: UserId,
+    pub minutes: usize,
+}
+
+/// Focus time recorded across every channel.
+///
+/// Records are kept with the time they were made, so that totals for any
+/// period can be worked out from them after the fact, rather than relying on
+/// counters being reset at exactly the right moment. Once a day has been
+/// summarised, records from before it are dropped.
+#[derive(Debug, Default)]
+pub struct Stats {
+    records: Vec<FocusRecord>,
+    /// The end of the most recent day that has been summarised.
+    last_reset: Option<DateTime<Utc>>,
+}
+
+impl Stats {
+    pub fn record(&mut self, record: FocusRecord) {
+        self.records.push(record);
+    }
+
+    pub fn last_reset(&self) -> Option<DateTime<Utc>> {
+        self.last_reset
+    }
+
+    /// Mark every record made before `at` as belonging to a day that has
+    /// already been summarised, and drop the ones from before that day, which
+    /// nothing needs any more.
+    pub fn reset(&mut self, at: DateTime<Utc>) {
+        let keep_from = at - Duration::days(1);
+        self.records.retain(|record| record.at >= keep_from);
+        self.last_reset = Some(at);
+    }
+
+    /// Delete every record of `user`'s focus time, returning how many minutes
+    /// it added up to, or `None` if there wasn't any.
+    pub fn delete_user(&mut self, user: UserId) -> Option<usize> {
+        self.delete_where(|record| record.user_id == user)
+    }
+
+    /// Delete every record of focus time in `guild`, returning how many
+    /// minutes it added up to, or `None` if there wasn't any.
+    pub fn delete_guild(&mut self, guild: GuildId) -> Option<usize> {
+        self.delete_where(|record| record.guild_id == Some(guild))
+    }
+
+    fn delete_where<P>(&mut self, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(&FocusRecord) -> bool,
+    {
+        let mut minutes = None;
+
+        self.records.retain(|record| {
+            if predicate(record) {
+                *minutes.get_or_insert(0) += record.minutes;
+                false
+            } else {
+                true
+            }
+        });
+
+        minutes
+    }
+
+    /// Summarise the focus time recorded in each channel between `from`
+    /// (inclusive) and `to` (exclusive).
+    ///
+    /// Channels with nothing recorded in that period are left out. Summaries
+    /// are ordered by channel.
+    pub fn summaries(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<StatsSummary> {
+        let mut channels: HashMap<ChannelId, (Option<GuildId>, HashMap<UserId, usize>)> =
+            HashMap::new();
+
+        for record in self
+            .records
+            .iter()
+            .filter(|record| from <= record.at && record.at < to)
+        {
+            let (_, members) = channels
+                .entry(record.channel_id)
+                .or_insert_with(|| (record.guild_id, HashMap::new()));
+
+            *members.entry(record.user_id).or_default() += record.minutes;
+        }
+
+        let mut summaries = channels
+            .into_iter()
+            .map(|(channel_id, (guild_id, members))| StatsSummary {
+                guild_id,
+                channel_id,
+                total_minutes: members.values().sum(),
+                // Ties go to whoever has the lowest ID, just so the result is
+                // the same every time.
+                top_contributor: members
+                    .into_iter()
+                    .max_by_key(|&(user_id, minutes)| (minutes, Reverse(user_id))),
+            })
+            .collect::<Vec<_>>();
+
+        summaries.sort_by_key(|summary| summary.channel_id);
+        summaries
+    }
+}
+
+/// What each member of a single session took part in, for the summaries sent
+/// to members who want them when it ends.
+#[derive(Debug, Default)]
+pub struct Participation {
+    /// How many phases of the session have finished.
+    phases: usize,
+    members: HashMap<UserId, MemberParticipation>,
+}
+
+/// What one member of a session took part in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemberParticipation {
+    /// How many of the session's phases they were a member for when the phase
+    /// finished.
+    pub phases: usize,
+    pub focus_minutes: usize,
+}
+
+impl Participation {
+    /// Record that a phase finished while `members` were in the session.
+    pub fn record_phase<'a, I>(&mut self, members: I)
+    where
+        I: IntoIterator<Item = &'a UserId>,
+    {
+        self.phases += 1;
+
+        for &member in members {
+            self.members.entry(member).or_default().phases += 1;
+        }
+    }
+
+    /// Credit each of `members` with `minutes` of focus time.
+    pub fn credit<'a, I>(&mut self, members: I, minutes: usize)
+    where
+        I: IntoIterator<Item = &'a UserId>,
+    {
+        for &member in members {
+            self.members.entry(member).or_default().focus_minutes += minutes;
+        }
+    }
+
+    /// Get how many phases of the session have finished.
+    pub fn phases(&self) -> usize {
+        self.phases
+    }
+
+    /// Get what `member` took part in, which is nothing if they were never
+    /// there when a phase finished.
+    pub fn member(&self, member: UserId) -> MemberParticipation {
+        self.members.get(&member).copied().unwrap_or_default()
+    }
+
+    /// Get everyone who was a member when at least one phase finished.
+    pub fn members(&self) -> impl Iterator<Item = UserId> + '_ {
+        self.members.keys().copied()
+    }
+}
+
+impl MemberParticipation {
+    /// Get the percentage of the `total` phases of the session that this
+    /// member was there for, or 100 if there weren't any.
+    pub fn completion_percent(&self, total: usize) -> usize {
+        if total == 0 {
+            100
+        } else {
+            self.phases * 100 / total
+        }
+    }
+}
+
+/// The focus time recorded in a channel over some period.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatsSummary {
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    /// The total focus minutes of every member.
+    pub total_minutes: usize,
+    /// The member with the most focus minutes, and how many they had.
+    pub top_contributor: Option<(UserId, usize)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(at: DateTime<Utc>, channel: u64, user: u64, minutes: usize) -> FocusRecord {
+        FocusRecord {
+            at,
+            guild_id: Some(GuildId(1)),
+            channel_id: ChannelId(channel),
+            user_id: UserId(user),
+            minutes,
+        }
+    }
+
+    #[test]
+    fn summaries() {
+        let to = Utc::now();
+        let from = to - Duration::days(1);
+
+        let mut stats = Stats::default();
+        stats.record(record(from - Duration::minutes(1), 1, 1, 100));
+        stats.record(record(from, 1, 1, 25));
+        stats.record(record(from + Duration::hours(1), 1, 2, 25));
+        stats.record(record(from + Duration::hours(2), 1, 2, 25));
+        stats.record(record(from + Duration::hours(3), 2, 3, 50));
+        stats.record(record(to, 2, 3, 100));
+
+        assert_eq!(
+            stats.summaries(from, to),
+            vec![
+                StatsSummary {
+                    guild_id: Some(GuildId(1)),
+                    channel_id: ChannelId(1),
+                    total_minutes: 75,
+                    top_contributor: Some((UserId(2), 50)),
+                },
+                StatsSummary {
+                    guild_id: Some(GuildId(1)),
+                    channel_id: ChannelId(2),
+                    total_minutes: 50,
+                    top_contributor: Some((UserId(3), 50)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let reset = Utc::now();
+        let from = reset - Duration::days(1);
+
+        let mut stats = Stats::default();
+        stats.record(record(from - Duration::minutes(1), 1, 1, 100));
+        stats.record(record(from, 1, 1, 25));
+        stats.record(record(reset, 1, 1, 50));
+
+        stats.reset(reset);
+
+        assert_eq!(stats.last_reset(), Some(reset));
+        assert_eq!(
+            stats.records,
+            vec![record(from, 1, 1, 25), record(reset, 1, 1, 50)],
+            "records from before the day that was just summarised should be dropped"
+        );
+    }
+
+    #[test]
+    fn delete() {
+        let now = Utc::now();
+
+        let mut stats = Stats::default();
+        stats.record(record(now, 1, 1, 25));
+        stats.record(record(now, 2, 1, 50));
+        stats.record(record(now, 1, 2, 25));
+        stats.record(FocusRecord {
+            guild_id: Some(GuildId(2)),
+            ..record(now, 3, 3, 25)
+        });
+
+        assert_eq!(stats.delete_user(UserId(1)), Some(75));
+        assert_eq!(stats.delete_user(UserId(1)), None);
+        assert_eq!(
+            stats.summaries(now, now + Duration::minutes(1)),
+            vec![
+                StatsSummary {
+                    guild_id: Some(GuildId(1)),
+                    channel_id: ChannelId(1),
+                    total_minutes: 25,
+                    top_contributor: Some((UserId(2), 25)),
+                },
+                StatsSummary {
+                    guild_id: Some(GuildId(2)),
+                    channel_id: ChannelId(3),
+                    total_minutes: 25,
+                    top_contributor: Some((UserId(3), 25)),
+                },
+            ]
+        );
+
+        assert_eq!(stats.delete_guild(GuildId(1)), Some(25));
+        assert_eq!(stats.delete_guild(GuildId(1)), None);
+        assert_eq!(
+            stats.summaries(now, now + Duration::minutes(1)).len(),
+            1,
+            "other guilds' stats should be left alone"
+        );
+    }
+
+    #[test]
+    fn strict_credit() {
+        let mode = StatsMode::Strict;
+
+        assert_eq!(
+            mode.credit(&PhaseResult::Completed(PhaseType::Work(25))),
+            Some(25)
+        );
+        assert_eq!(
+            mode.credit(&PhaseResult::Skipped(
+                PhaseType::Work(25),
+                Duration::minutes(10)
+            )),
+            None,
+            "skipped work counted in strict mode"
+        );
+        assert_eq!(
+            mode.credit(&PhaseResult::Completed(PhaseType::Short(5))),
+            None
+        );
+    }
+
+    #[test]
+    fn partial_credit() {
+        let mode = StatsMode::Partial;
+
+        assert_eq!(
+            mode.credit(&PhaseResult::Completed(PhaseType::Work(25))),
+            Some(25)
+        );
+        assert_eq!(
+            mode.credit(&PhaseResult::Skipped(
+                PhaseType::Work(25),
+                Duration::seconds(10 * 60 + 59)
+            )),
+            Some(10),
+            "skipped work should count for whole minutes elapsed"
+        );
+        assert_eq!(
+            mode.credit(&PhaseResult::Skipped(
+                PhaseType::Work(25),
+                Duration::seconds(59)
+            )),
+            None
+        );
+        assert_eq!(
+            mode.credit(&PhaseResult::Skipped(
+                PhaseType::Long(15),
+                Duration::minutes(10)
+            )),
+            None,
+            "skipped breaks should never count"
+        );
+        assert_eq!(
+            mode.credit(&PhaseResult::Stopped(
+                PhaseType::Work(25),
+                Duration::minutes(20)
+            )),
+            Some(20),
+            "stopped work should count for whole minutes elapsed"
+        );
+    }
+
+    #[test]
+    fn participation() {
+        let mut participation = Participation::default();
+
+        participation.record_phase(&[UserId(1), UserId(2)]);
+        participation.credit(&[UserId(1), UserId(2)], 25);
+        participation.record_phase(&[UserId(1)]);
+        participation.record_phase(&[UserId(1)]);
+
+        assert_eq!(participation.phases(), 3);
+        assert_eq!(
+            participation.member(UserId(1)),
+            MemberParticipation {
+                phases: 3,
+                focus_minutes: 25,
+            }
+        );
+        assert_eq!(participation.member(UserId(2)).completion_percent(3), 33);
+        assert_eq!(
+            participation.member(UserId(3)),
+            MemberParticipation::default()
+        );
+        assert_eq!(MemberParticipation::default().completion_percent(0), 100);
+    }
+}
@@ -0,0 +1,131 @@
+//! Session configs curated by a guild's admins, which anyone in the guild can
+//! start sessions from but only admins can change.
+
+use std::collections::{BTreeMap, HashMap};
+
+use poise::serenity_prelude::GuildId;
+use thiserror::Error;
+
+use crate::{
+    pomo::session::SessionConfig,
+    presets::{normalize_name, MAX_NAME_LENGTH},
+};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error(
+        "template names must be 1 to {} letters, numbers, dashes or underscores",
+        MAX_NAME_LENGTH
+    )]
+    InvalidName,
+}
+
+/// Every guild's templates, by name.
+#[derive(Debug, Default)]
+pub struct Templates {
+    saved: HashMap<GuildId, BTreeMap<String, SessionConfig>>,
+}
+
+impl Templates {
+    /// Save `config` as the template called `name` in `guild_id`, returning the
+    /// config it replaced if there was already one with that name.
+    pub fn save(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+        config: SessionConfig,
+    ) -> Result<Option<SessionConfig>, TemplateError> {
+        let name = normalize_name(name).map_err(|_| TemplateError::InvalidName)?;
+
+        Ok(self.saved.entry(guild_id).or_default().insert(name, config))
+    }
+
+    /// Delete the template called `name` in `guild_id`, returning its config if
+    /// it existed.
+    pub fn delete(
+        &mut self,
+        guild_id: GuildId,
+        name: &str,
+    ) -> Result<Option<SessionConfig>, TemplateError> {
+        let name = normalize_name(name).map_err(|_| TemplateError::InvalidName)?;
+
+        Ok(self
+            .saved
+            .get_mut(&guild_id)
+            .and_then(|templates| templates.remove(&name)))
+    }
+
+    /// Find the template called `name` in `guild_id`.
+    pub fn get(&self, guild_id: GuildId, name: &str) -> Option<SessionConfig> {
+        let name = normalize_name(name).ok()?;
+
+        self.saved.get(&guild_id)?.get(&name).cloned()
+    }
+
+    /// List every template in `guild_id`, in order of name.
+    pub fn list(&self, guild_id: GuildId) -> Vec<(String, SessionConfig)> {
+        self.saved
+            .get(&guild_id)
+            .into_iter()
+            .flatten()
+            .map(|(name, config)| (name.clone(), config.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_get_delete() {
+        let mut templates = Templates::default();
+        let guild = GuildId(1);
+        let study = SessionConfig::default().work(50).short(10);
+
+        assert_eq!(templates.save(guild, "Study", study.clone()), Ok(None));
+        assert_eq!(templates.get(guild, "study"), Some(study.clone()));
+        assert_eq!(
+            templates.get(GuildId(2), "study"),
+            None,
+            "templates should only be available in their own guild"
+        );
+
+        assert_eq!(
+            templates.save(guild, "study", SessionConfig::default()),
+            Ok(Some(study))
+        );
+        assert_eq!(
+            templates.save(guild, "has space", SessionConfig::default()),
+            Err(TemplateError::InvalidName)
+        );
+
+        assert_eq!(
+            templates.delete(guild, "STUDY"),
+            Ok(Some(SessionConfig::default()))
+        );
+        assert_eq!(templates.get(guild, "study"), None);
+        assert_eq!(templates.delete(guild, "study"), Ok(None));
+    }
+
+    #[test]
+    fn list_in_order() {
+        let mut templates = Templates::default();
+        let guild = GuildId(1);
+
+        for name in ["exam", "deep-work", "revision"] {
+            templates
+                .save(guild, name, SessionConfig::default())
+                .unwrap();
+        }
+
+        let names = templates
+            .list(guild)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["deep-work", "exam", "revision"]);
+        assert!(templates.list(GuildId(2)).is_empty());
+    }
+}